@@ -0,0 +1,54 @@
+use client_core::ClientSession;
+use std::io;
+use std::time::Instant;
+
+use crate::pattern::InputPattern;
+use crate::report::BotStats;
+
+/// One simulated player: a `ClientSession` of its own, fed by an input pattern instead of
+/// a keyboard.
+pub struct Bot {
+    index: u32,
+    session: ClientSession,
+    pattern: InputPattern,
+    started: Instant,
+}
+
+impl Bot {
+    pub fn connect(
+        index: u32,
+        server_addr: &str,
+        scene_name: &str,
+        delay_millis: u64,
+        pattern: InputPattern,
+    ) -> io::Result<Bot> {
+        Ok(Bot {
+            index,
+            session: ClientSession::connect(server_addr, scene_name, delay_millis)?,
+            pattern,
+            started: Instant::now(),
+        })
+    }
+
+    /// Apply the next snapshot if one arrived, then predict and send one frame of input
+    pub fn frame(&mut self, dt_micros: u64) {
+        let input = self.pattern.input(self.started.elapsed().as_secs_f32());
+        self.session.poll(input, dt_micros);
+    }
+
+    pub fn stats(&self) -> BotStats {
+        let summary = self.session.net_stats().summary();
+        let desync = &self.session.predictor.desync;
+        BotStats {
+            index: self.index,
+            player_id: (self.session.snapshots() > 0).then(|| self.session.client_player_id()),
+            snapshots: self.session.snapshots(),
+            rtt_ms: summary.rtt_ms,
+            snapshots_per_sec: summary.packets_received_per_sec,
+            bytes_sent_per_sec: summary.bytes_sent_per_sec,
+            bytes_received_per_sec: summary.bytes_received_per_sec,
+            desync_checks: desync.checks(),
+            desync_mismatches: desync.mismatches(),
+        }
+    }
+}
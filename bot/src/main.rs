@@ -0,0 +1,163 @@
+//! Headless bots for load and soak testing. Spawns simulated players that connect to a
+//! running server, follow a scripted or random input pattern, and report round trip,
+//! desync and throughput statistics. Exits with status 1 when a threshold is exceeded.
+//!
+//! ```text
+//! cargo run --bin bot -- --bots 20 --duration 60 --pattern random --max-rtt 50
+//! ```
+
+mod bot;
+mod pattern;
+mod report;
+
+use bot::Bot;
+use pattern::{InputPattern, PatternKind};
+use report::{Thresholds, print_report, total_desync_rate};
+use std::process::ExitCode;
+use std::str::FromStr;
+use std::thread::sleep;
+use std::time::{Duration, Instant};
+
+const FRAME_DURATION: Duration = Duration::from_micros(16_667);
+const PROGRESS_INTERVAL: Duration = Duration::from_secs(5);
+
+const USAGE: &str = "Usage: bot [--server ADDR] [--bots N] [--duration SECS] \
+[--pattern script|random] [--seed N] [--delay MS] [--scene NAME] \
+[--max-rtt MS] [--max-desync RATIO] [--min-snapshot-rate PER_SEC]";
+
+struct Config {
+    server_addr: String,
+    bots: u32,
+    duration: Duration,
+    pattern: PatternKind,
+    seed: u64,
+    delay_millis: u64,
+    scene_name: String,
+    thresholds: Thresholds,
+}
+
+impl Config {
+    fn from_args(mut args: impl Iterator<Item = String>) -> Result<Config, String> {
+        let mut config = Config {
+            server_addr: "127.0.0.1:9000".to_string(),
+            bots: 10,
+            duration: Duration::from_secs(60),
+            pattern: PatternKind::Random,
+            seed: 1,
+            delay_millis: 0,
+            scene_name: "scene_3".to_string(),
+            thresholds: Thresholds::default(),
+        };
+
+        while let Some(flag) = args.next() {
+            let value = args
+                .next()
+                .ok_or_else(|| format!("{} needs a value", flag))?;
+            match flag.as_str() {
+                "--server" => config.server_addr = value.clone(),
+                "--bots" => config.bots = parse(&flag, &value)?,
+                "--duration" => config.duration = Duration::from_secs_f32(parse(&flag, &value)?),
+                "--pattern" => {
+                    config.pattern = PatternKind::from_name(&value)
+                        .ok_or_else(|| format!("Unknown pattern {}", value))?
+                }
+                "--seed" => config.seed = parse(&flag, &value)?,
+                "--delay" => config.delay_millis = parse(&flag, &value)?,
+                "--scene" => config.scene_name = value.clone(),
+                "--max-rtt" => config.thresholds.max_rtt_ms = Some(parse(&flag, &value)?),
+                "--max-desync" => config.thresholds.max_desync_rate = Some(parse(&flag, &value)?),
+                "--min-snapshot-rate" => {
+                    config.thresholds.min_snapshots_per_sec = Some(parse(&flag, &value)?)
+                }
+                _ => return Err(format!("Unknown flag {}", flag)),
+            }
+        }
+        Ok(config)
+    }
+}
+
+fn parse<T: FromStr>(flag: &str, value: &str) -> Result<T, String> {
+    value
+        .parse()
+        .map_err(|_| format!("Invalid value for {}: {}", flag, value))
+}
+
+fn main() -> ExitCode {
+    let config = match Config::from_args(std::env::args().skip(1)) {
+        Ok(config) => config,
+        Err(e) => {
+            eprintln!("{}\n{}", e, USAGE);
+            return ExitCode::from(2);
+        }
+    };
+
+    let mut bots = Vec::new();
+    for index in 0..config.bots {
+        let pattern = InputPattern::new(config.pattern, config.seed.wrapping_add(index as u64));
+        match Bot::connect(
+            index,
+            &config.server_addr,
+            &config.scene_name,
+            config.delay_millis,
+            pattern,
+        ) {
+            Ok(bot) => bots.push(bot),
+            Err(e) => {
+                eprintln!("Bot {} couldn't connect: {}", index, e);
+                return ExitCode::from(2);
+            }
+        }
+    }
+    println!(
+        "Running {} {:?} bots against {} for {:.0} s",
+        bots.len(),
+        config.pattern,
+        config.server_addr,
+        config.duration.as_secs_f32()
+    );
+
+    let start = Instant::now();
+    let mut last_frame = start;
+    let mut last_progress = start;
+    while start.elapsed() < config.duration {
+        let frame_start = Instant::now();
+        let dt_micros = frame_start.duration_since(last_frame).as_micros() as u64;
+        last_frame = frame_start;
+
+        for bot in &mut bots {
+            bot.frame(dt_micros);
+        }
+
+        if last_progress.elapsed() >= PROGRESS_INTERVAL {
+            last_progress = Instant::now();
+            let stats: Vec<_> = bots.iter().map(Bot::stats).collect();
+            let connected = stats.iter().filter(|bot| bot.snapshots > 0).count();
+            let rtt = stats.iter().map(|bot| bot.rtt_ms).sum::<f32>() / stats.len().max(1) as f32;
+            println!(
+                "{:.0} s: {}/{} bots connected, avg RTT {:.0} ms, desync {:.1}%",
+                start.elapsed().as_secs_f32(),
+                connected,
+                stats.len(),
+                rtt,
+                total_desync_rate(&stats) * 100.0
+            );
+        }
+
+        if let Some(left) = FRAME_DURATION.checked_sub(frame_start.elapsed()) {
+            sleep(left);
+        }
+    }
+
+    let stats: Vec<_> = bots.iter().map(Bot::stats).collect();
+    print_report(&stats);
+    let failures = config.thresholds.failures(&stats);
+    if failures.is_empty() {
+        println!("PASS");
+        ExitCode::SUCCESS
+    } else {
+        for failure in &failures {
+            println!("FAIL: {}", failure);
+        }
+        ExitCode::FAILURE
+    }
+}
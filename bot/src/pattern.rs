@@ -0,0 +1,143 @@
+use client_core::FrameInput;
+use shared::generated::PlayerCommand;
+use shared::state::quantize_axis;
+
+/// Length of one loop of the scripted pattern in seconds
+const SCRIPT_LOOP_SECS: f32 = 4.0;
+/// Shortest and longest time random input is held before it changes
+const RANDOM_HOLD_SECS: (f32, f32) = (0.3, 1.5);
+
+/// How a bot picks its input.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PatternKind {
+    /// Run right, jump, run left, jump, over and over
+    Script,
+    /// Hold a random mix of buttons and stick for a random time
+    Random,
+}
+
+impl PatternKind {
+    pub fn from_name(name: &str) -> Option<PatternKind> {
+        match name {
+            "script" => Some(PatternKind::Script),
+            "random" => Some(PatternKind::Random),
+            _ => None,
+        }
+    }
+}
+
+/// Input source for one bot. Reproducible for a given kind, seed and sequence of times.
+pub struct InputPattern {
+    kind: PatternKind,
+    /// Seconds added to the clock so bots running the same script don't move in lockstep
+    offset: f32,
+    rng: u64,
+    held: FrameInput,
+    change_at: f32,
+}
+
+impl InputPattern {
+    pub fn new(kind: PatternKind, seed: u64) -> Self {
+        let mut pattern = InputPattern {
+            kind,
+            offset: 0.0,
+            // xorshift gets stuck on zero
+            rng: seed.wrapping_mul(0x9e37_79b9_7f4a_7c15) | 1,
+            held: FrameInput::default(),
+            change_at: 0.0,
+        };
+        pattern.offset = pattern.next_unit() * SCRIPT_LOOP_SECS;
+        pattern
+    }
+
+    /// What to hold `time` seconds after the bot started
+    pub fn input(&mut self, time: f32) -> FrameInput {
+        match self.kind {
+            PatternKind::Script => script_input((time + self.offset) % SCRIPT_LOOP_SECS),
+            PatternKind::Random => {
+                if time >= self.change_at {
+                    self.held = self.random_input();
+                    let (min, max) = RANDOM_HOLD_SECS;
+                    self.change_at = time + min + self.next_unit() * (max - min);
+                }
+                self.held.clone()
+            }
+        }
+    }
+
+    fn random_input(&mut self) -> FrameInput {
+        let mut input = FrameInput::default();
+        match (self.next_unit() * 4.0) as u32 {
+            0 => input.commands.push(PlayerCommand::MoveLeft),
+            1 => input.commands.push(PlayerCommand::MoveRight),
+            2 => input.move_axis = quantize_axis(self.next_unit() * 2.0 - 1.0),
+            _ => {}
+        }
+        if self.next_unit() < 0.3 {
+            input.commands.push(PlayerCommand::Jump);
+        }
+        if self.next_unit() < 0.1 {
+            input.commands.push(PlayerCommand::MoveDown);
+        }
+        if self.next_unit() < 0.1 {
+            input.commands.push(PlayerCommand::Dash);
+        }
+        input
+    }
+
+    /// Uniform in 0..1
+    fn next_unit(&mut self) -> f32 {
+        self.rng ^= self.rng << 13;
+        self.rng ^= self.rng >> 7;
+        self.rng ^= self.rng << 17;
+        (self.rng >> 40) as f32 / (1u64 << 24) as f32
+    }
+}
+
+/// Scripted input at `time` seconds into the loop
+fn script_input(time: f32) -> FrameInput {
+    let direction = if time < SCRIPT_LOOP_SECS / 2.0 {
+        PlayerCommand::MoveRight
+    } else {
+        PlayerCommand::MoveLeft
+    };
+    let mut commands = vec![direction];
+    let half_time = time % (SCRIPT_LOOP_SECS / 2.0);
+    if (1.5..1.7).contains(&half_time) {
+        commands.push(PlayerCommand::Jump);
+    }
+    FrameInput {
+        commands,
+        move_axis: 0,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run(kind: PatternKind, seed: u64) -> Vec<FrameInput> {
+        let mut pattern = InputPattern::new(kind, seed);
+        (0..600)
+            .map(|frame| pattern.input(frame as f32 / 60.0))
+            .collect()
+    }
+
+    #[test]
+    fn test_patterns_are_reproducible() {
+        assert_eq!(run(PatternKind::Random, 7), run(PatternKind::Random, 7));
+        assert_ne!(run(PatternKind::Random, 7), run(PatternKind::Random, 8));
+    }
+
+    #[test]
+    fn test_script_runs_both_ways_and_jumps() {
+        let inputs = run(PatternKind::Script, 1);
+        for command in [
+            PlayerCommand::MoveLeft,
+            PlayerCommand::MoveRight,
+            PlayerCommand::Jump,
+        ] {
+            assert!(inputs.iter().any(|input| input.commands.contains(&command)));
+        }
+    }
+}
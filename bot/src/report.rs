@@ -0,0 +1,168 @@
+/// Statistics of one bot at the end of a run. Rates are over the last few seconds.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct BotStats {
+    pub index: u32,
+    pub player_id: Option<u32>,
+    pub snapshots: u32,
+    pub rtt_ms: f32,
+    pub snapshots_per_sec: f32,
+    pub bytes_sent_per_sec: f32,
+    pub bytes_received_per_sec: f32,
+    pub desync_checks: u32,
+    pub desync_mismatches: u32,
+}
+
+impl BotStats {
+    /// Share of checked predictions that didn't match the server
+    pub fn desync_rate(&self) -> f32 {
+        if self.desync_checks == 0 {
+            return 0.0;
+        }
+        self.desync_mismatches as f32 / self.desync_checks as f32
+    }
+}
+
+/// Limits a run has to stay within, unset ones aren't checked
+#[derive(Debug, Clone, Default)]
+pub struct Thresholds {
+    /// Highest round trip time of any bot
+    pub max_rtt_ms: Option<f32>,
+    /// Highest desync rate over all bots
+    pub max_desync_rate: Option<f32>,
+    /// Lowest snapshot rate of any bot
+    pub min_snapshots_per_sec: Option<f32>,
+}
+
+impl Thresholds {
+    /// Why the run failed, empty if it passed. Bots that never heard from the server
+    /// always fail it.
+    pub fn failures(&self, stats: &[BotStats]) -> Vec<String> {
+        let mut failures = Vec::new();
+        for bot in stats.iter().filter(|bot| bot.snapshots == 0) {
+            failures.push(format!("bot {} never received a snapshot", bot.index));
+        }
+
+        if let Some(max) = self.max_rtt_ms
+            && let Some(worst) = stats.iter().max_by(|a, b| a.rtt_ms.total_cmp(&b.rtt_ms))
+            && worst.rtt_ms > max
+        {
+            failures.push(format!(
+                "bot {} RTT {:.0} ms is above {:.0} ms",
+                worst.index, worst.rtt_ms, max
+            ));
+        }
+
+        if let Some(max) = self.max_desync_rate {
+            let total = total_desync_rate(stats);
+            if total > max {
+                failures.push(format!(
+                    "desync rate {:.1}% is above {:.1}%",
+                    total * 100.0,
+                    max * 100.0
+                ));
+            }
+        }
+
+        if let Some(min) = self.min_snapshots_per_sec
+            && let Some(worst) = stats
+                .iter()
+                .min_by(|a, b| a.snapshots_per_sec.total_cmp(&b.snapshots_per_sec))
+            && worst.snapshots_per_sec < min
+        {
+            failures.push(format!(
+                "bot {} receives {:.1} snapshots/s, below {:.1}",
+                worst.index, worst.snapshots_per_sec, min
+            ));
+        }
+        failures
+    }
+}
+
+/// Mismatched predictions over all checked ones, across every bot
+pub fn total_desync_rate(stats: &[BotStats]) -> f32 {
+    let checks: u32 = stats.iter().map(|bot| bot.desync_checks).sum();
+    let mismatches: u32 = stats.iter().map(|bot| bot.desync_mismatches).sum();
+    if checks == 0 {
+        return 0.0;
+    }
+    mismatches as f32 / checks as f32
+}
+
+/// Print one line per bot and a total
+pub fn print_report(stats: &[BotStats]) {
+    println!(
+        "{:>4} {:>6} {:>9} {:>8} {:>8} {:>10} {:>10} {:>8}",
+        "bot", "player", "snapshots", "rtt ms", "snaps/s", "out B/s", "in B/s", "desync"
+    );
+    for bot in stats {
+        let player = bot.player_id.map_or("-".to_string(), |id| id.to_string());
+        println!(
+            "{:>4} {:>6} {:>9} {:>8.0} {:>8.1} {:>10.0} {:>10.0} {:>7.1}%",
+            bot.index,
+            player,
+            bot.snapshots,
+            bot.rtt_ms,
+            bot.snapshots_per_sec,
+            bot.bytes_sent_per_sec,
+            bot.bytes_received_per_sec,
+            bot.desync_rate() * 100.0
+        );
+    }
+
+    let out: f32 = stats.iter().map(|bot| bot.bytes_sent_per_sec).sum();
+    let incoming: f32 = stats.iter().map(|bot| bot.bytes_received_per_sec).sum();
+    println!(
+        "Total: out {:.0} B/s, in {:.0} B/s, desync {:.1}%",
+        out,
+        incoming,
+        total_desync_rate(stats) * 100.0
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bot(index: u32, rtt_ms: f32, checks: u32, mismatches: u32) -> BotStats {
+        BotStats {
+            index,
+            player_id: Some(index + 1),
+            snapshots: 30,
+            rtt_ms,
+            snapshots_per_sec: 3.0,
+            desync_checks: checks,
+            desync_mismatches: mismatches,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_run_within_thresholds_passes() {
+        let thresholds = Thresholds {
+            max_rtt_ms: Some(100.0),
+            max_desync_rate: Some(0.05),
+            min_snapshots_per_sec: Some(2.0),
+        };
+        let stats = vec![bot(0, 20.0, 100, 1), bot(1, 80.0, 100, 5)];
+        assert!(thresholds.failures(&stats).is_empty());
+    }
+
+    #[test]
+    fn test_thresholds_report_worst_bot() {
+        let thresholds = Thresholds {
+            max_rtt_ms: Some(100.0),
+            max_desync_rate: Some(0.05),
+            ..Default::default()
+        };
+        let mut silent = bot(2, 0.0, 0, 0);
+        silent.snapshots = 0;
+        let stats = vec![bot(0, 20.0, 10, 5), bot(1, 150.0, 10, 0), silent];
+
+        let failures = thresholds.failures(&stats);
+
+        assert_eq!(failures.len(), 3, "{:?}", failures);
+        assert!(failures[0].starts_with("bot 2 never"));
+        assert!(failures[1].starts_with("bot 1 RTT"));
+        assert!(failures[2].starts_with("desync rate 25.0%"));
+    }
+}
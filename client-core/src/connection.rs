@@ -0,0 +1,240 @@
+use flatbuffers::FlatBufferBuilder;
+use shared::net_stats::NetStats;
+use shared::state::{PlayerStateCommand, SnapshotHeader};
+use shared::transport::{NetConditions, SimulatedTransport, Transport, UdpTransport};
+use std::collections::HashMap;
+use std::io;
+use std::net::{SocketAddr, ToSocketAddrs};
+use std::sync::mpsc::{self, Receiver, Sender, TryRecvError};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// How long the network thread sleeps when there was nothing to receive
+const IDLE_SLEEP: Duration = Duration::from_millis(1);
+
+/// Carries a session's commands to the server and the server's snapshots back.
+/// `Connection` is the real one, tests can run a session over their own.
+pub trait Link {
+    /// Queue a command for the server
+    fn send(&self, command: PlayerStateCommand);
+
+    /// Next snapshot packet from the server, if one arrived. The session decodes it
+    /// against its own copy of the scene.
+    fn try_recv(&self) -> Option<Vec<u8>>;
+}
+
+/// Talks to the server straight over a transport, on the caller's thread. A `Connection`
+/// runs one on its own thread; a test on a virtual clock can use one directly.
+pub struct TransportLink<T: Transport> {
+    transport: T,
+    server_addr: SocketAddr,
+    net_stats: Arc<Mutex<NetStats>>,
+}
+
+impl<T: Transport> TransportLink<T> {
+    pub fn new(transport: T, server_addr: SocketAddr) -> Self {
+        TransportLink {
+            transport,
+            server_addr,
+            net_stats: Arc::new(Mutex::new(NetStats::new())),
+        }
+    }
+
+    pub fn transport(&self) -> &T {
+        &self.transport
+    }
+
+    pub fn net_stats(&self) -> MutexGuard<'_, NetStats> {
+        self.net_stats.lock().unwrap()
+    }
+}
+
+impl<T: Transport> Link for TransportLink<T> {
+    fn send(&self, command: PlayerStateCommand) {
+        let mut builder = FlatBufferBuilder::with_capacity(2048);
+        let serialized_commands = command.serialize(&mut builder);
+        builder.finish(serialized_commands, None);
+        let bytes = builder.finished_data();
+        match self.transport.send(bytes, self.server_addr) {
+            Ok(amt) => self.net_stats().record_sent(amt, Instant::now()),
+            Err(e) => eprintln!("Packet couldn't send: {}", e),
+        }
+    }
+
+    fn try_recv(&self) -> Option<Vec<u8>> {
+        let mut buf = [0u8; 2048];
+        // An error, like the server's port being closed, just means nothing arrived
+        while let Ok(Some((amt, src_addr))) = self.transport.recv(&mut buf) {
+            if src_addr != self.server_addr {
+                continue;
+            }
+
+            let Some(header) = SnapshotHeader::read(&buf[..amt]) else {
+                continue;
+            };
+            let mut net_stats = self.net_stats();
+            net_stats.record_received(amt, Instant::now());
+            net_stats.record_sequence(0, header.tick);
+            return Some(buf[..amt].to_vec());
+        }
+        None
+    }
+}
+
+/// Link to the server, serviced by its own thread. Commands and snapshots can both be
+/// held back by an artificial delay to simulate latency. The thread stops when the
+/// connection is dropped.
+pub struct Connection {
+    local_addr: SocketAddr,
+    command_sender: Sender<PlayerStateCommand>,
+    delay_sender: Sender<u64>,
+    state_receiver: Receiver<Vec<u8>>,
+    net_stats: Arc<Mutex<NetStats>>,
+}
+
+impl Connection {
+    /// Bind `local_addr` and start talking to the server at `server_addr` over UDP
+    pub fn connect(
+        local_addr: impl ToSocketAddrs,
+        server_addr: impl ToSocketAddrs,
+        delay_millis: u64,
+    ) -> io::Result<Connection> {
+        let transport = UdpTransport::bind(local_addr)?;
+        let server_addr = server_addr
+            .to_socket_addrs()?
+            .next()
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "No server address"))?;
+        Connection::over(transport, server_addr, delay_millis)
+    }
+
+    /// Start talking to the server at `server_addr` over `transport`
+    pub fn over<T: Transport + 'static>(
+        transport: T,
+        server_addr: SocketAddr,
+        delay_millis: u64,
+    ) -> io::Result<Connection> {
+        let local_addr = transport.local_addr()?;
+        let transport = SimulatedTransport::new(transport, delay_conditions(delay_millis), 1);
+        let link = TransportLink::new(transport, server_addr);
+        let net_stats = Arc::clone(&link.net_stats);
+
+        let (command_sender, command_receiver) = mpsc::channel();
+        let (delay_sender, delay_receiver) = mpsc::channel();
+        let (state_sender, state_receiver) = mpsc::channel();
+        let worker = NetworkThread { link };
+        thread::spawn(move || worker.run(command_receiver, delay_receiver, state_sender));
+
+        Ok(Connection {
+            local_addr,
+            command_sender,
+            delay_sender,
+            state_receiver,
+            net_stats,
+        })
+    }
+
+    pub fn local_addr(&self) -> SocketAddr {
+        self.local_addr
+    }
+
+    /// Artificial delay added to both directions, in milliseconds
+    pub fn set_delay(&self, delay_millis: u64) {
+        let _ = self.delay_sender.send(delay_millis);
+    }
+
+    pub fn net_stats(&self) -> MutexGuard<'_, NetStats> {
+        self.net_stats.lock().unwrap()
+    }
+}
+
+impl Link for Connection {
+    fn send(&self, command: PlayerStateCommand) {
+        if let Err(e) = self.command_sender.send(command) {
+            eprintln!("Error sending player state command: {}", e);
+        }
+    }
+
+    /// Next snapshot that made it through the delay, if any
+    fn try_recv(&self) -> Option<Vec<u8>> {
+        self.state_receiver.try_recv().ok()
+    }
+}
+
+/// `delay_millis` each way
+fn delay_conditions(delay_millis: u64) -> NetConditions {
+    NetConditions {
+        rtt: Duration::from_millis(delay_millis * 2),
+        ..NetConditions::default()
+    }
+}
+
+struct NetworkThread<T: Transport> {
+    link: TransportLink<SimulatedTransport<T>>,
+}
+
+impl<T: Transport> NetworkThread<T> {
+    fn run(
+        self,
+        command_receiver: Receiver<PlayerStateCommand>,
+        delay_receiver: Receiver<u64>,
+        state_sender: Sender<Vec<u8>>,
+    ) {
+        // When each command was issued, to measure the round trip once the server acks it
+        let mut command_sent_at: HashMap<u32, Instant> = HashMap::new();
+        let mut last_acked_sequence = 0;
+
+        loop {
+            while let Ok(delay) = delay_receiver.try_recv() {
+                self.link
+                    .transport()
+                    .set_conditions(delay_conditions(delay));
+            }
+
+            let received = self.link.try_recv();
+            let idle = received.is_none();
+            match received {
+                Some(snapshot) => {
+                    // The link only hands over packets with a readable header
+                    let Some(header) = SnapshotHeader::read(&snapshot) else {
+                        continue;
+                    };
+                    let acked_sequence = header.sequence;
+                    if acked_sequence > last_acked_sequence {
+                        last_acked_sequence = acked_sequence;
+                        if let Some(sent_at) = command_sent_at.get(&acked_sequence) {
+                            // The command waited for the server's next tick, that's not
+                            // time spent on the network
+                            let held = Duration::from_micros(header.ack_hold_micros);
+                            let rtt = sent_at.elapsed().saturating_sub(held);
+                            self.link.net_stats().record_rtt(rtt);
+                        }
+                        command_sent_at.retain(|sequence, _| *sequence > acked_sequence);
+                    }
+
+                    // Send to game loop
+                    if state_sender.send(snapshot).is_err() {
+                        // The connection was dropped
+                        return;
+                    }
+                }
+                None => self.link.net_stats().update(Instant::now()),
+            }
+
+            loop {
+                match command_receiver.try_recv() {
+                    Ok(player_state_command) => {
+                        command_sent_at.insert(player_state_command.sequence, Instant::now());
+                        self.link.send(player_state_command);
+                    }
+                    Err(TryRecvError::Empty) => break,
+                    Err(TryRecvError::Disconnected) => return,
+                }
+            }
+
+            if idle {
+                thread::sleep(IDLE_SLEEP);
+            }
+        }
+    }
+}
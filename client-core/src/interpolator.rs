@@ -0,0 +1,131 @@
+use shared::state::{self, GameState};
+
+/// Moves the other players from the previous snapshot to the newest over the time the
+/// newest took to arrive. Times are unix microseconds passed in by the session, so it
+/// follows a virtual clock too.
+pub struct Interpolator {
+    old_server_state: GameState,
+    new_server_state: GameState,
+    received_new_state_at: u64,
+    interpolation_micros: u64,
+    t: f32,
+    pub active: bool,
+}
+
+impl Interpolator {
+    pub fn new(game_state: &GameState) -> Self {
+        Self {
+            old_server_state: game_state.clone(),
+            new_server_state: game_state.clone(),
+            received_new_state_at: 0,
+            interpolation_micros: 0,
+            t: 0.0,
+            active: true,
+        }
+    }
+
+    pub fn set_new_state(&mut self, new_state: GameState, now_micros: u64) {
+        if !self.active {
+            return;
+        }
+
+        self.old_server_state = self.new_server_state.clone();
+        self.new_server_state = new_state;
+        self.interpolation_micros = now_micros.saturating_sub(self.received_new_state_at);
+        self.received_new_state_at = now_micros;
+    }
+
+    pub fn interpolate(
+        &mut self,
+        game_state: &mut GameState,
+        client_player_id: u32,
+        now_micros: u64,
+    ) {
+        if !self.active {
+            return;
+        }
+
+        self.update_t(now_micros);
+        for player in game_state.players.values_mut() {
+            if player.id == client_player_id {
+                continue;
+            }
+
+            let old_position = self
+                .old_server_state
+                .players
+                .get(&player.id)
+                .map_or(state::Vec2::ZERO, |p| p.pos);
+            let new_position = self
+                .new_server_state
+                .players
+                .get(&player.id)
+                .map_or(state::Vec2::ZERO, |p| p.pos);
+
+            player.pos = self.lerp_position(old_position, new_position);
+        }
+    }
+
+    /// Stays at the newest snapshot once it is reached. Snapshots applied at the same time
+    /// leave no interval to spread over, `t` keeps its value then.
+    pub fn update_t(&mut self, now_micros: u64) {
+        if self.interpolation_micros == 0 {
+            return;
+        }
+        let t = now_micros.saturating_sub(self.received_new_state_at) as f32
+            / self.interpolation_micros as f32;
+        self.t = t.clamp(0.0, 1.0);
+    }
+
+    pub fn lerp_position(&self, start: state::Vec2, end: state::Vec2) -> state::Vec2 {
+        start + (end - start) * self.t
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use shared::state::{PlayerState, SpawnPoint};
+
+    /// State with player 2 standing at `x`
+    fn state_at(x: f32) -> GameState {
+        let mut state = GameState::empty(1000.0, 1000.0);
+        state
+            .players
+            .insert(2, PlayerState::new(2, &SpawnPoint { x, y: 0.0 }));
+        state
+    }
+
+    fn drawn_x(interpolator: &mut Interpolator, now_micros: u64) -> f32 {
+        let mut state = state_at(0.0);
+        interpolator.interpolate(&mut state, 1, now_micros);
+        state.players[&2].pos.x
+    }
+
+    #[test]
+    fn test_snapshots_at_the_same_time_stay_finite() {
+        let mut interpolator = Interpolator::new(&state_at(0.0));
+        interpolator.set_new_state(state_at(0.0), 1_000_000);
+        interpolator.set_new_state(state_at(100.0), 1_100_000);
+        assert_eq!(drawn_x(&mut interpolator, 1_150_000), 50.0);
+
+        // A second snapshot in the same frame leaves no interval
+        interpolator.set_new_state(state_at(200.0), 1_150_000);
+        interpolator.set_new_state(state_at(300.0), 1_150_000);
+        let x = drawn_x(&mut interpolator, 1_150_000);
+        assert!(x.is_finite());
+        assert_eq!(x, 250.0);
+    }
+
+    #[test]
+    fn test_stays_at_the_newest_snapshot() {
+        let mut interpolator = Interpolator::new(&state_at(0.0));
+        interpolator.set_new_state(state_at(0.0), 1_000_000);
+        interpolator.set_new_state(state_at(100.0), 1_100_000);
+
+        // The next snapshot is late
+        assert_eq!(drawn_x(&mut interpolator, 1_400_000), 100.0);
+        // And a clock read before the snapshot doesn't run backwards past the old one
+        assert_eq!(drawn_x(&mut interpolator, 1_050_000), 0.0);
+    }
+}
@@ -0,0 +1,15 @@
+//! Client netcode without a window: the connection to the server, prediction,
+//! interpolation and scene loading, tied together by `ClientSession`. The macroquad client
+//! and the headless bot are both built on it.
+
+pub mod connection;
+pub mod interpolator;
+pub mod predictor;
+pub mod scene;
+pub mod session;
+
+pub use connection::{Connection, Link, TransportLink};
+pub use interpolator::Interpolator;
+pub use predictor::Predictor;
+pub use scene::Scene;
+pub use session::{ClientSession, FrameInput, KEEPALIVE_FRAMES, RELEASE_REPEATS, RenderState};
@@ -0,0 +1,119 @@
+use shared::state::{
+    CommandContent, DesyncDetector, GameState, PlayerInput, PlayerStateCommand, player_checksum,
+};
+
+pub struct ReconciliationCommand {
+    command: CommandContent,
+    sequence: u32,
+    /// Physics steps predicted while the client player held this command's input
+    steps: u32,
+}
+
+/// Client-side prediction of the local player, replaying unacknowledged commands on top of
+/// every server snapshot
+pub struct Predictor {
+    reconciliation_commands: Vec<ReconciliationCommand>,
+    pub active_prediction: bool,
+    pub active_reconciliation: bool,
+    pub sequence: u32,
+    pub desync: DesyncDetector,
+}
+
+impl Default for Predictor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Predictor {
+    pub fn new() -> Self {
+        Predictor {
+            reconciliation_commands: Vec::new(),
+            active_prediction: true,
+            active_reconciliation: true,
+            sequence: 0,
+            desync: DesyncDetector::new(),
+        }
+    }
+
+    /// Simulate the client player for one frame ending at `now_micros`, a unix timestamp in
+    /// microseconds
+    pub fn predict(
+        &mut self,
+        game_state: &mut GameState,
+        client_player_id: u32,
+        player_state_command: Option<&PlayerStateCommand>,
+        dt_micros: u64,
+        now_micros: u64,
+    ) {
+        if !self.active_prediction {
+            return;
+        }
+
+        let physics_step = game_state.physics_step;
+        if let Some(command) = player_state_command {
+            let command_content = CommandContent {
+                player_id: client_player_id,
+                player_state_command: command.clone(),
+                client_delay_micros: 0,
+            };
+            game_state.mutate_at(
+                std::slice::from_ref(&command_content),
+                dt_micros,
+                Some(client_player_id),
+                now_micros,
+            );
+            self.count_steps(game_state, physics_step);
+
+            if self.active_reconciliation {
+                if let Some(player) = game_state.players.get(&client_player_id) {
+                    self.desync
+                        .record_predicted(self.sequence, player_checksum(player));
+                }
+                self.reconciliation_commands.push(ReconciliationCommand {
+                    command: command_content,
+                    sequence: self.sequence,
+                    steps: 0,
+                });
+                self.sequence += 1;
+            }
+        } else {
+            game_state.mutate_at(&[], dt_micros, Some(client_player_id), now_micros);
+            self.count_steps(game_state, physics_step);
+        }
+    }
+
+    /// The frame's steps ran before its command was applied, holding the one before
+    fn count_steps(&mut self, game_state: &GameState, physics_step: u32) {
+        if let Some(held) = self.reconciliation_commands.last_mut() {
+            held.steps += game_state.physics_step.wrapping_sub(physics_step);
+        }
+    }
+
+    /// Replay what was predicted after the server's snapshot on top of it: the rest of the
+    /// acked command's steps, then every newer command for as many steps as it was held.
+    /// The server simulated `ack_steps` since it applied the acked command.
+    pub fn reconciliation(
+        &mut self,
+        game_state: &mut GameState,
+        server_sequence: u32,
+        client_player_id: u32,
+        ack_steps: u32,
+    ) {
+        if !self.active_reconciliation {
+            return;
+        }
+
+        // The acked command stays, its input is still held until the next one is acked
+        self.reconciliation_commands
+            .retain(|c| c.sequence >= server_sequence);
+
+        let mut simulated = ack_steps;
+        for command in &self.reconciliation_commands {
+            let steps = command.steps.saturating_sub(simulated);
+            simulated = simulated.saturating_sub(command.steps);
+            let input = PlayerInput::from_command(&command.command.player_state_command);
+            game_state.mutate_steps(client_player_id, input, steps);
+        }
+    }
+}
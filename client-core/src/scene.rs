@@ -0,0 +1,71 @@
+use serde::Deserialize;
+use shared::state::CollidableKind;
+use std::collections::{BTreeMap, HashMap};
+use std::fs::File;
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct RgbaColor {
+    pub r: u8,
+    pub g: u8,
+    pub b: u8,
+    pub a: u8,
+}
+
+/// A drawn rectangle, decoration or collidable
+#[derive(Debug, Deserialize, Clone)]
+pub struct SceneObject {
+    pub x: f32,
+    pub y: f32,
+    pub w: f32,
+    pub h: f32,
+    pub color: RgbaColor,
+    pub z: f32,
+    #[serde(default)]
+    pub kind: CollidableKind,
+}
+
+/// Everything in a scene file, including what only matters for drawing it. The parts that
+/// affect gameplay are loaded by `GameState::new`.
+#[derive(Debug, Deserialize)]
+pub struct Scene {
+    pub decorations: HashMap<u32, SceneObject>,
+    pub collidables: HashMap<u32, SceneObject>,
+    pub width: f32,
+    pub height: f32,
+    pub background_color: RgbaColor,
+    pub border_color: RgbaColor,
+    #[serde(default)]
+    pub moving_platforms: BTreeMap<u32, PlatformStyle>,
+    #[serde(default)]
+    pub triggers: BTreeMap<u32, TriggerStyle>,
+}
+
+/// Looks of a moving platform, the path is simulated by `shared`
+#[derive(Debug, Deserialize, Clone)]
+pub struct PlatformStyle {
+    pub color: RgbaColor,
+    #[serde(default)]
+    pub z: f32,
+}
+
+/// Looks of a trigger volume, what it does to players is up to `shared`
+#[derive(Debug, Deserialize, Clone)]
+pub struct TriggerStyle {
+    pub x: f32,
+    pub y: f32,
+    pub w: f32,
+    pub h: f32,
+    pub color: RgbaColor,
+    #[serde(default)]
+    pub z: f32,
+}
+
+impl Scene {
+    /// Load `scenes/<scene_name>.json`
+    pub fn load(scene_name: &str) -> Scene {
+        let project_root = env!("CARGO_MANIFEST_DIR");
+        let file = File::open(format!("{}/../scenes/{}.json", project_root, scene_name))
+            .expect("Scene file must open");
+        serde_json::from_reader(file).expect("JSON must match Scene")
+    }
+}
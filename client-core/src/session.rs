@@ -0,0 +1,389 @@
+use shared::generated::PlayerCommand;
+use shared::net_stats::NetStats;
+use shared::state::{GameState, PlayerStateCommand, RacePhase, RaceView, Snapshot};
+use std::io;
+use std::net::ToSocketAddrs;
+use std::sync::MutexGuard;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::{Connection, Interpolator, Link, Predictor};
+
+/// Frames an empty command is still sent after letting go of everything, since a lost
+/// release would leave the player holding the buttons on the server
+pub const RELEASE_REPEATS: u32 = 5;
+/// Idle frames between the empty commands that keep the server from timing the client out
+pub const KEEPALIVE_FRAMES: u32 = 60;
+
+/// What the player holds this frame
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct FrameInput {
+    pub commands: Vec<PlayerCommand>,
+    /// Quantized analog axis, see `shared::state::quantize_axis`
+    pub move_axis: i8,
+}
+
+impl FrameInput {
+    pub fn is_empty(&self) -> bool {
+        self.commands.is_empty() && self.move_axis == 0
+    }
+}
+
+/// What a frontend draws after a `ClientSession::poll`
+pub struct RenderState<'a> {
+    /// Predicted client player and interpolated other players
+    pub game_state: &'a GameState,
+    pub client_player_id: u32,
+    pub race: &'a RaceView,
+}
+
+/// One client's view of the game: applies snapshots from the server, predicts the client
+/// player and interpolates everyone else. Doesn't draw or read devices, so it runs the
+/// same under macroquad, in a bot or in a test.
+pub struct ClientSession<L: Link = Connection> {
+    connection: L,
+    pub predictor: Predictor,
+    pub interpolator: Interpolator,
+    /// Prediction + reconciliation
+    game_state: GameState,
+    /// Other players as last reported by the server (snapshots may be partial)
+    server_state: GameState,
+    /// Round state, driven by the server's race events
+    race: RaceView,
+    client_player_id: u32,
+    /// Tick of the newest snapshot received, acked with every command
+    acked_tick: Option<u32>,
+    snapshots: u32,
+    idle_frames: u32,
+}
+
+impl ClientSession<Connection> {
+    /// Connect to the server at `server_addr` from any free local port
+    pub fn connect(
+        server_addr: impl ToSocketAddrs,
+        scene_name: &str,
+        delay_millis: u64,
+    ) -> io::Result<Self> {
+        let connection = Connection::connect("127.0.0.1:0", server_addr, delay_millis)?;
+        Ok(ClientSession::new(connection, scene_name))
+    }
+
+    /// Artificial delay added to both directions, in milliseconds
+    pub fn set_delay(&self, delay_millis: u64) {
+        self.connection.set_delay(delay_millis);
+    }
+
+    pub fn net_stats(&self) -> MutexGuard<'_, NetStats> {
+        self.connection.net_stats()
+    }
+}
+
+impl<L: Link> ClientSession<L> {
+    pub fn new(connection: L, scene_name: &str) -> Self {
+        let game_state = GameState::new(scene_name);
+        ClientSession {
+            connection,
+            predictor: Predictor::new(),
+            interpolator: Interpolator::new(&game_state),
+            server_state: game_state.clone(),
+            game_state,
+            race: RaceView::new(),
+            client_player_id: 1,
+            acked_tick: None,
+            snapshots: 0,
+            idle_frames: RELEASE_REPEATS,
+        }
+    }
+
+    /// Advance one frame of `dt_micros`: apply the next snapshot if one arrived, then send
+    /// and predict `input`
+    pub fn poll(&mut self, input: FrameInput, dt_micros: u64) -> RenderState<'_> {
+        // Get Unix epoch timestamp (absolute time)
+        let unix_timestamp_micro = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_micros() as u64;
+        self.poll_at(input, dt_micros, unix_timestamp_micro)
+    }
+
+    /// `poll` for a frame ending at `unix_timestamp_micro` instead of now, for sessions
+    /// running on a virtual clock
+    pub fn poll_at(
+        &mut self,
+        input: FrameInput,
+        dt_micros: u64,
+        unix_timestamp_micro: u64,
+    ) -> RenderState<'_> {
+        self.apply_snapshot(unix_timestamp_micro);
+
+        // The server ignores input during the countdown, so don't predict any
+        let input = match self.race.phase() {
+            RacePhase::Countdown => FrameInput::default(),
+            _ => input,
+        };
+        self.idle_frames = if input.is_empty() {
+            self.idle_frames.saturating_add(1)
+        } else {
+            0
+        };
+        let keepalive = self.idle_frames.is_multiple_of(KEEPALIVE_FRAMES);
+        let player_state_command = match self.idle_frames > RELEASE_REPEATS && !keepalive {
+            true => None,
+            false => Some(PlayerStateCommand {
+                sequence: self.predictor.sequence,
+                dt_micros,
+                commands: input.commands,
+                client_timestamp_micros: unix_timestamp_micro,
+                move_axis: input.move_axis,
+                acked_tick: self.acked_tick,
+            }),
+        };
+
+        // Mutate local state
+        self.predictor.predict(
+            &mut self.game_state,
+            self.client_player_id,
+            player_state_command.as_ref(),
+            dt_micros,
+            unix_timestamp_micro,
+        );
+
+        // Send command to network thread if exists
+        if let Some(player_state_command) = player_state_command {
+            self.connection.send(player_state_command);
+        }
+
+        self.interpolator.interpolate(
+            &mut self.game_state,
+            self.client_player_id,
+            unix_timestamp_micro,
+        );
+
+        RenderState {
+            game_state: &self.game_state,
+            client_player_id: self.client_player_id,
+            race: &self.race,
+        }
+    }
+
+    fn apply_snapshot(&mut self, unix_timestamp_micro: u64) {
+        let Some(packet) = self.connection.try_recv() else {
+            return;
+        };
+        let Snapshot {
+            tick,
+            physics_step,
+            players,
+            race_events,
+            client_player: mut server_client_player,
+            sequence: server_sequence,
+            removed_players,
+            client_player_checksum: server_checksum,
+            physics_params,
+            ack_steps,
+            ..
+        } = self.server_state.deserialize(&packet);
+        self.snapshots += 1;
+        self.acked_tick = self.acked_tick.max(Some(tick));
+
+        // Predict with the server's physics, not just the local copy of the scene
+        if let Some(physics_params) = physics_params {
+            self.game_state.physics_params = physics_params;
+            self.server_state.physics_params = physics_params;
+        }
+
+        self.predictor
+            .desync
+            .check(server_sequence, server_checksum);
+
+        if server_client_player.name.is_empty()
+            && let Some(known) = self.game_state.players.get(&server_client_player.id)
+        {
+            server_client_player.name = known.name.clone();
+        }
+
+        self.race.apply(&race_events, unix_timestamp_micro);
+
+        // Replay from the server's platform clock, the replay advances it again
+        let predicted_step = self.game_state.physics_step;
+        self.game_state.physics_step = physics_step;
+        self.server_state.merge_players(players, &removed_players);
+        self.interpolator
+            .set_new_state(self.server_state.clone(), unix_timestamp_micro);
+
+        self.client_player_id = server_client_player.id;
+        self.game_state.players = self.server_state.players.clone();
+        self.game_state
+            .players
+            .insert(server_client_player.id, server_client_player);
+
+        // reconciliation
+        self.predictor.reconciliation(
+            &mut self.game_state,
+            server_sequence,
+            self.client_player_id,
+            ack_steps,
+        );
+
+        // Platforms keep moving at the predicted time even when the replay fell short of it,
+        // so they never jump back to where the server saw them
+        if (predicted_step.wrapping_sub(self.game_state.physics_step) as i32) > 0 {
+            self.game_state.physics_step = predicted_step;
+        }
+    }
+
+    pub fn game_state(&self) -> &GameState {
+        &self.game_state
+    }
+
+    pub fn race(&self) -> &RaceView {
+        &self.race
+    }
+
+    /// Assigned by the server, 1 until the first snapshot arrives
+    pub fn client_player_id(&self) -> u32 {
+        self.client_player_id
+    }
+
+    /// Snapshots applied so far
+    pub fn snapshots(&self) -> u32 {
+        self.snapshots
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use flatbuffers::FlatBufferBuilder;
+    use shared::state::{PlayerState, SpawnPoint};
+    use std::net::UdpSocket;
+    use std::time::{Duration, Instant};
+
+    const SCENE_NAME: &str = "scene_3";
+
+    /// Session talking to a bare socket standing in for the server
+    fn connect() -> (ClientSession, UdpSocket) {
+        let server = UdpSocket::bind("127.0.0.1:0").unwrap();
+        server
+            .set_read_timeout(Some(Duration::from_secs(2)))
+            .unwrap();
+        let session = ClientSession::connect(server.local_addr().unwrap(), SCENE_NAME, 0).unwrap();
+        (session, server)
+    }
+
+    /// Poll with `input` until `done` or a couple of seconds have passed
+    fn poll_until(
+        session: &mut ClientSession,
+        input: &FrameInput,
+        mut done: impl FnMut(&RenderState) -> bool,
+    ) -> bool {
+        let started = Instant::now();
+        while started.elapsed() < Duration::from_secs(2) {
+            if done(&session.poll(input.clone(), 16_667)) {
+                return true;
+            }
+            std::thread::sleep(Duration::from_millis(5));
+        }
+        false
+    }
+
+    #[test]
+    fn test_input_is_sent_to_the_server() {
+        let (mut session, server) = connect();
+        let input = FrameInput {
+            commands: vec![PlayerCommand::Jump],
+            move_axis: 64,
+        };
+        session.poll(input, 16_667);
+
+        let mut buf = [0u8; 2048];
+        let (amt, _) = server.recv_from(&mut buf).unwrap();
+        let command = PlayerStateCommand::deserialize(&buf[..amt]);
+        assert_eq!(command.commands, vec![PlayerCommand::Jump]);
+        assert_eq!(command.move_axis, 64);
+    }
+
+    #[test]
+    fn test_snapshot_sets_client_player() {
+        let (mut session, server) = connect();
+        // The server only knows the client's address once it sent something
+        session.poll(
+            FrameInput {
+                commands: vec![PlayerCommand::MoveRight],
+                move_axis: 0,
+            },
+            16_667,
+        );
+        let mut buf = [0u8; 2048];
+        let (_, client_addr) = server.recv_from(&mut buf).unwrap();
+
+        let mut state = GameState::new(SCENE_NAME);
+        let spawn = SpawnPoint { x: 200.0, y: 100.0 };
+        state.players.insert(7, PlayerState::new(7, &spawn));
+        state.players.insert(8, PlayerState::new(8, &spawn));
+        let server_timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_micros() as u64;
+        let mut builder = FlatBufferBuilder::new();
+        let snapshot = state.serialize(&mut builder, 7, 0, server_timestamp);
+        server.send_to(snapshot, client_addr).unwrap();
+
+        let applied = poll_until(&mut session, &FrameInput::default(), |frame| {
+            frame.client_player_id == 7
+        });
+
+        assert!(applied, "Snapshot should have been applied");
+        assert_eq!(session.snapshots(), 1);
+        assert!(session.game_state().players.contains_key(&8));
+    }
+
+    #[test]
+    fn test_release_is_repeated_then_stops() {
+        let (mut session, server) = connect();
+        server
+            .set_read_timeout(Some(Duration::from_millis(200)))
+            .unwrap();
+        session.poll(
+            FrameInput {
+                commands: vec![PlayerCommand::MoveLeft],
+                move_axis: 0,
+            },
+            16_667,
+        );
+        for _ in 0..RELEASE_REPEATS + 3 {
+            session.poll(FrameInput::default(), 16_667);
+        }
+
+        let mut buf = [0u8; 2048];
+        let mut packets = 0;
+        while server.recv_from(&mut buf).is_ok() {
+            packets += 1;
+        }
+        assert_eq!(packets, 1 + RELEASE_REPEATS);
+    }
+
+    #[test]
+    fn test_idle_client_sends_keepalive() {
+        let (mut session, server) = connect();
+        server
+            .set_read_timeout(Some(Duration::from_millis(200)))
+            .unwrap();
+        session.poll(
+            FrameInput {
+                commands: vec![PlayerCommand::MoveLeft],
+                move_axis: 0,
+            },
+            16_667,
+        );
+        for _ in 0..KEEPALIVE_FRAMES {
+            session.poll(FrameInput::default(), 16_667);
+        }
+
+        let mut buf = [0u8; 2048];
+        let mut packets = 0;
+        while server.recv_from(&mut buf).is_ok() {
+            packets += 1;
+        }
+        assert_eq!(packets, 1 + RELEASE_REPEATS + 1);
+    }
+}
@@ -0,0 +1,377 @@
+//! Input-action map: which keys and gamepad buttons trigger which player action
+
+use macroquad::input::KeyCode;
+use serde::{Deserialize, Serialize};
+use shared::generated::PlayerCommand;
+
+/// Keys that can be bound to an action. Escape and F3 are kept for the UI.
+pub const BINDABLE_KEYS: &[KeyCode] = &[
+    KeyCode::A,
+    KeyCode::B,
+    KeyCode::C,
+    KeyCode::D,
+    KeyCode::E,
+    KeyCode::F,
+    KeyCode::G,
+    KeyCode::H,
+    KeyCode::I,
+    KeyCode::J,
+    KeyCode::K,
+    KeyCode::L,
+    KeyCode::M,
+    KeyCode::N,
+    KeyCode::O,
+    KeyCode::P,
+    KeyCode::Q,
+    KeyCode::R,
+    KeyCode::S,
+    KeyCode::T,
+    KeyCode::U,
+    KeyCode::V,
+    KeyCode::W,
+    KeyCode::X,
+    KeyCode::Y,
+    KeyCode::Z,
+    KeyCode::Key0,
+    KeyCode::Key1,
+    KeyCode::Key2,
+    KeyCode::Key3,
+    KeyCode::Key4,
+    KeyCode::Key5,
+    KeyCode::Key6,
+    KeyCode::Key7,
+    KeyCode::Key8,
+    KeyCode::Key9,
+    KeyCode::Space,
+    KeyCode::Enter,
+    KeyCode::Tab,
+    KeyCode::Backspace,
+    KeyCode::Comma,
+    KeyCode::Period,
+    KeyCode::Slash,
+    KeyCode::Semicolon,
+    KeyCode::Apostrophe,
+    KeyCode::Minus,
+    KeyCode::Equal,
+    KeyCode::LeftBracket,
+    KeyCode::RightBracket,
+    KeyCode::Backslash,
+    KeyCode::Up,
+    KeyCode::Down,
+    KeyCode::Left,
+    KeyCode::Right,
+    KeyCode::LeftShift,
+    KeyCode::RightShift,
+    KeyCode::LeftControl,
+    KeyCode::RightControl,
+    KeyCode::LeftAlt,
+    KeyCode::RightAlt,
+    KeyCode::Kp0,
+    KeyCode::Kp1,
+    KeyCode::Kp2,
+    KeyCode::Kp3,
+    KeyCode::Kp4,
+    KeyCode::Kp5,
+    KeyCode::Kp6,
+    KeyCode::Kp7,
+    KeyCode::Kp8,
+    KeyCode::Kp9,
+    KeyCode::KpEnter,
+];
+
+/// Name a key is saved under in the settings file
+pub fn key_name(key: KeyCode) -> String {
+    format!("{:?}", key)
+}
+
+/// Bindable key saved under `name`
+pub fn key_from_name(name: &str) -> Option<KeyCode> {
+    BINDABLE_KEYS
+        .iter()
+        .find(|key| key_name(**key) == name)
+        .copied()
+}
+
+/// Something the player can do, bound to any number of keys and gamepad buttons
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Action {
+    MoveLeft,
+    MoveRight,
+    Jump,
+    MoveDown,
+    Dash,
+}
+
+impl Action {
+    pub const ALL: [Action; 5] = [
+        Action::MoveLeft,
+        Action::MoveRight,
+        Action::Jump,
+        Action::MoveDown,
+        Action::Dash,
+    ];
+
+    /// Name shown on the controls screen
+    pub fn label(self) -> &'static str {
+        match self {
+            Action::MoveLeft => "Move left",
+            Action::MoveRight => "Move right",
+            Action::Jump => "Jump",
+            Action::MoveDown => "Drop down",
+            Action::Dash => "Dash",
+        }
+    }
+
+    /// Command sent to the server while the action is held
+    pub fn command(self) -> PlayerCommand {
+        match self {
+            Action::MoveLeft => PlayerCommand::MoveLeft,
+            Action::MoveRight => PlayerCommand::MoveRight,
+            Action::Jump => PlayerCommand::Jump,
+            Action::MoveDown => PlayerCommand::MoveDown,
+            Action::Dash => PlayerCommand::Dash,
+        }
+    }
+}
+
+/// Gamepad buttons that can be bound, see `crate::gamepad` for how they are read
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum PadButton {
+    South,
+    West,
+    RightTrigger,
+    DPadUp,
+    DPadDown,
+    DPadLeft,
+    DPadRight,
+}
+
+/// Why a key or button wasn't bound
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BindError {
+    /// The key or button already triggers this other action
+    Conflict(Action),
+    /// The key is used by the UI or can't be saved, see `BINDABLE_KEYS`
+    Reserved,
+}
+
+/// Bindings for every action. The left stick always moves the player analog.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Controls {
+    #[serde(with = "key_names")]
+    pub keys: Vec<(Action, KeyCode)>,
+    pub buttons: Vec<(Action, PadButton)>,
+    /// Stick deflection below this counts as centred
+    pub dead_zone: f32,
+}
+
+impl Default for Controls {
+    fn default() -> Self {
+        Controls {
+            keys: vec![
+                (Action::MoveLeft, KeyCode::Left),
+                (Action::MoveLeft, KeyCode::A),
+                (Action::MoveRight, KeyCode::Right),
+                (Action::MoveRight, KeyCode::D),
+                (Action::Jump, KeyCode::Up),
+                (Action::Jump, KeyCode::W),
+                (Action::Jump, KeyCode::Space),
+                (Action::MoveDown, KeyCode::Down),
+                (Action::MoveDown, KeyCode::S),
+                (Action::Dash, KeyCode::LeftShift),
+                (Action::Dash, KeyCode::K),
+            ],
+            buttons: vec![
+                (Action::MoveLeft, PadButton::DPadLeft),
+                (Action::MoveRight, PadButton::DPadRight),
+                (Action::Jump, PadButton::South),
+                (Action::Jump, PadButton::DPadUp),
+                (Action::MoveDown, PadButton::DPadDown),
+                (Action::Dash, PadButton::West),
+                (Action::Dash, PadButton::RightTrigger),
+            ],
+            dead_zone: 0.2,
+        }
+    }
+}
+
+impl Controls {
+    /// Keys bound to `action`
+    pub fn keys(&self, action: Action) -> impl Iterator<Item = KeyCode> + '_ {
+        self.keys
+            .iter()
+            .filter(move |(bound, _)| *bound == action)
+            .map(|(_, key)| *key)
+    }
+
+    /// Gamepad buttons bound to `action`
+    pub fn buttons(&self, action: Action) -> impl Iterator<Item = PadButton> + '_ {
+        self.buttons
+            .iter()
+            .filter(move |(bound, _)| *bound == action)
+            .map(|(_, button)| *button)
+    }
+
+    /// Make `key` the only key bound to `action`. Keys that already trigger another action
+    /// are refused rather than taken from it, so no action is ever left unbound.
+    pub fn rebind(&mut self, action: Action, key: KeyCode) -> Result<(), BindError> {
+        if !BINDABLE_KEYS.contains(&key) {
+            return Err(BindError::Reserved);
+        }
+        rebind(&mut self.keys, action, key)
+    }
+
+    /// Make `button` the only gamepad button bound to `action`, refusing buttons of other
+    /// actions like `rebind` does for keys
+    pub fn rebind_button(&mut self, action: Action, button: PadButton) -> Result<(), BindError> {
+        rebind(&mut self.buttons, action, button)
+    }
+
+    /// Stick position with the dead zone cut out, rescaled so it still reaches -1 and 1
+    pub fn stick_axis(&self, raw: f32) -> f32 {
+        let magnitude = raw.abs().min(1.0);
+        if magnitude < self.dead_zone {
+            return 0.0;
+        }
+        raw.signum() * (magnitude - self.dead_zone) / (1.0 - self.dead_zone)
+    }
+}
+
+/// Action other than `action` that `input` is bound to
+fn conflict<T: PartialEq>(bindings: &[(Action, T)], action: Action, input: &T) -> Option<Action> {
+    bindings
+        .iter()
+        .find(|(bound, bound_input)| *bound != action && bound_input == input)
+        .map(|(bound, _)| *bound)
+}
+
+fn rebind<T: PartialEq>(
+    bindings: &mut Vec<(Action, T)>,
+    action: Action,
+    input: T,
+) -> Result<(), BindError> {
+    if let Some(other) = conflict(bindings, action, &input) {
+        return Err(BindError::Conflict(other));
+    }
+    // Keep the action's place in the list so the controls screen doesn't reorder
+    let position = bindings
+        .iter()
+        .position(|(bound, _)| *bound == action)
+        .unwrap_or(bindings.len());
+    bindings.retain(|(bound, _)| *bound != action);
+    bindings.insert(position, (action, input));
+    Ok(())
+}
+
+/// Saves keys by name, since `KeyCode` has no serde support
+mod key_names {
+    use super::{Action, key_from_name, key_name};
+    use macroquad::input::KeyCode;
+    use serde::de::Error;
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(
+        keys: &[(Action, KeyCode)],
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(keys.iter().map(|(action, key)| (action, key_name(*key))))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Vec<(Action, KeyCode)>, D::Error> {
+        let named: Vec<(Action, String)> = Vec::deserialize(deserializer)?;
+        named
+            .into_iter()
+            .map(|(action, name)| match key_from_name(&name) {
+                Some(key) => Ok((action, key)),
+                None => Err(D::Error::custom(format!("unknown key {}", name))),
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rebind_replaces_keys() {
+        let mut controls = Controls::default();
+        controls.rebind(Action::Jump, KeyCode::J).unwrap();
+        assert_eq!(
+            controls.keys(Action::Jump).collect::<Vec<_>>(),
+            vec![KeyCode::J]
+        );
+    }
+
+    #[test]
+    fn test_rebind_refuses_conflicts() {
+        let mut controls = Controls::default();
+        assert_eq!(
+            controls.rebind(Action::Jump, KeyCode::A),
+            Err(BindError::Conflict(Action::MoveLeft))
+        );
+        assert_eq!(
+            controls.rebind(Action::Jump, KeyCode::Escape),
+            Err(BindError::Reserved)
+        );
+        // Binding a key the action already has is not a conflict
+        assert_eq!(controls.rebind(Action::Jump, KeyCode::W), Ok(()));
+        assert_eq!(controls.keys, {
+            let mut expected = Controls::default().keys;
+            expected.retain(|(action, key)| *action != Action::Jump || *key == KeyCode::W);
+            expected
+        });
+    }
+
+    #[test]
+    fn test_rebind_button_refuses_conflicts() {
+        let mut controls = Controls::default();
+        assert_eq!(
+            controls.rebind_button(Action::Jump, PadButton::West),
+            Err(BindError::Conflict(Action::Dash))
+        );
+        assert_eq!(controls.buttons, Controls::default().buttons);
+
+        controls
+            .rebind_button(Action::Jump, PadButton::DPadUp)
+            .unwrap();
+        assert_eq!(
+            controls.buttons(Action::Jump).collect::<Vec<_>>(),
+            vec![PadButton::DPadUp]
+        );
+        assert_eq!(controls.buttons[2], (Action::Jump, PadButton::DPadUp));
+    }
+
+    #[test]
+    fn test_stick_axis_cuts_out_dead_zone() {
+        let controls = Controls {
+            dead_zone: 0.2,
+            ..Controls::default()
+        };
+        assert_eq!(controls.stick_axis(0.1), 0.0);
+        assert_eq!(controls.stick_axis(-0.19), 0.0);
+        assert_eq!(controls.stick_axis(0.2), 0.0);
+        assert!((controls.stick_axis(0.6) - 0.5).abs() < 1e-6);
+        assert!((controls.stick_axis(-0.6) + 0.5).abs() < 1e-6);
+        assert_eq!(controls.stick_axis(1.0), 1.0);
+        assert_eq!(
+            controls.stick_axis(-1.5),
+            -1.0,
+            "Past the edge stays at full tilt"
+        );
+    }
+
+    #[test]
+    fn test_controls_round_trip_through_json() {
+        let mut controls = Controls::default();
+        controls.rebind(Action::Dash, KeyCode::Kp0).unwrap();
+        let json = serde_json::to_string(&controls).unwrap();
+        assert!(json.contains("\"Kp0\""));
+
+        let loaded: Controls = serde_json::from_str(&json).unwrap();
+        assert_eq!(loaded, controls);
+    }
+}
@@ -1,7 +1,9 @@
+pub mod controls;
 pub mod settings_state;
 pub mod ui_state;
 
 // re-export at `crate::game_logic::…`
 pub use ui_state::{Screen, UiState};
 
+pub use controls::{Action, Controls};
 pub use settings_state::SettingsState;
@@ -1,4 +1,36 @@
-#[derive(Debug, Clone, PartialEq, Default)]
+//! Client settings, saved as JSON so they survive restarts
+
+use std::fs::File;
+use std::io;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use super::Controls;
+
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+#[serde(default)]
 pub struct SettingsState {
     pub delay: u64,
+    pub controls: Controls,
+}
+
+impl SettingsState {
+    /// Settings saved at `path`, `None` if there are none or they can't be read
+    pub fn load(path: &Path) -> Option<SettingsState> {
+        let file = File::open(path).ok()?;
+        match serde_json::from_reader(file) {
+            Ok(settings) => Some(settings),
+            Err(e) => {
+                eprintln!("Ignoring settings in {}: {}", path.display(), e);
+                None
+            }
+        }
+    }
+
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        let file = File::create(path)?;
+        serde_json::to_writer_pretty(file, self)?;
+        Ok(())
+    }
 }
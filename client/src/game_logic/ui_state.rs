@@ -1,4 +1,6 @@
-/// UI state machine: manages which screen is active and navigation history
+//! UI state machine: manages which screen is active and navigation history
+
+use super::Action;
 
 /// All possible UI screens
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -7,6 +9,10 @@ pub enum Screen {
     InGame,
     PauseMenu,
     Settings,
+    /// Key and gamepad bindings, reached from Settings
+    Controls,
+    /// Finish order of the last round, shown over the game until the next countdown
+    RaceResults,
     // add more screens as needed
 }
 
@@ -14,6 +20,11 @@ pub enum Screen {
 #[derive(Debug, Default)]
 pub struct UiState {
     stack: Vec<Screen>,
+    show_net_stats: bool,
+    /// Action on the controls screen waiting for a key press
+    rebinding: Option<Action>,
+    /// Why the last rebind failed, shown on the controls screen
+    rebind_notice: Option<String>,
 }
 
 impl UiState {
@@ -21,6 +32,9 @@ impl UiState {
     pub fn new() -> Self {
         UiState {
             stack: vec![Screen::MainMenu],
+            show_net_stats: false,
+            rebinding: None,
+            rebind_notice: None,
         }
     }
 
@@ -48,4 +62,35 @@ impl UiState {
         self.stack.clear();
         self.stack.push(screen);
     }
+
+    /// Whether the network statistics overlay is shown in the HUD
+    pub fn show_net_stats(&self) -> bool {
+        self.show_net_stats
+    }
+
+    /// Show or hide the network statistics overlay
+    pub fn toggle_net_stats(&mut self) {
+        self.show_net_stats = !self.show_net_stats;
+    }
+
+    /// Action waiting for a key press, if any
+    pub fn rebinding(&self) -> Option<Action> {
+        self.rebinding
+    }
+
+    /// Wait for a key to bind to `action`
+    pub fn start_rebinding(&mut self, action: Action) {
+        self.rebinding = Some(action);
+        self.rebind_notice = None;
+    }
+
+    /// Stop waiting for a key, with a notice if it couldn't be bound
+    pub fn stop_rebinding(&mut self, notice: Option<String>) {
+        self.rebinding = None;
+        self.rebind_notice = notice;
+    }
+
+    pub fn rebind_notice(&self) -> Option<&str> {
+        self.rebind_notice.as_deref()
+    }
 }
@@ -0,0 +1,115 @@
+//! Gamepad input. Only read when the client is built with the `gamepad` feature, otherwise
+//! no gamepad is ever connected.
+
+use crate::game_logic::controls::PadButton;
+use crate::game_logic::{Action, Controls};
+
+/// What the connected gamepad holds this frame
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct GamepadInput {
+    /// Raw left stick deflection from -1 to 1, before the dead zone
+    pub stick_x: f32,
+    /// Actions whose bound buttons are pressed
+    pub held: Vec<Action>,
+    /// Bindable button pushed down since the last poll, for the controls screen
+    pub pressed: Option<PadButton>,
+}
+
+pub struct Gamepads {
+    #[cfg(feature = "gamepad")]
+    gilrs: Option<gilrs::Gilrs>,
+}
+
+#[cfg(feature = "gamepad")]
+impl Gamepads {
+    pub fn connect() -> Self {
+        let gilrs = match gilrs::Gilrs::new() {
+            Ok(gilrs) => Some(gilrs),
+            Err(e) => {
+                eprintln!("Gamepad input unavailable: {}", e);
+                None
+            }
+        };
+        Gamepads { gilrs }
+    }
+
+    /// Name of the gamepad input is read from, if one is connected
+    pub fn name(&self) -> Option<String> {
+        let (_, gamepad) = self.gilrs.as_ref()?.gamepads().next()?;
+        Some(gamepad.name().to_owned())
+    }
+
+    /// Read the first connected gamepad
+    pub fn poll(&mut self, controls: &Controls) -> GamepadInput {
+        let Some(gilrs) = self.gilrs.as_mut() else {
+            return GamepadInput::default();
+        };
+        // Gamepad state is only updated while handling events
+        let mut pressed = None;
+        while let Some(event) = gilrs.next_event() {
+            if let gilrs::EventType::ButtonPressed(button, _) = event.event {
+                pressed = pressed.or(from_gilrs(button));
+            }
+        }
+
+        let Some((_, gamepad)) = gilrs.gamepads().next() else {
+            return GamepadInput::default();
+        };
+        let held = Action::ALL
+            .into_iter()
+            .filter(|action| {
+                controls
+                    .buttons(*action)
+                    .any(|button| gamepad.is_pressed(to_gilrs(button)))
+            })
+            .collect();
+        GamepadInput {
+            stick_x: gamepad.value(gilrs::Axis::LeftStickX),
+            held,
+            pressed,
+        }
+    }
+}
+
+#[cfg(feature = "gamepad")]
+fn to_gilrs(button: PadButton) -> gilrs::Button {
+    match button {
+        PadButton::South => gilrs::Button::South,
+        PadButton::West => gilrs::Button::West,
+        PadButton::RightTrigger => gilrs::Button::RightTrigger2,
+        PadButton::DPadUp => gilrs::Button::DPadUp,
+        PadButton::DPadDown => gilrs::Button::DPadDown,
+        PadButton::DPadLeft => gilrs::Button::DPadLeft,
+        PadButton::DPadRight => gilrs::Button::DPadRight,
+    }
+}
+
+/// `None` for buttons that can't be bound
+#[cfg(feature = "gamepad")]
+fn from_gilrs(button: gilrs::Button) -> Option<PadButton> {
+    match button {
+        gilrs::Button::South => Some(PadButton::South),
+        gilrs::Button::West => Some(PadButton::West),
+        gilrs::Button::RightTrigger2 => Some(PadButton::RightTrigger),
+        gilrs::Button::DPadUp => Some(PadButton::DPadUp),
+        gilrs::Button::DPadDown => Some(PadButton::DPadDown),
+        gilrs::Button::DPadLeft => Some(PadButton::DPadLeft),
+        gilrs::Button::DPadRight => Some(PadButton::DPadRight),
+        _ => None,
+    }
+}
+
+#[cfg(not(feature = "gamepad"))]
+impl Gamepads {
+    pub fn connect() -> Self {
+        Gamepads {}
+    }
+
+    pub fn name(&self) -> Option<String> {
+        None
+    }
+
+    pub fn poll(&mut self, _controls: &Controls) -> GamepadInput {
+        GamepadInput::default()
+    }
+}
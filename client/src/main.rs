@@ -1,30 +1,22 @@
 mod game_logic;
-mod interpolator;
-mod predictor;
+mod gamepad;
 mod render;
 mod ui;
 
-use flatbuffers::FlatBufferBuilder;
-use interpolator::Interpolator;
+use client_core::{ClientSession, Connection, FrameInput, Scene};
+use gamepad::{GamepadInput, Gamepads};
 use macroquad::math::f32;
 use macroquad::prelude::*;
-use predictor::Predictor;
-use serde::Deserialize;
-use shared::generated;
 use shared::state;
-use state::{GameState, PlayerState, PlayerStateCommand};
-use std::collections::{HashMap, VecDeque};
-use std::fs::File;
-use std::net::UdpSocket;
-use std::sync::Arc;
-use std::sync::mpsc::{self, Receiver, Sender};
-use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
-use std::{io, thread};
-use ui::screens::settings_menu;
+use state::RacePhase;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+use ui::screens::{controls_menu, settings_menu};
 
-use crate::game_logic::{Screen, SettingsState, UiState};
+use crate::game_logic::{Action, Controls, Screen, SettingsState, UiState};
 use crate::render::render;
-use crate::ui::{UiContext, pause_menu, screens::hud, screens::main_menu};
+use crate::ui::{UiContext, pause_menu, screens::hud, screens::main_menu, screens::race_results};
 
 const CLIENT_ADDR: &str = "127.0.0.1:0";
 const SERVER_ADDR: &str = "127.0.0.1:9000";
@@ -36,37 +28,12 @@ const SCREEN_CLAMP_DISTANCE_Y: f32 = 400.0;
 const FONT_SIZE: f32 = 8.0;
 const DELAY_MILLIS: u64 = 300;
 const SCENE_NAME: &str = "scene_3";
+/// Client settings file, next to the client's manifest like the scenes
+const SETTINGS_FILE: &str = "settings.json";
 
 const SCALE: f32 = 1.0;
 const FULLSCREEN: bool = false;
 
-#[derive(Debug, Deserialize, Clone)]
-struct RgbaColor {
-    r: u8,
-    g: u8,
-    b: u8,
-    a: u8,
-}
-#[derive(Debug, Deserialize, Clone)]
-struct SceneObject {
-    x: f32,
-    y: f32,
-    w: f32,
-    h: f32,
-    color: RgbaColor,
-    z: f32,
-}
-
-#[derive(Debug, Deserialize)]
-struct Scene {
-    decorations: HashMap<u32, SceneObject>,
-    collidables: HashMap<u32, SceneObject>,
-    width: f32,
-    height: f32,
-    background_color: RgbaColor,
-    border_color: RgbaColor,
-}
-
 fn window_conf() -> Conf {
     Conf {
         window_title: "Multi".to_owned(),
@@ -81,158 +48,86 @@ fn window_conf() -> Conf {
     }
 }
 
-struct Client {
-    socket: UdpSocket,
-    command_sender: Sender<PlayerStateCommand>,
-    state_sender: Sender<StateData>,
-    settings_sender: Sender<SettingsState>,
-}
-
-type StateData = (GameState, PlayerState, u32, u64);
-
-type NewClientResult = io::Result<(
-    Client,
-    Receiver<PlayerStateCommand>,
-    Receiver<StateData>,
-    Receiver<SettingsState>,
-)>;
-
-impl Client {
-    fn new() -> NewClientResult {
-        let socket = UdpSocket::bind(CLIENT_ADDR)?;
-        let (command_sender, command_receiver) = mpsc::channel();
-        let (state_sender, state_receiver) = mpsc::channel();
-        let (settings_sender, settings_receiver) = mpsc::channel();
-
-        Ok((
-            Client {
-                socket,
-                command_sender,
-                state_sender,
-                settings_sender,
-            },
-            command_receiver,
-            state_receiver,
-            settings_receiver,
-        ))
-    }
-
-    async fn start_game_loop(
-        self: Arc<Self>,
-        state_receiver: Receiver<StateData>,
-    ) -> io::Result<()> {
-        let mut client_player_id = 1;
-
-        // Prediction + reconciliation
-        let mut game_state = GameState::new(SCENE_NAME);
-        let mut predictor = Predictor::new();
-
-        // Interpolation
-        let mut interpolator = Interpolator::new(&game_state);
-
-        // Loading game scene
-        let project_root = env!("CARGO_MANIFEST_DIR");
-        let file = File::open(format!("{}/../scenes/{}.json", project_root, SCENE_NAME))
-            .expect("Scene file must open");
-        let scene: Scene = serde_json::from_reader(file).expect("JSON must match Scene");
-        let mut last_frame = Instant::now();
-
-        let mut ui = UiContext::new();
-        let mut ui_state = UiState::new();
-        let mut delay_enabled = true;
-
-        loop {
-            // Get Unix epoch timestamp (absolute time)
-            let unix_timestamp_micro = SystemTime::now()
-                .duration_since(UNIX_EPOCH)
-                .unwrap_or_default()
-                .as_micros() as u64;
-
-            // Get new game state (if available)
-            if let Ok((
-                server_game_state,
-                server_client_player,
-                server_sequence,
-                server_timestamp,
-            )) = state_receiver.try_recv()
-            {
-                interpolator.set_new_state(server_game_state.clone());
+/// Draws the session and feeds it input, one frame per iteration
+async fn game_loop(mut session: ClientSession) -> io::Result<()> {
+    let scene = Scene::load(SCENE_NAME);
+    let mut last_frame = Instant::now();
+
+    let mut ui = UiContext::new();
+    let mut ui_state = UiState::new();
+    let project_root = env!("CARGO_MANIFEST_DIR");
+    let settings_path = PathBuf::from(format!("{}/{}", project_root, SETTINGS_FILE));
+    let mut settings = SettingsState::load(&settings_path).unwrap_or(SettingsState {
+        delay: DELAY_MILLIS,
+        ..Default::default()
+    });
+    session.set_delay(settings.delay);
+    let mut gamepads = Gamepads::connect();
+    let mut desyncs = 0;
+
+    loop {
+        // Get accurate frame timing
+        let now = Instant::now();
+        let dt_micros = now.duration_since(last_frame).as_micros() as u64;
+        last_frame = now;
+
+        // Get commands
+        let gamepad = gamepads.poll(&settings.controls);
+        let input = input_handler(&mut ui_state, &settings.controls, &gamepad);
+
+        // Rendering game
+        let frame = session.poll(input, dt_micros);
+        render(frame.game_state, frame.client_player_id, &scene);
+
+        // Results are shown over the game until the next countdown
+        match (frame.race.phase(), ui_state.current_screen()) {
+            (RacePhase::Results, Screen::InGame) => ui_state.push(Screen::RaceResults),
+            (phase, Screen::RaceResults) if phase != RacePhase::Results => ui_state.pop(),
+            _ => {}
+        }
 
-                client_player_id = server_client_player.id;
-                game_state.players = server_game_state.players;
-                game_state
-                    .players
-                    .insert(server_client_player.id, server_client_player);
+        let desync = &session.predictor.desync;
+        if desync.mismatches() > desyncs {
+            desyncs = desync.mismatches();
+            eprintln!(
+                "Desync ({} of {} checks mismatched)",
+                desync.mismatches(),
+                desync.checks()
+            );
+        }
 
-                let server_delay = unix_timestamp_micro.max(server_timestamp) - server_timestamp;
+        // begin UI frame
+        ui.begin_frame();
 
-                // reconciliation
-                predictor.reconciliation(
-                    &mut game_state,
-                    server_sequence,
+        let client_player_id = session.client_player_id();
+        match ui_state.current_screen() {
+            Screen::MainMenu => main_menu(&mut ui, &mut ui_state),
+            Screen::InGame => {
+                let net_stats = session.net_stats();
+                hud(
+                    &mut ui,
+                    &mut ui_state,
+                    session.game_state(),
+                    &net_stats,
+                    session.race(),
                     client_player_id,
-                    server_delay,
-                );
-            }
-
-            // Get accurate frame timing
-            let now = Instant::now();
-            let dt_micros = now.duration_since(last_frame).as_micros() as u64;
-            last_frame = now;
-
-            // Get commands
-            let commands = input_handler(&mut ui_state);
-            let player_state_command = match commands.is_empty() {
-                true => None,
-                false => Some(PlayerStateCommand {
-                    sequence: predictor.sequence,
-                    dt_micros,
-                    commands,
-                    client_timestamp_micros: unix_timestamp_micro,
-                }),
-            };
-
-            // Mutate local state
-            predictor.predict(
-                &mut game_state,
-                client_player_id,
-                player_state_command.as_ref(),
-                dt_micros,
-            );
-
-            // Send command to network thread if exists
-            if let Some(player_state_command) = player_state_command {
-                if let Err(e) = self.command_sender.send(player_state_command) {
-                    eprintln!("Error sending player state command: {}", e);
-                }
+                    &scene,
+                )
             }
-
-            // Interpolation
-            interpolator.interpolate(&mut game_state, client_player_id);
-
-            // Rendering game
-            render(&game_state, client_player_id, &scene);
-
-            // begin UI frame
-            ui.begin_frame();
-
-            match ui_state.current_screen() {
-                Screen::MainMenu => main_menu(&mut ui, &mut ui_state),
-                Screen::InGame => hud(&mut ui, &mut ui_state, &game_state, &scene),
-                Screen::PauseMenu => pause_menu(&mut ui, &mut ui_state),
-                Screen::Settings => settings_menu(
+            Screen::RaceResults => race_results(&mut ui, session.race(), session.game_state()),
+            Screen::PauseMenu => pause_menu(&mut ui, &mut ui_state),
+            Screen::Settings => {
+                let mut delay_changed = false;
+                let predictor = &mut session.predictor;
+                settings_menu(
                     &mut ui,
                     &mut ui_state,
-                    delay_enabled,
+                    settings.delay > 0,
                     predictor.active_reconciliation,
                     predictor.active_prediction,
                     || {
-                        delay_enabled = !delay_enabled;
-                        self.settings_sender
-                            .send(SettingsState {
-                                delay: if delay_enabled { 300 } else { 0 },
-                            })
-                            .unwrap();
+                        settings.delay = if settings.delay > 0 { 0 } else { DELAY_MILLIS };
+                        delay_changed = true;
                     },
                     || {
                         predictor.active_reconciliation = !predictor.active_reconciliation;
@@ -240,104 +135,59 @@ impl Client {
                     || {
                         predictor.active_prediction = !predictor.active_prediction;
                     },
-                ),
-            }
-            ui.end_frame();
-
-            next_frame().await;
-        }
-    }
-
-    fn start_network_thread(
-        self: Arc<Client>,
-        command_receiver: Receiver<PlayerStateCommand>,
-        settings_receiver: Receiver<SettingsState>,
-    ) -> io::Result<thread::JoinHandle<()>> {
-        self.socket.set_nonblocking(true)?;
-
-        let mut server_state_queue = VecDeque::new();
-        let mut command_queue = VecDeque::new();
-        let mut delay = DELAY_MILLIS;
-
-        Ok(thread::spawn(move || {
-            let mut buf = [0u8; 2048];
-            loop {
-                while let Ok(new_settings) = settings_receiver.try_recv() {
-                    delay = new_settings.delay;
-                }
-
-                if let Ok((amt, src_addr)) = self.socket.recv_from(&mut buf) {
-                    if src_addr.to_string() != SERVER_ADDR {
-                        continue;
-                    };
-
-                    let apply_when = Instant::now() + Duration::from_millis(delay);
-                    server_state_queue.push_back((apply_when, GameState::deserialize(&buf[..amt])));
-                };
-
-                let mut last_valid_state = None;
-                while let Some((apply_when, _)) = server_state_queue.front() {
-                    if Instant::now() >= *apply_when {
-                        if let Some((_, game_state)) = server_state_queue.pop_front() {
-                            last_valid_state = Some(game_state);
-                        }
-                    } else {
-                        break;
-                    }
-                }
-
-                // Send to game loop if new state is popped from the queue
-                if let Some(game_state) = last_valid_state {
-                    if let Err(e) = self.state_sender.send(game_state) {
-                        eprintln!("Error sending game state: {}", e);
-                    }
-                }
-
-                // Add commands to queue
-                while let Ok(player_state_command) = command_receiver.try_recv() {
-                    command_queue.push_back((
-                        Instant::now() + Duration::from_millis(delay),
-                        player_state_command,
-                    ));
+                );
+                if delay_changed {
+                    session.set_delay(settings.delay);
+                    save_settings(&settings, &settings_path);
                 }
-
-                // Send commands to server when ready
-                while let Some((apply_when, _)) = command_queue.front() {
-                    if Instant::now() >= *apply_when {
-                        if let Some((_, player_state_command)) = command_queue.pop_front() {
-                            let mut builder = FlatBufferBuilder::with_capacity(2048);
-                            let serialized_commands = player_state_command.serialize(&mut builder);
-                            builder.finish(serialized_commands, None);
-                            let bytes = builder.finished_data();
-                            self.socket
-                                .send_to(bytes, SERVER_ADDR)
-                                .expect("Packet couldn't send.");
-                        }
-                    } else {
-                        break;
-                    }
+            }
+            Screen::Controls => {
+                let gamepad_name = gamepads.name();
+                if controls_menu(
+                    &mut ui,
+                    &mut ui_state,
+                    &mut settings.controls,
+                    gamepad_name.as_deref(),
+                    gamepad.pressed,
+                ) {
+                    save_settings(&settings, &settings_path);
                 }
             }
-        }))
+        }
+        ui.end_frame();
+
+        next_frame().await;
     }
 }
 
 #[macroquad::main(window_conf)]
 async fn main() -> io::Result<()> {
-    let (client, command_receiver, state_receiver, settings_receiver) = Client::new()?;
-    let client_arc: Arc<Client> = Arc::new(client);
+    let connection = Connection::connect(CLIENT_ADDR, SERVER_ADDR, DELAY_MILLIS)?;
+    game_loop(ClientSession::new(connection, SCENE_NAME)).await
+}
 
-    client_arc
-        .clone()
-        .start_network_thread(command_receiver, settings_receiver)
-        .expect("Failed to start network thread");
-    client_arc.clone().start_game_loop(state_receiver).await
+fn save_settings(settings: &SettingsState, path: &Path) {
+    if let Err(e) = settings.save(path) {
+        eprintln!("Error saving settings to {}: {}", path.display(), e);
+    }
 }
 
-fn input_handler(ui_state: &mut UiState) -> Vec<generated::PlayerCommand> {
+/// Handle UI keys and return what the player holds
+fn input_handler(
+    ui_state: &mut UiState,
+    controls: &Controls,
+    gamepad: &GamepadInput,
+) -> FrameInput {
     // --- CLIENT/UI INPUT ---
+    if is_key_pressed(KeyCode::F3) {
+        ui_state.toggle_net_stats();
+    }
     if is_key_pressed(KeyCode::Escape) {
         match ui_state.current_screen() {
+            // Escape cancels waiting for a key instead of leaving the controls screen
+            Screen::Controls if ui_state.rebinding().is_some() => {
+                ui_state.stop_rebinding(None);
+            }
             Screen::InGame => {
                 ui_state.push(Screen::PauseMenu);
             }
@@ -351,16 +201,15 @@ fn input_handler(ui_state: &mut UiState) -> Vec<generated::PlayerCommand> {
     }
 
     // --- NETWORK INPUT ---
-    let mut commands = Vec::new();
-    if is_key_down(KeyCode::Right) || is_key_down(KeyCode::D) {
-        commands.push(generated::PlayerCommand::MoveRight);
-    }
-    if is_key_down(KeyCode::Left) || is_key_down(KeyCode::A) {
-        commands.push(generated::PlayerCommand::MoveLeft);
+    let commands = Action::ALL
+        .into_iter()
+        .filter(|action| controls.keys(*action).any(is_key_down) || gamepad.held.contains(action))
+        .map(Action::command)
+        .collect();
+    let move_axis = state::quantize_axis(controls.stick_axis(gamepad.stick_x));
+
+    FrameInput {
+        commands,
+        move_axis,
     }
-    if is_key_down(KeyCode::Up) || is_key_down(KeyCode::W) || is_key_down(KeyCode::Space) {
-        commands.push(generated::PlayerCommand::Jump);
-    }
-
-    commands
 }
@@ -1,11 +1,12 @@
-use crate::state::{GameState, PlayerState};
+use crate::state::{CollidableKind, GameState, PlayerState};
 use crate::{
     FONT_SIZE, PLAYER_SIZE, SCREEN_CLAMP_DISTANCE_X, SCREEN_CLAMP_DISTANCE_Y, SCREEN_HEIGHT,
-    SCREEN_WIDTH, Scene, SceneObject,
+    SCREEN_WIDTH,
 };
-use macroquad::color::{BEIGE, BLUE, GREEN, ORANGE, PINK, PURPLE, RED, WHITE};
+use client_core::scene::{Scene, SceneObject};
+use macroquad::color::{BEIGE, BLUE, GREEN, ORANGE, PINK, PURPLE, RED, WHITE, YELLOW};
 use macroquad::math::{Vec2, vec2};
-use macroquad::shapes::draw_rectangle;
+use macroquad::shapes::{draw_rectangle, draw_rectangle_lines, draw_triangle};
 use macroquad::text::draw_text;
 use macroquad::window::{clear_background, screen_height, screen_width};
 
@@ -58,6 +59,29 @@ pub fn render(game_state: &GameState, client_player_id: u32, scene: &Scene) {
         .chain(scene.collidables.values())
         .cloned()
         .collect();
+    let platform_rects = game_state.moving_platform_rects(game_state.physics_step);
+    for (rect, style) in platform_rects.iter().zip(scene.moving_platforms.values()) {
+        objects.push(SceneObject {
+            x: rect.x,
+            y: rect.y,
+            w: rect.w,
+            h: rect.h,
+            color: style.color.clone(),
+            z: style.z,
+            kind: rect.kind,
+        });
+    }
+    for trigger in scene.triggers.values() {
+        objects.push(SceneObject {
+            x: trigger.x,
+            y: trigger.y,
+            w: trigger.w,
+            h: trigger.h,
+            color: trigger.color.clone(),
+            z: trigger.z,
+            kind: CollidableKind::Solid,
+        });
+    }
     objects.sort_by(|a, b| b.z.total_cmp(&a.z));
 
     // frame
@@ -107,13 +131,27 @@ fn draw_player(scale: f32, world_offset: Vec2, p: (&u32, &PlayerState)) {
         PLAYER_SIZE * scale,
         col,
     );
+    // The tagger in tag mode gets an outline and a highlighted name
+    let name_color = if p.1.is_it {
+        draw_rectangle_lines(
+            world_offset.x + p.1.pos.x * scale,
+            world_offset.y + p.1.pos.y * scale,
+            PLAYER_SIZE * scale,
+            PLAYER_SIZE * scale,
+            3.0 * scale,
+            YELLOW,
+        );
+        YELLOW
+    } else {
+        WHITE
+    };
     draw_text(
         &p.1.name[..],
         world_offset.x
             + (p.1.pos.x + PLAYER_SIZE / 2.0 - FONT_SIZE * p.1.name.len() as f32 / 4.9) * scale,
         world_offset.y + (p.1.pos.y - 4.0) * scale,
         FONT_SIZE * scale,
-        WHITE,
+        name_color,
     );
 }
 
@@ -137,5 +175,17 @@ fn draw_scene_obj(
     let screen_x = offset.x + screen_center_scaled.x + (obj.x - cam_pos.x * pms) * scale;
     let screen_y = offset.y + screen_center_scaled.y + (obj.y - cam_pos.y * pms) * scale;
 
-    draw_rectangle(screen_x, screen_y, obj.w * scale, obj.h * scale, col);
+    match obj.kind {
+        CollidableKind::Slope { left, right } => {
+            // Box corners below the surface, split into two triangles
+            let bottom = screen_y + obj.h * scale;
+            let bottom_left = vec2(screen_x, bottom);
+            let bottom_right = vec2(screen_x + obj.w * scale, bottom);
+            let top_left = vec2(screen_x, bottom - left * scale);
+            let top_right = vec2(screen_x + obj.w * scale, bottom - right * scale);
+            draw_triangle(bottom_left, bottom_right, top_right, col);
+            draw_triangle(bottom_left, top_right, top_left, col);
+        }
+        _ => draw_rectangle(screen_x, screen_y, obj.w * scale, obj.h * scale, col),
+    }
 }
@@ -1,11 +1,17 @@
-use crate::Scene;
-use crate::game_logic::{Screen, UiState};
-use crate::state::GameState;
+use crate::game_logic::controls::{BindError, PadButton, key_name};
+use crate::game_logic::{Action, Controls, Screen, UiState};
+use crate::state::{GameState, RacePhase, RaceView};
 use crate::ui::{Button, DrawCmd, Label, UiContext, UiResponse, VBox, Widget};
-use macroquad::math::{Rect, vec2};
+use client_core::Scene;
+use macroquad::color::Color;
+use macroquad::input::get_last_key_pressed;
+use macroquad::math::{Rect, Vec2, vec2};
 use macroquad::time::{get_fps, get_time};
 use macroquad::window::{screen_height, screen_width, set_fullscreen};
 
+use shared::net_stats::NetStats;
+use shared::transport::{Clock, SystemClock};
+
 use super::widget::Toggle;
 
 // throttle FPS meter updates to every 0.2 seconds
@@ -13,6 +19,13 @@ static mut LAST_FPS_UPDATE: f64 = 0.0;
 static mut DISPLAY_FPS: f32 = 0.0;
 const FPS_UPDATE_INTERVAL: f64 = 0.2;
 
+// network statistics graph
+const NET_GRAPH_HEIGHT: f32 = 40.0;
+const NET_GRAPH_BAR_WIDTH: f32 = 3.0;
+
+// countdown digits are drawn this many times the normal font size
+const COUNTDOWN_FONT_SCALE: f32 = 4.0;
+
 /// Main menu screen: shows title and navigation buttons.
 pub fn main_menu(ctx: &mut UiContext, state: &mut UiState) {
     let area = Rect::new(0.0, 0.0, screen_width(), screen_height());
@@ -41,15 +54,16 @@ pub fn main_menu(ctx: &mut UiContext, state: &mut UiState) {
 }
 
 /// Settings menu: adjust game options
+#[allow(clippy::too_many_arguments)]
 pub fn settings_menu(
     ctx: &mut UiContext,
     state: &mut UiState,
     delay: bool,
     reconciliation: bool,
     prediction: bool,
-    mut on_delay_change: impl FnMut() -> (),
-    mut on_reconciliation_change: impl FnMut() -> (),
-    mut on_prediction_change: impl FnMut() -> (),
+    mut on_delay_change: impl FnMut(),
+    mut on_reconciliation_change: impl FnMut(),
+    mut on_prediction_change: impl FnMut(),
 ) {
     let area = Rect::new(0.0, 0.0, screen_width(), screen_height());
     let mut menu = VBox::new(20.0, 10.0);
@@ -63,6 +77,11 @@ pub fn settings_menu(
         set_fullscreen(true);
     }
 
+    let controls_area = menu.item(ctx, vec2(200.0, 50.0));
+    if Button::new("Controls").ui(ctx, controls_area) == UiResponse::Clicked {
+        state.push(Screen::Controls);
+    }
+
     let back_area = menu.item(ctx, vec2(200.0, 50.0));
     if Button::new("Back").ui(ctx, back_area) == UiResponse::Clicked {
         state.pop();
@@ -98,8 +117,109 @@ pub fn settings_menu(
     menu.end(ctx);
 }
 
+/// Controls screen: lists the bindings of each action. Clicking an action waits for a key
+/// or a gamepad button (`pad_pressed`) to bind to it instead; Escape cancels. Returns
+/// whether `controls` changed.
+pub fn controls_menu(
+    ctx: &mut UiContext,
+    state: &mut UiState,
+    controls: &mut Controls,
+    gamepad: Option<&str>,
+    pad_pressed: Option<PadButton>,
+) -> bool {
+    let mut changed = false;
+    if let Some(action) = state.rebinding() {
+        let bound = if let Some(key) = get_last_key_pressed() {
+            Some((controls.rebind(action, key), key_name(key)))
+        } else {
+            pad_pressed.map(|button| {
+                (
+                    controls.rebind_button(action, button),
+                    format!("{:?}", button),
+                )
+            })
+        };
+        if let Some((result, name)) = bound {
+            let notice = match result {
+                Ok(()) => None,
+                Err(BindError::Conflict(other)) => {
+                    Some(format!("{} is already bound to {}", name, other.label()))
+                }
+                Err(BindError::Reserved) => Some(format!("{} can't be bound", name)),
+            };
+            changed = notice.is_none();
+            state.stop_rebinding(notice);
+        }
+    }
+
+    let area = Rect::new(0.0, 0.0, screen_width(), screen_height());
+    let mut menu = VBox::new(20.0, 10.0);
+    menu.begin(ctx, area);
+
+    let title_area = menu.item(ctx, vec2(300.0, 50.0));
+    Label::new("Controls").ui(ctx, title_area);
+
+    for action in Action::ALL {
+        let row = if state.rebinding() == Some(action) {
+            format!("{}: press a key or button", action.label())
+        } else {
+            let keys: Vec<String> = controls.keys(action).map(key_name).collect();
+            let buttons: Vec<String> = controls
+                .buttons(action)
+                .map(|b| format!("{:?}", b))
+                .collect();
+            format!(
+                "{}: {} / {}",
+                action.label(),
+                keys.join(", "),
+                buttons.join(", ")
+            )
+        };
+        let row_area = menu.item(ctx, vec2(400.0, 20.0));
+        if Button::new(row).ui(ctx, row_area) == UiResponse::Clicked {
+            state.start_rebinding(action);
+        }
+    }
+
+    if let Some(notice) = state.rebind_notice() {
+        let notice_area = menu.item(ctx, vec2(400.0, 20.0));
+        Label::new(notice).ui(ctx, notice_area);
+    }
+
+    let gamepad_text = match gamepad {
+        Some(name) => format!("Gamepad: {} (left stick moves)", name),
+        None => "No gamepad connected".to_string(),
+    };
+    let gamepad_area = menu.item(ctx, vec2(400.0, 20.0));
+    Label::new(gamepad_text).ui(ctx, gamepad_area);
+
+    let reset_area = menu.item(ctx, vec2(200.0, 50.0));
+    if Button::new("Reset to defaults").ui(ctx, reset_area) == UiResponse::Clicked {
+        *controls = Controls::default();
+        state.stop_rebinding(None);
+        changed = true;
+    }
+
+    let back_area = menu.item(ctx, vec2(200.0, 50.0));
+    if Button::new("Back").ui(ctx, back_area) == UiResponse::Clicked {
+        state.stop_rebinding(None);
+        state.pop();
+    }
+
+    menu.end(ctx);
+    changed
+}
+
 /// In-game HUD: shows FPS and player count, updated at a fixed interval
-pub fn hud(ctx: &mut UiContext, state: &mut UiState, game_state: &GameState, _scene: &Scene) {
+pub fn hud(
+    ctx: &mut UiContext,
+    state: &mut UiState,
+    game_state: &GameState,
+    net_stats: &NetStats,
+    race: &RaceView,
+    client_player_id: u32,
+    _scene: &Scene,
+) {
     // throttle FPS updates
     let now = get_time();
     unsafe {
@@ -125,6 +245,12 @@ pub fn hud(ctx: &mut UiContext, state: &mut UiState, game_state: &GameState, _sc
         color: ctx.theme.text_color,
     });
 
+    race_status(ctx, race, client_player_id);
+
+    if state.show_net_stats() {
+        net_stats_overlay(ctx, net_stats, vec2(10.0, 20.0 + ctx.font_size * 3.0));
+    }
+
     // Pause button in HUD
     let sw = screen_width();
     let pause_size = vec2(60.0, 30.0);
@@ -139,6 +265,142 @@ pub fn hud(ctx: &mut UiContext, state: &mut UiState, game_state: &GameState, _sc
     }
 }
 
+/// Race countdown, the running time and where the client player finished
+fn race_status(ctx: &mut UiContext, race: &RaceView, client_player_id: u32) {
+    let now = SystemClock.now_micros();
+    let status = match race.phase() {
+        RacePhase::Waiting | RacePhase::Results => return,
+        RacePhase::Countdown => {
+            let digit = format!("{}", race.seconds_left(now).ceil().max(1.0));
+            let font_size = ctx.font_size * COUNTDOWN_FONT_SCALE;
+            ctx.push_cmd(DrawCmd::Text {
+                text: digit,
+                pos: vec2(
+                    screen_width() / 2.0 - font_size / 4.0,
+                    screen_height() / 2.0,
+                ),
+                font_size,
+                color: ctx.theme.text_color,
+            });
+            format!("Round {}: get ready", race.round())
+        }
+        RacePhase::Running => match race.result_for(client_player_id) {
+            Some((place, result)) => format!(
+                "Round {}: finished #{} in {:.2}s",
+                race.round(),
+                place,
+                result.time
+            ),
+            None => format!(
+                "Round {}: {:.2}s",
+                race.round(),
+                race.run_time(now).unwrap_or(0.0)
+            ),
+        },
+    };
+    ctx.push_cmd(DrawCmd::Text {
+        text: status,
+        pos: vec2(screen_width() / 2.0 - 80.0, 20.0),
+        font_size: ctx.font_size,
+        color: ctx.theme.text_color,
+    });
+}
+
+/// Round results until the server starts the next round: finish times in a race, time as
+/// "it" in tag
+pub fn race_results(ctx: &mut UiContext, race: &RaceView, game_state: &GameState) {
+    let area = Rect::new(0.0, 0.0, screen_width(), screen_height());
+    let mut menu = VBox::new(20.0, 4.0);
+    menu.begin(ctx, area);
+
+    let title_area = menu.item(ctx, vec2(300.0, 40.0));
+    Label::new(format!("Round {} results", race.round())).ui(ctx, title_area);
+
+    let mut finished = 0;
+    for (place, result) in race.results() {
+        let name = game_state
+            .players
+            .get(&result.player_id)
+            .map(|p| p.name.clone())
+            .unwrap_or_else(|| format!("Player {}", result.player_id));
+        let line_area = menu.item(ctx, vec2(300.0, 24.0));
+        Label::new(format!("{}. {}  {:.2}s", place, name, result.time)).ui(ctx, line_area);
+        finished += 1;
+    }
+    if finished == 0 {
+        let line_area = menu.item(ctx, vec2(300.0, 24.0));
+        Label::new("Nobody finished").ui(ctx, line_area);
+    }
+
+    let next_area = menu.item(ctx, vec2(300.0, 40.0));
+    Label::new(format!(
+        "Next round in {:.0}s",
+        race.seconds_left(SystemClock.now_micros()).ceil()
+    ))
+    .ui(ctx, next_area);
+
+    menu.end(ctx);
+}
+
+/// Network statistics: current numbers plus a graph of incoming bandwidth
+fn net_stats_overlay(ctx: &mut UiContext, net_stats: &NetStats, origin: Vec2) {
+    let summary = net_stats.summary();
+    let lines = [
+        format!(
+            "In: {:.0} B/s ({:.0} pkt/s)",
+            summary.bytes_received_per_sec, summary.packets_received_per_sec
+        ),
+        format!(
+            "Out: {:.0} B/s ({:.0} pkt/s)",
+            summary.bytes_sent_per_sec, summary.packets_sent_per_sec
+        ),
+        format!("Avg packet: {:.0} B", summary.avg_packet_size),
+        format!("Loss: {:.1}%", summary.loss * 100.0),
+        format!(
+            "RTT: {:.0} ms (jitter {:.0} ms)",
+            summary.rtt_ms, summary.jitter_ms
+        ),
+    ];
+    let line_height = ctx.font_size * 1.5;
+    let graph_top = origin.y + lines.len() as f32 * line_height - ctx.font_size / 2.0;
+    for (i, line) in lines.into_iter().enumerate() {
+        ctx.push_cmd(DrawCmd::Text {
+            text: line,
+            pos: vec2(origin.x, origin.y + i as f32 * line_height),
+            font_size: ctx.font_size,
+            color: ctx.theme.text_color,
+        });
+    }
+
+    // Bars are scaled to the largest sample in the history
+    let history: Vec<f32> = net_stats
+        .history()
+        .map(|s| s.bytes_received_per_sec)
+        .collect();
+    let max = history.iter().copied().fold(1.0, f32::max);
+    ctx.push_cmd(DrawCmd::Rect {
+        rect: Rect::new(
+            origin.x,
+            graph_top,
+            NET_GRAPH_BAR_WIDTH * history.len().max(1) as f32,
+            NET_GRAPH_HEIGHT,
+        ),
+        color: ctx.theme.button_bg,
+    });
+    for (i, value) in history.iter().enumerate() {
+        let height = value / max * NET_GRAPH_HEIGHT;
+        ctx.push_cmd(DrawCmd::Rect {
+            rect: Rect::new(
+                origin.x + i as f32 * NET_GRAPH_BAR_WIDTH,
+                graph_top + NET_GRAPH_HEIGHT - height,
+                NET_GRAPH_BAR_WIDTH - 1.0,
+                height,
+            ),
+            color: Color::from_rgba(90, 200, 120, 220),
+        });
+    }
+}
+
 /// Pause menu: allows resuming or returning to main menu
 pub fn pause_menu(ctx: &mut UiContext, state: &mut UiState) {
     let area = Rect::new(0.0, 0.0, screen_width(), screen_height());
@@ -0,0 +1,172 @@
+//! End to end tests without sockets, threads or sleeps: the real `GameServer` and real
+//! `ClientSession`s talk over a `LoopbackNetwork` in one process, and time only moves when
+//! the test steps it. Latency, jitter and loss are simulated at the server's end with a
+//! seeded `SimulatedTransport`, so a failing run fails the same way every time.
+//!
+//! ```
+//! # use client_core::FrameInput;
+//! # use harness::{Harness, NetConditions};
+//! # use shared::generated::PlayerCommand;
+//! # use shared::state::ClassicMode;
+//! let mut harness = Harness::new("scene_3", Box::new(ClassicMode), NetConditions::default(), 1);
+//! harness.add_client();
+//! let right = FrameInput { commands: vec![PlayerCommand::MoveRight], move_axis: 0 };
+//! harness.step_ticks(2, &[right]);
+//! assert!(harness.server_player(0).is_some());
+//! ```
+
+use client_core::{ClientSession, FrameInput, TransportLink};
+use server::{GameServer, TICK_DURATION};
+use shared::state::{GameMode, PlayerState};
+use shared::transport::{
+    Clock, LoopbackNetwork, LoopbackTransport, ManualClock, SimulatedTransport,
+};
+use std::net::{Ipv4Addr, SocketAddr, SocketAddrV4};
+
+pub use shared::transport::NetConditions;
+
+/// Length of a client frame, 60 per second
+pub const FRAME_MICROS: u64 = 16_667;
+/// Where the virtual clock starts. A realistic unix time, since timestamps are subtracted
+/// from each other all over the netcode.
+pub const START_MICROS: u64 = 1_700_000_000_000_000;
+pub const SERVER_ADDR: SocketAddr =
+    SocketAddr::V4(SocketAddrV4::new(Ipv4Addr::new(10, 0, 0, 1), 9000));
+
+/// A client session talking straight to the loopback network
+pub type HarnessSession = ClientSession<TransportLink<LoopbackTransport>>;
+
+/// A server and its clients on one virtual network and clock
+pub struct Harness {
+    clock: ManualClock,
+    network: LoopbackNetwork,
+    /// Where the whole network's latency and loss are simulated, since all traffic goes
+    /// through the server
+    transport: SimulatedTransport<LoopbackTransport, ManualClock>,
+    server: GameServer,
+    scene_name: String,
+    clients: Vec<(SocketAddr, HarnessSession)>,
+    last_tick_micros: u64,
+    ticks: u32,
+}
+
+impl Harness {
+    pub fn new(
+        scene_name: &str,
+        mode: Box<dyn GameMode>,
+        conditions: NetConditions,
+        seed: u64,
+    ) -> Self {
+        let clock = ManualClock::new(START_MICROS);
+        let network = LoopbackNetwork::new();
+        let server_end = network
+            .bind(SERVER_ADDR)
+            .expect("A new network has no addresses taken");
+        Harness {
+            transport: SimulatedTransport::with_clock(server_end, clock.clone(), conditions, seed),
+            clock,
+            network,
+            server: GameServer::new(scene_name, mode),
+            scene_name: scene_name.to_string(),
+            clients: Vec::new(),
+            last_tick_micros: START_MICROS,
+            ticks: 0,
+        }
+    }
+
+    /// Start a client session and return its index. The server only learns about it once
+    /// its first command arrives.
+    pub fn add_client(&mut self) -> usize {
+        let index = self.clients.len();
+        let addr = SocketAddr::from(([10, 0, 1, 1], 7000 + index as u16));
+        let transport = self
+            .network
+            .bind(addr)
+            .expect("Every client gets its own port");
+        let link = TransportLink::new(transport, SERVER_ADDR);
+        self.clients
+            .push((addr, ClientSession::new(link, &self.scene_name)));
+        index
+    }
+
+    /// Advance the clock by one frame: deliver what arrived, tick the server when a tick is
+    /// due, then poll every client with its input. Clients past the end of `inputs` hold
+    /// nothing.
+    pub fn step(&mut self, inputs: &[FrameInput]) {
+        self.clock.advance(FRAME_MICROS);
+        let now_micros = self.clock.now_micros();
+        self.server
+            .receive(&self.transport, now_micros)
+            .expect("The loopback network doesn't fail");
+
+        let dt_micros = now_micros - self.last_tick_micros;
+        if dt_micros >= TICK_DURATION.as_micros() as u64 {
+            self.server
+                .tick_and_send(&self.transport, dt_micros, &self.clock);
+            self.last_tick_micros = now_micros;
+            self.ticks += 1;
+        }
+
+        for (index, (_, session)) in self.clients.iter_mut().enumerate() {
+            let input = inputs.get(index).cloned().unwrap_or_default();
+            session.poll_at(input, FRAME_MICROS, now_micros);
+        }
+        // Start the delay of what the clients just sent now rather than next frame
+        self.transport
+            .pump()
+            .expect("The loopback network doesn't fail");
+    }
+
+    /// Step with the same inputs until the server has ticked `ticks` more times
+    pub fn step_ticks(&mut self, ticks: u32, inputs: &[FrameInput]) {
+        let target = self.ticks + ticks;
+        while self.ticks < target {
+            self.step(inputs);
+        }
+    }
+
+    pub fn now_micros(&self) -> u64 {
+        self.clock.now_micros()
+    }
+
+    /// Server ticks so far
+    pub fn ticks(&self) -> u32 {
+        self.ticks
+    }
+
+    pub fn set_conditions(&mut self, conditions: NetConditions) {
+        self.transport.set_conditions(conditions);
+    }
+
+    /// The server's end of the network, where latency and loss are simulated
+    pub fn transport(&self) -> &SimulatedTransport<LoopbackTransport, ManualClock> {
+        &self.transport
+    }
+
+    pub fn server(&self) -> &GameServer {
+        &self.server
+    }
+
+    pub fn client(&self, index: usize) -> &HarnessSession {
+        &self.clients[index].1
+    }
+
+    pub fn client_mut(&mut self, index: usize) -> &mut HarnessSession {
+        &mut self.clients[index].1
+    }
+
+    /// The server's copy of a client's player, once it joined
+    pub fn server_player(&self, index: usize) -> Option<&PlayerState> {
+        let player_id = self.server.player_id(&self.clients[index].0)?;
+        self.server.game_state().players.get(&player_id)
+    }
+
+    /// A client's predicted copy of its own player
+    pub fn predicted_player(&self, index: usize) -> Option<&PlayerState> {
+        let session = self.client(index);
+        session
+            .game_state()
+            .players
+            .get(&session.client_player_id())
+    }
+}
@@ -0,0 +1,191 @@
+use client_core::FrameInput;
+use harness::{Harness, NetConditions};
+use shared::generated::PlayerCommand;
+use shared::state::{ClassicMode, PlayerState, Race};
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+const SCENE_NAME: &str = "scene_3";
+
+fn holding(commands: &[PlayerCommand]) -> FrameInput {
+    FrameInput {
+        commands: commands.to_vec(),
+        move_axis: 0,
+    }
+}
+
+fn distance(a: &PlayerState, b: &PlayerState) -> f32 {
+    let offset = a.pos - b.pos;
+    (offset.x * offset.x + offset.y * offset.y).sqrt()
+}
+
+/// One client that runs right for `frames` then lets go, on a network with `conditions`
+fn run_right_then_stop(conditions: NetConditions, seed: u64, frames: u32) -> Harness {
+    let mut harness = Harness::new(SCENE_NAME, Box::new(ClassicMode), conditions, seed);
+    harness.add_client();
+    for _ in 0..frames {
+        harness.step(&[holding(&[PlayerCommand::MoveRight])]);
+    }
+    harness
+}
+
+#[test]
+fn test_client_joins_and_follows_server() {
+    let mut harness = run_right_then_stop(NetConditions::default(), 1, 30);
+    harness.step_ticks(2, &[]);
+
+    let server_player = harness.server_player(0).expect("Client should have joined");
+    assert_eq!(harness.client(0).client_player_id(), server_player.id);
+    assert!(harness.client(0).snapshots() > 0);
+    assert!(
+        server_player.pos.x > harness.server().game_state().spawn_point.x,
+        "Server should have applied the input"
+    );
+}
+
+#[test]
+fn test_prediction_converges_with_latency_and_loss() {
+    let conditions = NetConditions {
+        rtt: Duration::from_millis(150),
+        jitter: Duration::ZERO,
+        loss: 0.05,
+    };
+    let mut harness = Harness::new(SCENE_NAME, Box::new(ClassicMode), conditions, 1);
+    harness.add_client();
+    // Where the client predicted itself at each physics step, to hold against the server
+    // once it simulated that step
+    let mut predicted_at = BTreeMap::new();
+    let mut compared = 0;
+    for _ in 0..120 {
+        let ticks = harness.ticks();
+        harness.step(&[holding(&[PlayerCommand::MoveRight])]);
+        // Steps are only numbered like the server's from the first snapshot on
+        if harness.client(0).snapshots() > 0 {
+            let step = harness.client(0).game_state().physics_step;
+            predicted_at.insert(step, harness.predicted_player(0).unwrap().pos);
+        }
+
+        let server_step = harness.server().game_state().physics_step;
+        if harness.ticks() > ticks
+            && let Some(server_player) = harness.server_player(0)
+            && let Some(predicted) = predicted_at.get(&server_step)
+        {
+            let offset = *predicted - server_player.pos;
+            assert!(
+                offset.x.abs() < 1.0 && offset.y.abs() < 1.0,
+                "Predicted {:?} for step {} but the server has {:?}",
+                predicted,
+                server_step,
+                server_player.pos
+            );
+            compared += 1;
+        }
+    }
+    assert!(
+        compared >= 3,
+        "Only {} steps compared while running",
+        compared
+    );
+    let moved_from = harness.server_player(0).unwrap().pos.x;
+    let gap = distance(
+        harness.server_player(0).unwrap(),
+        harness.predicted_player(0).unwrap(),
+    );
+    assert!(gap > 10.0, "Prediction should run ahead of the server");
+
+    harness.step_ticks(3, &[]);
+
+    let server_player = harness.server_player(0).unwrap();
+    let predicted = harness.predicted_player(0).unwrap();
+    assert!(server_player.pos.x > moved_from, "Player kept running");
+    assert!(
+        distance(server_player, predicted) < 1.0,
+        "Predicted {:?} but the server has {:?}",
+        predicted.pos,
+        server_player.pos
+    );
+    assert!(
+        harness.transport().dropped() > 0,
+        "Some packets should be lost"
+    );
+}
+
+#[test]
+fn test_runs_are_reproducible() {
+    let conditions = NetConditions {
+        rtt: Duration::from_millis(80),
+        jitter: Duration::from_millis(30),
+        loss: 0.1,
+    };
+    let run = || {
+        let mut harness = run_right_then_stop(conditions, 42, 90);
+        harness.step_ticks(2, &[holding(&[PlayerCommand::Jump])]);
+        let server_player = harness.server_player(0).unwrap().clone();
+        let predicted = harness.predicted_player(0).unwrap().clone();
+        (
+            (server_player.pos.x, server_player.pos.y),
+            (predicted.pos.x, predicted.pos.y),
+            harness.transport().dropped(),
+            harness.now_micros(),
+        )
+    };
+
+    assert_eq!(run(), run());
+}
+
+#[test]
+fn test_no_desync_without_loss() {
+    let mut harness = run_right_then_stop(NetConditions::default(), 1, 120);
+    harness.step_ticks(3, &[]);
+
+    let desync = &harness.client(0).predictor.desync;
+    assert!(desync.checks() > 0, "Snapshots should have been checked");
+    assert_eq!(desync.mismatches(), 0);
+}
+
+#[test]
+fn test_platform_clock_runs_ahead_of_the_server() {
+    let conditions = NetConditions {
+        rtt: Duration::from_millis(150),
+        jitter: Duration::ZERO,
+        loss: 0.05,
+    };
+    let mut harness = run_right_then_stop(conditions, 1, 30);
+    // Without a replay nothing advances the clock past the snapshot but the session itself
+    harness.client_mut(0).predictor.active_reconciliation = false;
+    let mut previous = harness.client(0).game_state().physics_step;
+    for _ in 0..90 {
+        harness.step(&[holding(&[PlayerCommand::MoveRight])]);
+        let predicted = harness.client(0).game_state().physics_step;
+        assert!(predicted >= previous, "Platforms went back in time");
+        previous = predicted;
+    }
+
+    assert!(previous > harness.server().game_state().physics_step);
+}
+
+#[test]
+fn test_client_joins_during_race_countdown() {
+    let mut harness = Harness::new(
+        SCENE_NAME,
+        Box::new(Race::new()),
+        NetConditions::default(),
+        1,
+    );
+    let right = holding(&[PlayerCommand::MoveRight]);
+    harness.add_client();
+    harness.step_ticks(2, std::slice::from_ref(&right));
+    harness.add_client();
+    harness.step_ticks(2, &[right.clone(), right]);
+
+    let late = harness
+        .server_player(1)
+        .expect("Late client should have joined");
+    assert_eq!(
+        late.pos.x,
+        harness.server().game_state().spawn_point.x,
+        "Nobody moves during the countdown"
+    );
+    assert!(harness.client(1).snapshots() > 0);
+    assert_eq!(harness.client(1).client_player_id(), late.id);
+}
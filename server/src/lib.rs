@@ -0,0 +1,949 @@
+//! The authoritative game without sockets or threads: `receive` the packets waiting on a
+//! `Transport` and `tick_and_send` on a timer. The UDP binary and the in-process test
+//! harness both drive it.
+
+use flatbuffers::FlatBufferBuilder;
+use shared::net_stats::NetStats;
+use shared::state::{
+    CommandContent, Encoding, GameMode, GameState, InterestConfig, InterestManager, NameTracker,
+    PlayerInput, PlayerStateCommand, PriorityConfig, SendContext, SnapshotPrioritizer,
+    StateHistory, Touch, TouchTarget,
+};
+use shared::transport::{Clock, Transport};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::io;
+use std::net::SocketAddr;
+use std::time::{Duration, Instant};
+
+pub const TICK_DURATION: Duration = Duration::from_millis(333);
+const MAX_REWIND: Duration = Duration::from_millis(1000);
+/// How far behind the newest snapshot a client draws the other players: it interpolates
+/// between the last two, a tick apart
+const INTERPOLATION_DELAY: Duration = TICK_DURATION;
+const COMPACT_SNAPSHOTS: bool = true;
+/// A client that sent nothing for this long has left. Idle clients still send a keepalive
+/// about once a second.
+pub const CLIENT_TIMEOUT: Duration = Duration::from_secs(5);
+/// Snapshot send times kept to time round trips, acks of older ticks aren't measured
+const SNAPSHOT_TIMES_KEPT: usize = 64;
+
+/// Game state, rules and per-client bookkeeping of one server. All times are unix
+/// timestamps in microseconds passed in by the caller, so a test can run it on a virtual
+/// clock.
+pub struct GameServer {
+    game_state: GameState,
+    mode: Box<dyn GameMode>,
+    history: StateHistory,
+    interest: InterestManager,
+    prioritizer: SnapshotPrioritizer,
+    names: NameTracker,
+    ip_to_player_id: HashMap<SocketAddr, u32>,
+    /// Ids aren't reused, a new client must not inherit what others knew of a departed one
+    next_player_id: u32,
+    /// Per player, when its last packet arrived
+    last_heard: HashMap<u32, u64>,
+    /// Per client, the tick of the first snapshot that carried the physics params. Every
+    /// snapshot carries them until the client acks one of these ticks.
+    physics_params_sent: HashMap<u32, u32>,
+    /// Clients that acked a snapshot with the physics params
+    physics_params_acked: HashSet<u32>,
+    /// Commands received since the last tick
+    commands: Vec<CommandContent>,
+    /// Per player, when each command from the newest acked one on arrived
+    received_commands: HashMap<u32, BTreeMap<u32, u64>>,
+    /// When the snapshots of each recent tick went out
+    snapshot_times: BTreeMap<u32, u64>,
+    /// Per client, the newest snapshot tick it acked
+    acked_ticks: HashMap<u32, u32>,
+    /// Per client, the smoothed time from sending a snapshot to the first command acking it
+    rtt_micros: HashMap<u32, u64>,
+    net_stats: NetStats,
+    tick: u32,
+}
+
+impl GameServer {
+    pub fn new(scene_name: &str, mode: Box<dyn GameMode>) -> Self {
+        GameServer {
+            game_state: GameState::new(scene_name),
+            mode,
+            history: StateHistory::new(MAX_REWIND),
+            interest: InterestManager::new(InterestConfig::default()),
+            prioritizer: SnapshotPrioritizer::new(PriorityConfig::default()),
+            names: NameTracker::new(),
+            ip_to_player_id: HashMap::new(),
+            next_player_id: 1,
+            last_heard: HashMap::new(),
+            physics_params_sent: HashMap::new(),
+            physics_params_acked: HashSet::new(),
+            commands: Vec::new(),
+            received_commands: HashMap::new(),
+            snapshot_times: BTreeMap::new(),
+            acked_ticks: HashMap::new(),
+            rtt_micros: HashMap::new(),
+            net_stats: NetStats::new(),
+            tick: 0,
+        }
+    }
+
+    pub fn game_state(&self) -> &GameState {
+        &self.game_state
+    }
+
+    pub fn mode(&self) -> &dyn GameMode {
+        self.mode.as_ref()
+    }
+
+    /// Player id of the client at `addr`, once it sent something
+    pub fn player_id(&self, addr: &SocketAddr) -> Option<u32> {
+        self.ip_to_player_id.get(addr).copied()
+    }
+
+    pub fn net_stats_mut(&mut self) -> &mut NetStats {
+        &mut self.net_stats
+    }
+
+    /// Handle every packet waiting on `transport` and return how many there were
+    pub fn receive(&mut self, transport: &dyn Transport, now_micros: u64) -> io::Result<usize> {
+        let mut buf = [0u8; 2048];
+        let mut received = 0;
+        while let Some((amt, src_addr)) = transport.recv(&mut buf)? {
+            self.net_stats.record_received(amt, Instant::now());
+            self.handle_packet(&buf[..amt], src_addr, now_micros);
+            received += 1;
+        }
+        Ok(received)
+    }
+
+    /// `tick`, then send every client its snapshot over `transport`. The snapshots are
+    /// timestamped by `clock` after the simulation, so the hold time they report covers it.
+    pub fn tick_and_send(&mut self, transport: &dyn Transport, dt_micros: u64, clock: &dyn Clock) {
+        self.simulate_tick(dt_micros, clock.now_micros());
+        for (ip, bytes) in self.snapshots(clock.now_micros()) {
+            match transport.send(&bytes, ip) {
+                Ok(amt) => self.net_stats.record_sent(amt, Instant::now()),
+                Err(e) => eprintln!("Failed to send data to client: {}", e),
+            }
+        }
+    }
+
+    /// Queue the command in `packet` for the next tick, `now_micros` being when it arrived
+    pub fn handle_packet(&mut self, packet: &[u8], src_addr: SocketAddr, now_micros: u64) {
+        let player_state_command = PlayerStateCommand::deserialize(packet);
+        let player_id = self.get_or_add_player_id(&src_addr);
+        self.last_heard.insert(player_id, now_micros);
+        self.net_stats
+            .record_sequence(player_id, player_state_command.sequence);
+        if let Some(acked_tick) = player_state_command.acked_tick {
+            self.record_round_trip(player_id, acked_tick, now_micros);
+            self.interest.acknowledge(player_id, acked_tick);
+            if self
+                .physics_params_sent
+                .get(&player_id)
+                .is_some_and(|first_tick| acked_tick >= *first_tick)
+            {
+                self.physics_params_acked.insert(player_id);
+            }
+        }
+
+        self.received_commands
+            .entry(player_id)
+            .or_default()
+            .insert(player_state_command.sequence, now_micros);
+
+        let client_delay_micros = player_state_command.client_timestamp_micros.max(now_micros)
+            - player_state_command.client_timestamp_micros;
+        // Empty commands matter too, they release the buttons the player held
+        self.commands.push(CommandContent {
+            player_id,
+            player_state_command,
+            client_delay_micros,
+        });
+    }
+
+    /// Time the round trip to `player_id` when its first command acking `acked_tick` arrived
+    fn record_round_trip(&mut self, player_id: u32, acked_tick: u32, now_micros: u64) {
+        if self
+            .acked_ticks
+            .get(&player_id)
+            .is_some_and(|newest| acked_tick <= *newest)
+        {
+            return;
+        }
+        self.acked_ticks.insert(player_id, acked_tick);
+        let Some(sent_at) = self.snapshot_times.get(&acked_tick) else {
+            return;
+        };
+
+        let sample = now_micros.saturating_sub(*sent_at);
+        self.net_stats.record_rtt(Duration::from_micros(sample));
+        self.rtt_micros
+            .entry(player_id)
+            .and_modify(|rtt| *rtt = (*rtt * 7 + sample) / 8)
+            .or_insert(sample);
+    }
+
+    /// Per commanding player, the server time of the world it drew when it sent its newest
+    /// command this tick: half a round trip before the command arrived it acted on a
+    /// snapshot that had taken another half to reach it
+    fn seen_at(&self, commands: &[CommandContent], now_micros: u64) -> BTreeMap<u32, u64> {
+        let mut seen_at = BTreeMap::new();
+        for command in commands {
+            let player_id = command.player_id;
+            let arrived_at = self
+                .received_commands
+                .get(&player_id)
+                .and_then(|received| received.get(&command.player_state_command.sequence))
+                .copied()
+                .unwrap_or(now_micros);
+            let rtt_micros = self.rtt_micros.get(&player_id).copied().unwrap_or(0);
+            let seen = seen_at.entry(player_id).or_insert(0);
+            *seen = (*seen).max(arrived_at.saturating_sub(rtt_micros));
+        }
+        seen_at
+    }
+
+    fn get_or_add_player_id(&mut self, client_addr: &SocketAddr) -> u32 {
+        if let Some(&id) = self.ip_to_player_id.get(client_addr) {
+            // Found player ID
+            return id;
+        }
+
+        // Else add the player
+        let new_player_id = self.next_player_id;
+        self.next_player_id += 1;
+
+        self.ip_to_player_id.insert(*client_addr, new_player_id);
+        new_player_id
+    }
+
+    /// Remove the clients that went quiet for `CLIENT_TIMEOUT`, along with their player
+    fn drop_silent_clients(&mut self, now_micros: u64) {
+        let timeout_micros = CLIENT_TIMEOUT.as_micros() as u64;
+        let mut silent: Vec<(SocketAddr, u32)> = self
+            .ip_to_player_id
+            .iter()
+            .filter(|(_, id)| {
+                let last_heard = self.last_heard.get(id).copied().unwrap_or(now_micros);
+                now_micros.saturating_sub(last_heard) >= timeout_micros
+            })
+            .map(|(&ip, &id)| (ip, id))
+            .collect();
+        silent.sort_by_key(|(_, id)| *id);
+
+        for (ip, player_id) in silent {
+            self.ip_to_player_id.remove(&ip);
+            self.last_heard.remove(&player_id);
+            self.physics_params_sent.remove(&player_id);
+            self.physics_params_acked.remove(&player_id);
+            self.received_commands.remove(&player_id);
+            self.acked_ticks.remove(&player_id);
+            self.rtt_micros.remove(&player_id);
+            self.game_state.applied_commands.remove(&player_id);
+            self.net_stats.forget_peer(player_id);
+            self.interest.remove_client(player_id);
+            self.prioritizer.remove_client(player_id);
+            self.names.remove_client(player_id);
+            if self.game_state.players.remove(&player_id).is_some() {
+                self.mode.on_leave(&mut self.game_state, player_id);
+            }
+        }
+    }
+
+    /// Simulate the `dt_micros` that passed since the last tick with the commands received
+    /// meanwhile, and return the snapshot for every client with its address
+    pub fn tick(&mut self, dt_micros: u64, now_micros: u64) -> Vec<(SocketAddr, Vec<u8>)> {
+        self.simulate_tick(dt_micros, now_micros);
+        self.snapshots(now_micros)
+    }
+
+    fn simulate_tick(&mut self, dt_micros: u64, now_micros: u64) {
+        self.drop_silent_clients(now_micros);
+        let commands = std::mem::take(&mut self.commands);
+        let seen_at = self.seen_at(&commands, now_micros);
+        self.game_state.tick = self.tick;
+        simulate(
+            &mut self.game_state,
+            self.mode.as_mut(),
+            &mut self.history,
+            &commands,
+            &seen_at,
+            dt_micros,
+            now_micros,
+        );
+    }
+
+    /// Snapshot the simulated tick for every client, acking the newest command of each that
+    /// the simulation applied
+    fn snapshots(&mut self, server_timestamp: u64) -> Vec<(SocketAddr, Vec<u8>)> {
+        let snapshots = self.broadcast_state(server_timestamp);
+        self.snapshot_times.insert(self.tick, server_timestamp);
+        while self.snapshot_times.len() > SNAPSHOT_TIMES_KEPT {
+            self.snapshot_times.pop_first();
+        }
+        self.tick += 1;
+        snapshots
+    }
+
+    fn broadcast_state(&mut self, server_timestamp: u64) -> Vec<(SocketAddr, Vec<u8>)> {
+        let mut clients: Vec<(SocketAddr, u32)> = self
+            .ip_to_player_id
+            .iter()
+            .map(|(&ip, &id)| (ip, id))
+            .collect();
+        // Same order every tick, so a run on a virtual network is reproducible
+        clients.sort_by_key(|(_, id)| *id);
+
+        let mut snapshots = Vec::with_capacity(clients.len());
+        for (ip, player_id) in clients {
+            // No snapshot until the client's first command was due and created its player
+            if !self.game_state.players.contains_key(&player_id) {
+                continue;
+            }
+            let (sequence, received_at) = self.ack(player_id).unwrap_or((0, server_timestamp));
+            let send_physics_params = self.should_send_physics_params(player_id);
+            let game_state = &self.game_state;
+            let update = self.interest.update(game_state, player_id, self.tick);
+            self.prioritizer.forget(player_id, &update.removed);
+            self.names.forget(player_id, &update.removed);
+            let ranked = self.prioritizer.rank(game_state, player_id, &update.send);
+
+            let mut named_player_ids = ranked.clone();
+            named_player_ids.push(player_id);
+            let named_player_ids = self.names.names_to_send(player_id, &named_player_ids);
+            let encoding = if COMPACT_SNAPSHOTS {
+                Encoding::Compact {
+                    named_player_ids: &named_player_ids,
+                }
+            } else {
+                Encoding::Full
+            };
+
+            let context = SendContext {
+                client_player_id: player_id,
+                sequence,
+                ack_hold_micros: server_timestamp.saturating_sub(received_at),
+                server_timestamp,
+                encoding,
+                send_physics_params,
+            };
+
+            let mut builder = FlatBufferBuilder::with_capacity(2048);
+            let sent = game_state.serialize_within_budget(
+                &mut builder,
+                &context,
+                &ranked,
+                &update.removed,
+                self.prioritizer.byte_budget(),
+            );
+            self.prioritizer
+                .mark_sent(game_state, player_id, &ranked[..sent]);
+            let mut sent_player_ids = ranked[..sent].to_vec();
+            sent_player_ids.push(player_id);
+            self.names.mark_sent(player_id, &sent_player_ids);
+
+            snapshots.push((ip, builder.finished_data().to_vec()));
+        }
+        snapshots
+    }
+
+    /// The newest command of `player_id` the simulation applied and when it arrived. Older
+    /// ones are acked along with it, newer ones still waiting to be due are left for a later
+    /// snapshot. Acked again until a newer one is applied, the client replays on top of it.
+    fn ack(&mut self, player_id: u32) -> Option<(u32, u64)> {
+        let sequence = self.game_state.applied_commands.get(&player_id)?.sequence;
+        let received = self.received_commands.get_mut(&player_id)?;
+        received.retain(|received_sequence, _| *received_sequence >= sequence);
+        let received_at = *received.get(&sequence)?;
+        Some((sequence, received_at))
+    }
+
+    /// Whether the next snapshot to `player_id` should carry the physics params
+    fn should_send_physics_params(&mut self, player_id: u32) -> bool {
+        self.physics_params_sent
+            .entry(player_id)
+            .or_insert(self.tick);
+        !self.physics_params_acked.contains(&player_id)
+    }
+}
+
+/// Run the physics for one tick with the game mode's hooks around it, and record the result
+/// in `history` as `game_state.tick`. `seen_at` is the server time of the world each
+/// commanding player was looking at.
+fn simulate(
+    game_state: &mut GameState,
+    mode: &mut dyn GameMode,
+    history: &mut StateHistory,
+    commands: &[CommandContent],
+    seen_at: &BTreeMap<u32, u64>,
+    dt_micros: u64,
+    now_micros: u64,
+) {
+    // Commands the mode refuses still create their player and get acked, just without input
+    let commands: Vec<CommandContent> = commands
+        .iter()
+        .cloned()
+        .map(|mut command| {
+            if !mode.accepts_input(command.player_id) {
+                command.player_state_command.commands.clear();
+                command.player_state_command.move_axis = 0;
+            }
+            command
+        })
+        .collect();
+    // Held input would otherwise keep moving players the mode no longer listens to
+    for player in game_state.players.values_mut() {
+        if !mode.accepts_input(player.id) {
+            player.input = PlayerInput::default();
+            player.pending_press = PlayerInput::default();
+        }
+    }
+    let players_before: BTreeSet<u32> = game_state.players.keys().copied().collect();
+
+    game_state.mutate_at(&commands, dt_micros, None, now_micros);
+    history.record(game_state.tick, now_micros, game_state);
+    let seen = seen_contacts(
+        history,
+        game_state.tick,
+        game_state.physics_step,
+        seen_at,
+        &game_state.touches,
+    );
+    game_state.touches.extend(seen);
+
+    let players_after: BTreeSet<u32> = game_state.players.keys().copied().collect();
+    for player_id in players_after.difference(&players_before) {
+        mode.on_join(game_state, *player_id);
+    }
+    for player_id in players_before.difference(&players_after) {
+        mode.on_leave(game_state, *player_id);
+    }
+    for touch in std::mem::take(&mut game_state.touches) {
+        mode.on_trigger(game_state, touch);
+    }
+    mode.on_tick(game_state, dt_micros as f32 / 1_000_000.0);
+}
+
+/// Players that each commanding player overlapped on its own screen, where the others
+/// were drawn the interpolation delay before `seen_at`. Lets a contact the client saw count
+/// even when the players never overlapped on the server. Pairs the physics already
+/// `reported` this tick aren't touched twice.
+fn seen_contacts(
+    history: &StateHistory,
+    tick: u32,
+    step: u32,
+    seen_at: &BTreeMap<u32, u64>,
+    reported: &[Touch],
+) -> Vec<Touch> {
+    let Some(frame_micros) = history.frame(tick).map(|frame| frame.timestamp_micros) else {
+        return Vec::new();
+    };
+    let touching = |touches: &[Touch], a: u32, b: u32| {
+        touches.iter().any(|touch| {
+            (touch.player_id == a && touch.target == TouchTarget::Player(b))
+                || (touch.player_id == b && touch.target == TouchTarget::Player(a))
+        })
+    };
+
+    let mut touches = Vec::new();
+    for (&player_id, &seen_micros) in seen_at {
+        let seen_delay =
+            INTERPOLATION_DELAY + Duration::from_micros(frame_micros.saturating_sub(seen_micros));
+        let others = history
+            .query_as_seen_by(tick, player_id, seen_delay, |seen| {
+                seen.players
+                    .get(&player_id)
+                    .map_or_else(Vec::new, |viewer| {
+                        seen.players_overlapping(&viewer.rect(), player_id)
+                    })
+            })
+            .unwrap_or_default();
+        for other_id in others {
+            if touching(reported, player_id, other_id) || touching(&touches, player_id, other_id) {
+                continue;
+            }
+            touches.push(Touch {
+                player_id,
+                step,
+                target: TouchTarget::Player(other_id),
+            });
+        }
+    }
+    touches
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use shared::state::ClassicMode;
+    use shared::transport::{LoopbackNetwork, SystemClock};
+    use std::sync::{Arc, Mutex};
+    use std::time::{SystemTime, UNIX_EPOCH};
+
+    const SCENE_NAME: &str = "scene_3";
+
+    fn now_micros() -> u64 {
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_micros() as u64
+    }
+
+    #[test]
+    fn test_player_id_assignment() {
+        let mut server = GameServer::new(SCENE_NAME, Box::new(ClassicMode));
+        let addr1 = "127.0.0.1:8001".parse().unwrap();
+        let addr2 = "127.0.0.1:8002".parse().unwrap();
+
+        let id1 = server.get_or_add_player_id(&addr1);
+        let id2 = server.get_or_add_player_id(&addr2);
+        let id1_again = server.get_or_add_player_id(&addr1);
+
+        assert_eq!(id1, 1); // First player gets ID 1
+        assert_eq!(id2, 2); // Second player gets ID 2
+        assert_eq!(id1_again, id1); // Same player gets same ID
+    }
+
+    /// Records which hooks were called
+    #[derive(Default)]
+    struct RecordingMode {
+        calls: Vec<String>,
+    }
+
+    impl GameMode for RecordingMode {
+        fn name(&self) -> &'static str {
+            "recording"
+        }
+
+        fn accepts_input(&self, player_id: u32) -> bool {
+            player_id != 2
+        }
+
+        fn on_join(&mut self, _state: &mut GameState, player_id: u32) {
+            self.calls.push(format!("join {}", player_id));
+        }
+
+        fn on_trigger(&mut self, _state: &mut GameState, touch: shared::state::Touch) {
+            self.calls
+                .push(format!("touch {} {:?}", touch.player_id, touch.target));
+        }
+
+        fn on_tick(&mut self, _state: &mut GameState, _dt: f32) {
+            self.calls.push("tick".to_string());
+        }
+    }
+
+    fn command_from(player_id: u32) -> CommandContent {
+        CommandContent {
+            player_id,
+            player_state_command: PlayerStateCommand {
+                sequence: 1,
+                dt_micros: 16000,
+                commands: vec![shared::generated::PlayerCommand::MoveRight],
+                client_timestamp_micros: 0,
+                move_axis: 0,
+                acked_tick: None,
+            },
+            client_delay_micros: 0,
+        }
+    }
+
+    #[test]
+    fn test_tick_calls_game_mode_hooks() {
+        let mut game_state = GameState::new(SCENE_NAME);
+        let mut mode = RecordingMode::default();
+
+        let mut history = StateHistory::new(MAX_REWIND);
+
+        // Player 2's input is refused, it joins but stands still. Commands wait for the step
+        // they are due in.
+        let now = now_micros();
+        simulate(
+            &mut game_state,
+            &mut mode,
+            &mut history,
+            &[command_from(1), command_from(2)],
+            &BTreeMap::from([(1, now), (2, now)]),
+            16000,
+            now,
+        );
+        // Both spawn in the same spot
+        assert_eq!(
+            mode.calls,
+            vec!["join 1", "join 2", "touch 1 Player(2)", "tick"]
+        );
+        assert_eq!(game_state.players[&1].input.axis, 1.0);
+        assert_eq!(game_state.players[&2].input, PlayerInput::default());
+
+        let win_point = game_state.win_point.clone();
+        let player = game_state.players.get_mut(&1).unwrap();
+        player.pos = shared::state::Vec2::new(win_point.x, win_point.y);
+        mode.calls.clear();
+        simulate(
+            &mut game_state,
+            &mut mode,
+            &mut history,
+            &[],
+            &BTreeMap::new(),
+            16000,
+            now_micros(),
+        );
+
+        assert_eq!(mode.calls, vec!["touch 1 WinPoint", "tick"]);
+        assert!(
+            game_state.touches.is_empty(),
+            "Touches are handed over once"
+        );
+    }
+
+    #[test]
+    fn test_contacts_are_judged_as_the_client_saw_them() {
+        let mut game_state = GameState::new(SCENE_NAME);
+        let mut history = StateHistory::new(MAX_REWIND);
+        let at = |x| shared::state::SpawnPoint { x, y: 100.0 };
+        game_state
+            .players
+            .insert(1, shared::state::PlayerState::new(1, &at(100.0)));
+        game_state
+            .players
+            .insert(2, shared::state::PlayerState::new(2, &at(100.0)));
+        history.record(0, 0, &game_state);
+        // Player 2 ran off during the tick, but player 1 still sees it where it was
+        game_state.players.get_mut(&2).unwrap().pos.x = 300.0;
+        history.record(1, TICK_DURATION.as_micros() as u64, &game_state);
+
+        // Both commands arrived with the tick, on a link without delay
+        let now = TICK_DURATION.as_micros() as u64;
+        let seen_at = BTreeMap::from([(1, now), (2, now)]);
+        let touches = seen_contacts(&history, 1, 7, &seen_at, &[]);
+
+        assert_eq!(
+            touches,
+            vec![Touch {
+                player_id: 1,
+                step: 7,
+                target: TouchTarget::Player(2),
+            }]
+        );
+
+        // The physics already reported the pair
+        let reported = Touch {
+            player_id: 2,
+            step: 6,
+            target: TouchTarget::Player(1),
+        };
+        assert!(seen_contacts(&history, 1, 7, &seen_at, &[reported]).is_empty());
+    }
+
+    #[test]
+    fn test_seen_contacts_rewind_by_the_round_trip() {
+        let mut game_state = GameState::new(SCENE_NAME);
+        let mut history = StateHistory::new(MAX_REWIND);
+        let at = |x| shared::state::SpawnPoint { x, y: 100.0 };
+        game_state
+            .players
+            .insert(1, shared::state::PlayerState::new(1, &at(100.0)));
+        game_state
+            .players
+            .insert(2, shared::state::PlayerState::new(2, &at(300.0)));
+        let tick_micros = TICK_DURATION.as_micros() as u64;
+        history.record(0, 0, &game_state);
+        history.record(1, tick_micros, &game_state);
+        // Player 2 only stood next to player 1 a while ago
+        game_state.players.get_mut(&2).unwrap().pos.x = 100.0;
+        history.record(2, 2 * tick_micros, &game_state);
+        game_state.players.get_mut(&2).unwrap().pos.x = 300.0;
+        history.record(3, 3 * tick_micros, &game_state);
+        history.record(4, 4 * tick_micros, &game_state);
+
+        // Without delay, player 1 draws tick 3 and misses it
+        let now = 4 * tick_micros;
+        let seen_at = BTreeMap::from([(1, now)]);
+        assert!(seen_contacts(&history, 4, 0, &seen_at, &[]).is_empty());
+
+        // A tick of round trip puts player 2 back next to it
+        let seen_at = BTreeMap::from([(1, now - tick_micros)]);
+        assert_eq!(seen_contacts(&history, 4, 0, &seen_at, &[]).len(), 1);
+    }
+
+    #[test]
+    fn test_round_trip_is_timed_from_acks() {
+        let mut server = GameServer::new(SCENE_NAME, Box::new(ClassicMode));
+        let addr: SocketAddr = "127.0.0.1:8001".parse().unwrap();
+        let start = now_micros();
+        send_empty_command(&mut server, addr, 1, start);
+        server.tick(16667, start + 16667);
+
+        // The client's clock is far off, only the ack counts
+        let mut builder = FlatBufferBuilder::new();
+        let command = PlayerStateCommand {
+            sequence: 2,
+            dt_micros: 16667,
+            commands: vec![],
+            client_timestamp_micros: start - 5_000_000,
+            move_axis: 0,
+            acked_tick: Some(0),
+        };
+        let offset = command.serialize(&mut builder);
+        builder.finish(offset, None);
+        server.handle_packet(builder.finished_data(), addr, start + 96_667);
+        assert_eq!(server.rtt_micros[&1], 80_000);
+
+        // Acked again until the next snapshot arrives, that's no new sample
+        server.handle_packet(builder.finished_data(), addr, start + 200_000);
+        assert_eq!(server.rtt_micros[&1], 80_000);
+
+        let seen_at = server.seen_at(&server.commands, start + 200_000);
+        assert_eq!(seen_at[&1], start + 200_000 - 80_000);
+    }
+
+    #[test]
+    fn test_handle_packet() {
+        use shared::generated;
+
+        let mut server = GameServer::new(SCENE_NAME, Box::new(ClassicMode));
+        let client_addr = "127.0.0.1:8001".parse().unwrap();
+
+        // Create a test packet
+        let mut builder = FlatBufferBuilder::new();
+        let commands = vec![generated::PlayerCommand::MoveRight];
+        let commands_vec = builder.create_vector(&commands);
+        let player_commands = generated::PlayerCommands::create(
+            &mut builder,
+            &generated::PlayerCommandsArgs {
+                sequence: 1,
+                dt_micro: 16667, // ~60fps
+                commands: Some(commands_vec),
+                client_timestamp_micro: now_micros(),
+                move_axis: 0,
+                ..Default::default()
+            },
+        );
+        builder.finish(player_commands, None);
+        let packet = builder.finished_data();
+
+        // Handle the packet
+        server.handle_packet(packet, client_addr, now_micros());
+
+        // Check if command was queued
+        assert_eq!(server.commands.len(), 1);
+        let mutate_command = &server.commands[0];
+
+        let player_id = mutate_command.player_id;
+        let received_commands = &mutate_command.player_state_command;
+
+        assert_eq!(player_id, 1); // First player gets ID 1
+        assert_eq!(received_commands.sequence, 1);
+        assert_eq!(received_commands.commands.len(), 1);
+        assert_eq!(
+            received_commands.commands[0],
+            generated::PlayerCommand::MoveRight
+        );
+    }
+
+    #[test]
+    fn test_receive_and_send_over_transport() {
+        let network = LoopbackNetwork::new();
+        let server_transport = network.bind("10.0.0.1:9000".parse().unwrap()).unwrap();
+        let client = network.bind("10.0.1.1:7000".parse().unwrap()).unwrap();
+        let mut server = GameServer::new(SCENE_NAME, Box::new(ClassicMode));
+        // Sent and received a frame ago, so it is due within the tick
+        let sent_at = now_micros() - 16667;
+
+        let mut builder = FlatBufferBuilder::new();
+        let command = PlayerStateCommand {
+            sequence: 2,
+            dt_micros: 16667,
+            commands: vec![shared::generated::PlayerCommand::MoveRight],
+            client_timestamp_micros: sent_at,
+            move_axis: 0,
+            acked_tick: None,
+        };
+        let offset = command.serialize(&mut builder);
+        builder.finish(offset, None);
+        client
+            .send(
+                builder.finished_data(),
+                server_transport.local_addr().unwrap(),
+            )
+            .unwrap();
+
+        let received = server.receive(&server_transport, sent_at).unwrap();
+        server.tick_and_send(&server_transport, 16667, &SystemClock);
+
+        assert_eq!(received, 1);
+        let mut buf = [0u8; 2048];
+        let (amt, from) = client
+            .recv(&mut buf)
+            .unwrap()
+            .expect("Should get a snapshot");
+        assert_eq!(from, server_transport.local_addr().unwrap());
+        let snapshot = server.game_state().deserialize(&buf[..amt]);
+        assert_eq!(snapshot.client_player.id, 1);
+        assert_eq!(snapshot.sequence, 2);
+    }
+
+    #[test]
+    fn test_tick_snapshots_every_client() {
+        let mut server = GameServer::new(SCENE_NAME, Box::new(ClassicMode));
+        let addr1: SocketAddr = "127.0.0.1:8001".parse().unwrap();
+        let addr2: SocketAddr = "127.0.0.1:8002".parse().unwrap();
+        let mut builder = FlatBufferBuilder::new();
+        let command = PlayerStateCommand {
+            sequence: 4,
+            dt_micros: 16667,
+            commands: vec![],
+            client_timestamp_micros: now_micros(),
+            move_axis: 0,
+            acked_tick: None,
+        };
+        let offset = command.serialize(&mut builder);
+        builder.finish(offset, None);
+        let received_at = now_micros();
+        server.handle_packet(builder.finished_data(), addr1, received_at);
+        server.handle_packet(builder.finished_data(), addr2, received_at);
+
+        let snapshots = server.tick(16667, received_at + 16667);
+
+        assert_eq!(snapshots.len(), 2);
+        for (addr, bytes) in snapshots {
+            let snapshot = server.game_state().deserialize(&bytes);
+            assert_eq!(Some(snapshot.client_player.id), server.player_id(&addr));
+            assert_eq!(snapshot.sequence, 4);
+            assert!(
+                snapshot.physics_params.is_some(),
+                "Joining clients get the physics"
+            );
+        }
+        assert_eq!(server.game_state().players.len(), 2);
+    }
+
+    /// Hand `server` an empty command from `addr` that arrived at `received_at`
+    fn send_empty_command(
+        server: &mut GameServer,
+        addr: SocketAddr,
+        sequence: u32,
+        received_at: u64,
+    ) {
+        let mut builder = FlatBufferBuilder::new();
+        let command = PlayerStateCommand {
+            sequence,
+            dt_micros: 16667,
+            commands: vec![],
+            client_timestamp_micros: received_at,
+            move_axis: 0,
+            acked_tick: None,
+        };
+        let offset = command.serialize(&mut builder);
+        builder.finish(offset, None);
+        server.handle_packet(builder.finished_data(), addr, received_at);
+    }
+
+    #[test]
+    fn test_snapshot_reports_how_long_the_acked_command_waited() {
+        let mut server = GameServer::new(SCENE_NAME, Box::new(ClassicMode));
+        let addr: SocketAddr = "127.0.0.1:8001".parse().unwrap();
+        let start = now_micros();
+        send_empty_command(&mut server, addr, 1, start);
+        send_empty_command(&mut server, addr, 2, start + 100_000);
+
+        let snapshots = server.tick(16667, start + 300_000);
+
+        let snapshot = server.game_state().deserialize(&snapshots[0].1);
+        assert_eq!(snapshot.sequence, 2);
+        assert_eq!(snapshot.ack_hold_micros, 200_000);
+    }
+
+    #[test]
+    fn test_command_is_acked_once_it_was_applied() {
+        let mut server = GameServer::new(SCENE_NAME, Box::new(ClassicMode));
+        let addr: SocketAddr = "127.0.0.1:8001".parse().unwrap();
+        let start = now_micros();
+        send_empty_command(&mut server, addr, 1, start);
+        // Arrives after the tick's only physics step started
+        send_empty_command(&mut server, addr, 2, start + 299_000);
+
+        let snapshots = server.tick(16667, start + 300_000);
+        let snapshot = server.game_state().deserialize(&snapshots[0].1);
+        assert_eq!(snapshot.sequence, 1);
+
+        let snapshots = server.tick(16667, start + 316_667);
+        let snapshot = server.game_state().deserialize(&snapshots[0].1);
+        assert_eq!(snapshot.sequence, 2);
+        assert_eq!(snapshot.ack_hold_micros, 17_667);
+    }
+
+    /// Shares the players it saw leave with the test
+    struct LeavingMode(Arc<Mutex<Vec<u32>>>);
+
+    impl GameMode for LeavingMode {
+        fn name(&self) -> &'static str {
+            "leaving"
+        }
+
+        fn on_leave(&mut self, _state: &mut GameState, player_id: u32) {
+            self.0.lock().unwrap().push(player_id);
+        }
+    }
+
+    #[test]
+    fn test_silent_client_leaves() {
+        let left = Arc::new(Mutex::new(Vec::new()));
+        let mut server = GameServer::new(SCENE_NAME, Box::new(LeavingMode(left.clone())));
+        let quiet: SocketAddr = "127.0.0.1:8001".parse().unwrap();
+        let chatty: SocketAddr = "127.0.0.1:8003".parse().unwrap();
+        let start = now_micros();
+        send_empty_command(&mut server, quiet, 1, start);
+        send_empty_command(&mut server, chatty, 1, start);
+        server.tick(16667, start + 16667);
+        assert_eq!(server.game_state().players.len(), 2);
+
+        let timeout = CLIENT_TIMEOUT.as_micros() as u64;
+        send_empty_command(&mut server, chatty, 2, start + timeout);
+        let snapshots = server.tick(16667, start + timeout);
+
+        assert_eq!(server.player_id(&quiet), None);
+        assert_eq!(*left.lock().unwrap(), vec![1]);
+        assert!(!server.game_state().players.contains_key(&1));
+        assert_eq!(snapshots.len(), 1);
+        let snapshot = server.game_state().deserialize(&snapshots[0].1);
+        assert_eq!(snapshot.removed_players, vec![1]);
+
+        // A returning client is a new player
+        send_empty_command(&mut server, quiet, 1, start + timeout);
+        assert_eq!(server.player_id(&quiet), Some(3));
+    }
+
+    #[test]
+    fn test_physics_params_are_sent_until_acked() {
+        let mut server = GameServer::new(SCENE_NAME, Box::new(ClassicMode));
+        let addr: SocketAddr = "127.0.0.1:8001".parse().unwrap();
+        let start = now_micros();
+        send_empty_command(&mut server, addr, 1, start);
+
+        // None of these arrive
+        let mut ticks = Vec::new();
+        for tick in 1..=5 {
+            let snapshots = server.tick(16667, start + tick * 16667);
+            let snapshot = server.game_state().deserialize(&snapshots[0].1);
+            assert!(snapshot.physics_params.is_some());
+            ticks.push(snapshot.tick);
+        }
+
+        let mut builder = FlatBufferBuilder::new();
+        let command = PlayerStateCommand {
+            sequence: 2,
+            dt_micros: 16667,
+            commands: vec![],
+            client_timestamp_micros: start + 6 * 16667,
+            move_axis: 0,
+            acked_tick: Some(ticks[4]),
+        };
+        let offset = command.serialize(&mut builder);
+        builder.finish(offset, None);
+        server.handle_packet(builder.finished_data(), addr, start + 6 * 16667);
+
+        let snapshots = server.tick(16667, start + 7 * 16667);
+        let snapshot = server.game_state().deserialize(&snapshots[0].1);
+        assert!(snapshot.physics_params.is_none());
+    }
+}
@@ -1,141 +1,66 @@
-use flatbuffers::FlatBufferBuilder;
-use shared::state::{CommandContent, GameState, PlayerStateCommand};
-use std::collections::HashMap;
+use server::{GameServer, TICK_DURATION};
+use shared::state::{GameMode, game_mode_by_name};
+use shared::transport::{Clock, SystemClock, Transport, UdpTransport};
 use std::io;
-use std::net::{SocketAddr, UdpSocket};
-use std::sync::mpsc::{self, Receiver, Sender};
+use std::process::ExitCode;
 use std::sync::{Arc, Mutex};
 use std::thread;
 use std::thread::sleep;
-use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use std::time::{Duration, Instant};
 
 const SCENE_NAME: &str = "scene_3";
-const TICK_DURATION: Duration = Duration::from_millis(333);
+const DEFAULT_GAME_MODE: &str = "classic";
+/// Picks the game mode when `--mode` isn't given
+const GAME_MODE_VAR: &str = "GAME_MODE";
+const NET_STATS_LOG_INTERVAL: Duration = Duration::from_secs(10);
+/// How long the receiving thread sleeps when there was nothing to receive
+const IDLE_SLEEP: Duration = Duration::from_millis(1);
 const SERVER_ADDR: &str = "127.0.0.1:9000";
 
+const USAGE: &str = "Usage: server [--mode classic|race|tag]";
+
+/// Runs a `GameServer` over a UDP transport: one thread receives packets, another ticks on a
+/// timer and sends out the snapshots
 struct Server {
-    command_sender: Sender<CommandContent>,
-    ip_to_player_id: Arc<Mutex<HashMap<SocketAddr, u32>>>,
-    socket: UdpSocket,
+    transport: Box<dyn Transport>,
+    game: Mutex<GameServer>,
 }
 
-type NewServerResult = io::Result<(Server, Receiver<CommandContent>)>;
-
 impl Server {
-    fn new() -> NewServerResult {
-        Self::with_addr(SERVER_ADDR)
+    fn new(mode: Box<dyn GameMode>) -> io::Result<Server> {
+        Self::with_addr(SERVER_ADDR, mode)
     }
 
-    fn with_addr(addr: &str) -> NewServerResult {
-        let socket = UdpSocket::bind(addr)?;
-        let (command_sender, command_receiver) = mpsc::channel();
+    fn with_addr(addr: &str, mode: Box<dyn GameMode>) -> io::Result<Server> {
+        let transport = UdpTransport::bind(addr)?;
+        println!("Game mode: {}", mode.name());
 
         println!("UDP running on {}...", addr);
-        Ok((
-            Server {
-                command_sender,
-                ip_to_player_id: Arc::new(Mutex::new(HashMap::new())),
-                socket,
-            },
-            command_receiver,
-        ))
+        Ok(Server {
+            transport: Box::new(transport),
+            game: Mutex::new(GameServer::new(SCENE_NAME, mode)),
+        })
     }
 
-    fn handle_packet(&self, packet: &[u8], src_addr: SocketAddr) {
-        let player_state_command = PlayerStateCommand::deserialize(packet);
-        let player_id = self.get_or_add_player_id(&src_addr);
-
-        let system_time_micro = SystemTime::now()
-            .duration_since(UNIX_EPOCH)
-            .unwrap_or_default()
-            .as_micros() as u64;
-
-        let client_delay_micros = player_state_command
-            .client_timestamp_micros
-            .max(system_time_micro)
-            - player_state_command.client_timestamp_micros;
-        if !player_state_command.commands.is_empty() {
-            if let Err(e) = self.command_sender.send(CommandContent {
-                player_id,
-                player_state_command,
-                client_delay_micros,
-            }) {
-                eprintln!("Failed to send command: {}", e);
-            }
-        }
-    }
-
-    fn get_or_add_player_id(&self, client_addr: &SocketAddr) -> u32 {
-        let mut ip_to_player_id_guard = self.ip_to_player_id.lock().unwrap();
-
-        if let Some(&id) = ip_to_player_id_guard.get(client_addr) {
-            // Found player ID
-            return id;
-        }
-
-        // Else add the player
-        let highest_id = ip_to_player_id_guard.values().max().copied().unwrap_or(0);
-        let new_player_id = highest_id + 1;
-
-        ip_to_player_id_guard.insert(*client_addr, new_player_id);
-        new_player_id
-    }
-
-    fn broadcast_state(&self, game_state: &GameState, sequence: HashMap<u32, u32>) {
-        let server_timestamp = SystemTime::now()
-            .duration_since(UNIX_EPOCH)
-            .unwrap_or_default()
-            .as_micros() as u64;
-        // Send data to client
-        for (ip, player_id) in self.read_ip_id() {
-            let mut builder = FlatBufferBuilder::with_capacity(2048);
-            let bytes = game_state.serialize(
-                &mut builder,
-                player_id,
-                sequence.get(&player_id).copied().unwrap_or(0),
-                server_timestamp,
-            );
-            if let Err(e) = self.socket.send_to(bytes, ip) {
-                eprintln!("Failed to send data to client: {}", e);
-            }
-        }
-    }
-
-    fn read_ip_id(&self) -> Vec<(SocketAddr, u32)> {
-        let ip_to_player_guard = self.ip_to_player_id.lock().unwrap();
-        ip_to_player_guard
-            .iter()
-            .map(|(&ip, &id)| (ip, id))
-            .collect()
-    }
-
-    fn start_tick_thread(self: Arc<Self>, command_receiver: Receiver<CommandContent>) {
+    fn start_tick_thread(self: Arc<Self>) {
         println!("Starting tick thread!");
 
-        let mut game_state = GameState::new(SCENE_NAME);
-
         thread::spawn(move || {
             let mut last_tick = Instant::now();
+            let mut last_stats_log = Instant::now();
             loop {
                 let start = Instant::now();
                 let dt_micros = start.duration_since(last_tick).as_micros() as u64;
                 last_tick = start;
 
-                let mut sequence = HashMap::new();
-                let mut commands = Vec::new();
-                while let Ok(mutate_command) = command_receiver.try_recv() {
-                    sequence.insert(
-                        mutate_command.player_id,
-                        *sequence
-                            .get(&mutate_command.player_id)
-                            .unwrap_or(&0)
-                            .max(&mutate_command.player_state_command.sequence),
-                    );
-                    commands.push(mutate_command);
-                }
+                let mut game = self.game.lock().unwrap();
+                game.tick_and_send(self.transport.as_ref(), dt_micros, &SystemClock);
 
-                self.tick(&mut game_state, &commands, dt_micros);
-                self.broadcast_state(&game_state, sequence);
+                if last_stats_log.elapsed() >= NET_STATS_LOG_INTERVAL {
+                    last_stats_log = Instant::now();
+                    log_net_stats(&mut game);
+                }
+                drop(game);
 
                 let sleep_time = TICK_DURATION.checked_sub(start.elapsed());
                 if let Some(sleep_time) = sleep_time {
@@ -145,36 +70,80 @@ impl Server {
         });
     }
 
-    pub fn run(self: Arc<Self>, command_receiver: Receiver<CommandContent>) -> io::Result<()> {
-        Arc::clone(&self).start_tick_thread(command_receiver);
+    pub fn run(self: Arc<Self>) -> io::Result<()> {
+        Arc::clone(&self).start_tick_thread();
 
         // Listen for commands
-        let socket = self.socket.try_clone()?;
         loop {
-            let mut buf = [0u8; 2048];
-            let (amt, src_addr) = socket.recv_from(&mut buf)?;
-
-            self.handle_packet(&buf[..amt], src_addr);
+            let received = self
+                .game
+                .lock()
+                .unwrap()
+                .receive(self.transport.as_ref(), SystemClock.now_micros())?;
+            if received == 0 {
+                sleep(IDLE_SLEEP);
+            }
         }
     }
+}
+
+fn log_net_stats(game: &mut GameServer) {
+    let net_stats = game.net_stats_mut();
+    net_stats.update(Instant::now());
+    let summary = net_stats.summary();
+    println!(
+        "Net: out {:.0} B/s ({:.1} pkt/s), in {:.0} B/s ({:.1} pkt/s), avg {:.0} B, loss {:.1}%",
+        summary.bytes_sent_per_sec,
+        summary.packets_sent_per_sec,
+        summary.bytes_received_per_sec,
+        summary.packets_received_per_sec,
+        summary.avg_packet_size,
+        summary.loss * 100.0
+    );
+}
 
-    fn tick(&self, game_state: &mut GameState, commands: &[CommandContent], dt_micros: u64) {
-        game_state.mutate(commands, dt_micros, None);
+/// `--mode NAME`, else `env_mode`, else classic
+fn game_mode_name(
+    mut args: impl Iterator<Item = String>,
+    env_mode: Option<String>,
+) -> Result<String, String> {
+    let mut name = env_mode.unwrap_or_else(|| DEFAULT_GAME_MODE.to_string());
+    while let Some(flag) = args.next() {
+        let value = args
+            .next()
+            .ok_or_else(|| format!("{} needs a value", flag))?;
+        match flag.as_str() {
+            "--mode" => name = value,
+            _ => return Err(format!("Unknown flag {}", flag)),
+        }
     }
+    Ok(name)
 }
 
-fn main() -> io::Result<()> {
-    let (server, command_receiver) = Server::new()?;
-    let server_arc = Arc::new(server);
-    server_arc.run(command_receiver)
+fn main() -> ExitCode {
+    let mode = game_mode_name(std::env::args().skip(1), std::env::var(GAME_MODE_VAR).ok())
+        .and_then(|name| {
+            game_mode_by_name(&name).ok_or_else(|| format!("Unknown game mode {}", name))
+        });
+    let mode = match mode {
+        Ok(mode) => mode,
+        Err(e) => {
+            eprintln!("{}\n{}", e, USAGE);
+            return ExitCode::from(2);
+        }
+    };
+
+    let result = Server::new(mode).and_then(|server| Arc::new(server).run());
+    if let Err(e) = result {
+        eprintln!("Server stopped: {}", e);
+        return ExitCode::FAILURE;
+    }
+    ExitCode::SUCCESS
 }
 
 #[cfg(test)]
 mod tests {
-    use shared::generated::{PlayerCommand, PlayerCommands, PlayerCommandsArgs};
-
     use super::*;
-    use std::time::Duration;
 
     const TEST_SERVER_PORT_START: u16 = 9100;
     static mut NEXT_TEST_PORT: u16 = TEST_SERVER_PORT_START;
@@ -191,70 +160,32 @@ mod tests {
     #[test]
     fn test_server_creation() {
         let addr = get_test_server_addr();
-        let (server, _receiver) = Server::with_addr(&addr).expect("Server should be created");
-        assert_eq!(server.socket.local_addr().unwrap().to_string(), addr);
-    }
-
-    #[test]
-    fn test_player_id_assignment() {
-        let addr = get_test_server_addr();
-        let (server, _receiver) = Server::with_addr(&addr).expect("Server should be created");
-        let addr1 = "127.0.0.1:8001".parse().unwrap();
-        let addr2 = "127.0.0.1:8002".parse().unwrap();
-
-        let id1 = server.get_or_add_player_id(&addr1);
-        let id2 = server.get_or_add_player_id(&addr2);
-        let id1_again = server.get_or_add_player_id(&addr1);
-
-        assert_eq!(id1, 1); // First player gets ID 1
-        assert_eq!(id2, 2); // Second player gets ID 2
-        assert_eq!(id1_again, id1); // Same player gets same ID
+        let mode = game_mode_by_name(DEFAULT_GAME_MODE).unwrap();
+        let server = Server::with_addr(&addr, mode).expect("Server should be created");
+        assert_eq!(server.transport.local_addr().unwrap().to_string(), addr);
     }
 
     #[test]
-    fn test_handle_packet() {
-        use shared::generated;
+    fn test_game_mode_from_flag_or_env() {
+        let args = |list: &[&str]| list.iter().map(|arg| arg.to_string()).collect::<Vec<_>>();
 
-        let addr = get_test_server_addr();
-        let (server, receiver) = Server::with_addr(&addr).expect("Server should be created");
-        let client_addr = "127.0.0.1:8001".parse().unwrap();
-
-        // Create a test packet
-        let mut builder = FlatBufferBuilder::new();
-        let commands = vec![generated::PlayerCommand::MoveRight];
-        let commands_vec = builder.create_vector(&commands);
-        let player_commands = generated::PlayerCommands::create(
-            &mut builder,
-            &generated::PlayerCommandsArgs {
-                sequence: 1,
-                dt_micro: 16667, // ~60fps
-                commands: Some(commands_vec),
-                client_timestamp_micro: SystemTime::now()
-                    .duration_since(UNIX_EPOCH)
-                    .unwrap()
-                    .as_micros() as u64,
-            },
+        assert_eq!(
+            game_mode_name(args(&[]).into_iter(), None).unwrap(),
+            "classic"
+        );
+        assert_eq!(
+            game_mode_name(args(&[]).into_iter(), Some("tag".to_string())).unwrap(),
+            "tag"
         );
-        builder.finish(player_commands, None);
-        let packet = builder.finished_data();
-
-        // Handle the packet
-        server.handle_packet(packet, client_addr);
-
-        // Check if command was received
-        let mutate_command = receiver
-            .recv_timeout(Duration::from_secs(1))
-            .expect("Should receive command");
-
-        let player_id = mutate_command.player_id;
-        let received_commands = mutate_command.player_state_command;
-
-        assert_eq!(player_id, 1); // First player gets ID 1
-        assert_eq!(received_commands.sequence, 1);
-        assert_eq!(received_commands.commands.len(), 1);
         assert_eq!(
-            received_commands.commands[0],
-            generated::PlayerCommand::MoveRight
+            game_mode_name(
+                args(&["--mode", "race"]).into_iter(),
+                Some("tag".to_string())
+            )
+            .unwrap(),
+            "race"
         );
+        assert!(game_mode_name(args(&["--mode"]).into_iter(), None).is_err());
+        assert!(game_mode_name(args(&["--scene", "x"]).into_iter(), None).is_err());
     }
 }
@@ -1,7 +1,6 @@
 use flatbuffers::FlatBufferBuilder;
 use shared::generated::{PlayerCommand, PlayerCommands, PlayerCommandsArgs};
-use shared::state::{GameState, PlayerState, SceneObject, SpawnPoint, Vec2};
-use std::collections::{BinaryHeap, HashMap};
+use shared::state::{GameState, PhysicsParams, PlayerInput, PlayerState, Snapshot, Vec2};
 use std::net::UdpSocket;
 use std::thread;
 use std::time::{Duration, SystemTime, UNIX_EPOCH};
@@ -27,21 +26,7 @@ fn test_client_server_communication() {
                 println!("Server received {} bytes from {}", amt, src);
                 // Create a simple game state response
                 let mut builder = FlatBufferBuilder::new();
-                let mut game_state = GameState {
-                    players: HashMap::new(),
-                    collidables: vec![],
-                    width: 800.0,
-                    height: 600.0,
-                    spawn_point: SpawnPoint { x: 100.0, y: 100.0 },
-                    win_point: SceneObject {
-                        x: 2750.0,
-                        y: 159.0,
-                        w: 100.0,
-                        h: 20.0,
-                    },
-                    cached_dt_micros: 0,
-                    scheduled_commands: BinaryHeap::new(),
-                };
+                let mut game_state = GameState::empty(800.0, 600.0);
 
                 // Add a client player (id: 1)
                 let client_player = PlayerState {
@@ -51,6 +36,16 @@ fn test_client_server_communication() {
                     vel: Vec2::new(0.0, 0.0),
                     grounded: true,
                     jump_timer: 0.0,
+                    drop_timer: 0.0,
+                    respawn_point: Vec2::ZERO,
+                    is_it: false,
+                    facing: 1.0,
+                    wall_dir: 0.0,
+                    air_jumps: 0,
+                    dash_timer: 0.0,
+                    input: PlayerInput::default(),
+                    prev_input: PlayerInput::default(),
+                    pending_press: PlayerInput::default(),
                     color: shared::generated::Color::Red,
                     size: 32.0,
                 };
@@ -63,6 +58,16 @@ fn test_client_server_communication() {
                     vel: Vec2::new(0.0, 0.0),
                     grounded: true,
                     jump_timer: 0.0,
+                    drop_timer: 0.0,
+                    respawn_point: Vec2::ZERO,
+                    is_it: false,
+                    facing: 1.0,
+                    wall_dir: 0.0,
+                    air_jumps: 0,
+                    dash_timer: 0.0,
+                    input: PlayerInput::default(),
+                    prev_input: PlayerInput::default(),
+                    pending_press: PlayerInput::default(),
                     color: shared::generated::Color::Blue,
                     size: 32.0,
                 };
@@ -123,6 +128,8 @@ fn test_client_server_communication() {
                 .duration_since(UNIX_EPOCH)
                 .unwrap()
                 .as_micros() as u64,
+            move_axis: 0,
+            ..Default::default()
         },
     );
     builder.finish(player_commands, None);
@@ -141,22 +148,29 @@ fn test_client_server_communication() {
     match client_socket.recv_from(&mut buf) {
         Ok((amt, src)) => {
             println!("Client received {} bytes from {}", amt, src);
-            // Try to parse the response as a GameState
-            let (game_state, client_player, sequence, server_delay) =
-                GameState::deserialize(&buf[..amt]);
+            // Try to parse the response against the same scene as the server
+            let Snapshot {
+                players,
+                client_player,
+                sequence,
+                physics_params,
+                ..
+            } = GameState::empty(800.0, 600.0).deserialize(&buf[..amt]);
             println!(
                 "Deserialized game state has {} players, client_player_id: {}, sequence: {}",
-                game_state.players.len(),
+                players.len(),
                 client_player.id,
                 sequence
             );
 
             // Verify we got both the client player and other player
             assert_eq!(client_player.id, 1, "Client player should have id 1");
-            assert_eq!(game_state.players.len(), 1, "Should have 1 other player");
-            assert!(
-                game_state.players.contains_key(&2),
-                "Other player should have id 2"
+            assert_eq!(players.len(), 1, "Should have 1 other player");
+            assert!(players.contains_key(&2), "Other player should have id 2");
+            assert_eq!(
+                physics_params,
+                Some(PhysicsParams::default()),
+                "Full snapshots carry the physics"
             );
         }
         Err(e) => {
@@ -0,0 +1,141 @@
+use criterion::{Criterion, criterion_group, criterion_main};
+use shared::generated::Color;
+use shared::state::{CollidableKind, GameState, PlayerInput, PlayerState, SceneObject, Vec2};
+use std::hint::black_box;
+
+const PLATFORMS: usize = 10_000;
+const PLAYERS: u32 = 100;
+const WIDTH: f32 = 100_000.0;
+const HEIGHT: f32 = 10_000.0;
+
+/// Small LCG so the generated scene is the same on every run
+struct Lcg(u64);
+
+impl Lcg {
+    fn next(&mut self) -> f32 {
+        self.0 = self
+            .0
+            .wrapping_mul(6364136223846793005)
+            .wrapping_add(1442695040888963407);
+        (self.0 >> 40) as f32 / (1u64 << 24) as f32
+    }
+}
+
+fn generated_scene() -> GameState {
+    let mut rng = Lcg(42);
+    let collidables: Vec<SceneObject> = (0..PLATFORMS)
+        .map(|_| SceneObject {
+            x: rng.next() * WIDTH,
+            y: rng.next() * HEIGHT,
+            w: 40.0 + rng.next() * 200.0,
+            h: 20.0,
+            kind: CollidableKind::Solid,
+        })
+        .collect();
+    let players = (1..=PLAYERS)
+        .map(|id| {
+            let player = PlayerState {
+                id,
+                name: format!("player {}", id),
+                pos: Vec2::new(rng.next() * WIDTH * 0.9, rng.next() * HEIGHT),
+                vel: Vec2::new(rng.next() * 400.0 - 200.0, 0.0),
+                grounded: false,
+                jump_timer: 0.0,
+                drop_timer: 0.0,
+                respawn_point: Vec2::ZERO,
+                is_it: false,
+                facing: 1.0,
+                wall_dir: 0.0,
+                air_jumps: 0,
+                dash_timer: 0.0,
+                input: PlayerInput::default(),
+                prev_input: PlayerInput::default(),
+                pending_press: PlayerInput::default(),
+                color: Color::Red,
+                size: 16.0,
+            };
+            (id, player)
+        })
+        .collect();
+
+    let mut state = GameState::empty(WIDTH, HEIGHT);
+    state.win_point.x = WIDTH - 100.0;
+    state.collidables = collidables;
+    state.players = players;
+    state.rebuild_collision_grid();
+    state
+}
+
+/// One 16ms physics step for every player
+fn step(state: &mut GameState) {
+    state.mutate(&[], 16_000, None);
+}
+
+/// What a player could run into during one step: its box grown by a step's worth of
+/// movement in every direction
+fn reach(player: &PlayerState) -> SceneObject {
+    const MARGIN: f32 = 32.0;
+    SceneObject {
+        x: player.pos.x - MARGIN,
+        y: player.pos.y - MARGIN,
+        w: player.size + 2.0 * MARGIN,
+        h: player.size + 2.0 * MARGIN,
+        kind: CollidableKind::Solid,
+    }
+}
+
+fn overlaps(a: &SceneObject, b: &SceneObject) -> bool {
+    a.x < b.x + b.w && a.x + a.w > b.x && a.y < b.y + b.h && a.y + a.h > b.y
+}
+
+/// Collidables every player could touch this step, found through the grid
+fn grid_candidates(state: &GameState) -> usize {
+    state
+        .players
+        .values()
+        .map(|player| {
+            let reach = reach(player);
+            state
+                .collision_grid
+                .query_collidables(&reach)
+                .filter(|index| overlaps(&reach, &state.collidables[*index]))
+                .count()
+        })
+        .sum()
+}
+
+/// The same by checking every collidable for every player
+fn naive_candidates(state: &GameState) -> usize {
+    state
+        .players
+        .values()
+        .map(|player| {
+            let reach = reach(player);
+            state
+                .collidables
+                .iter()
+                .filter(|col| overlaps(&reach, col))
+                .count()
+        })
+        .sum()
+}
+
+fn bench_physics(c: &mut Criterion) {
+    let mut state = generated_scene();
+    c.bench_function("physics_10k_platforms", |b| {
+        b.iter(|| step(black_box(&mut state)))
+    });
+}
+
+fn bench_broadphase(c: &mut Criterion) {
+    let state = generated_scene();
+    assert_eq!(grid_candidates(&state), naive_candidates(&state));
+
+    let mut group = c.benchmark_group("collidable_queries_10k_platforms");
+    group.bench_function("grid", |b| b.iter(|| grid_candidates(black_box(&state))));
+    group.bench_function("naive", |b| b.iter(|| naive_candidates(black_box(&state))));
+    group.finish();
+}
+
+criterion_group!(benches, bench_physics, bench_broadphase);
+criterion_main!(benches);
@@ -118,6 +118,99 @@ impl<'a> flatbuffers::Verifiable for Color {
 }
 
 impl flatbuffers::SimpleToVerifyInSlice for Color {}
+#[deprecated(since = "2.0.0", note = "Use associated constants instead. This will no longer be generated in 2021.")]
+pub const ENUM_MIN_RACE_EVENT_KIND: u8 = 0;
+#[deprecated(since = "2.0.0", note = "Use associated constants instead. This will no longer be generated in 2021.")]
+pub const ENUM_MAX_RACE_EVENT_KIND: u8 = 3;
+#[deprecated(since = "2.0.0", note = "Use associated constants instead. This will no longer be generated in 2021.")]
+#[allow(non_camel_case_types)]
+pub const ENUM_VALUES_RACE_EVENT_KIND: [RaceEventKind; 4] = [
+  RaceEventKind::Countdown,
+  RaceEventKind::Started,
+  RaceEventKind::Finished,
+  RaceEventKind::Results,
+];
+
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+#[repr(transparent)]
+pub struct RaceEventKind(pub u8);
+#[allow(non_upper_case_globals)]
+impl RaceEventKind {
+  pub const Countdown: Self = Self(0);
+  pub const Started: Self = Self(1);
+  pub const Finished: Self = Self(2);
+  pub const Results: Self = Self(3);
+
+  pub const ENUM_MIN: u8 = 0;
+  pub const ENUM_MAX: u8 = 3;
+  pub const ENUM_VALUES: &'static [Self] = &[
+    Self::Countdown,
+    Self::Started,
+    Self::Finished,
+    Self::Results,
+  ];
+  /// Returns the variant's name or "" if unknown.
+  pub fn variant_name(self) -> Option<&'static str> {
+    match self {
+      Self::Countdown => Some("Countdown"),
+      Self::Started => Some("Started"),
+      Self::Finished => Some("Finished"),
+      Self::Results => Some("Results"),
+      _ => None,
+    }
+  }
+}
+impl core::fmt::Debug for RaceEventKind {
+  fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+    if let Some(name) = self.variant_name() {
+      f.write_str(name)
+    } else {
+      f.write_fmt(format_args!("<UNKNOWN {:?}>", self.0))
+    }
+  }
+}
+impl<'a> flatbuffers::Follow<'a> for RaceEventKind {
+  type Inner = Self;
+  #[inline]
+  unsafe fn follow(buf: &'a [u8], loc: usize) -> Self::Inner {
+    let b = flatbuffers::read_scalar_at::<u8>(buf, loc);
+    Self(b)
+  }
+}
+
+impl flatbuffers::Push for RaceEventKind {
+    type Output = RaceEventKind;
+    #[inline]
+    unsafe fn push(&self, dst: &mut [u8], _written_len: usize) {
+        flatbuffers::emplace_scalar::<u8>(dst, self.0);
+    }
+}
+
+impl flatbuffers::EndianScalar for RaceEventKind {
+  type Scalar = u8;
+  #[inline]
+  fn to_little_endian(self) -> u8 {
+    self.0.to_le()
+  }
+  #[inline]
+  #[allow(clippy::wrong_self_convention)]
+  fn from_little_endian(v: u8) -> Self {
+    let b = u8::from_le(v);
+    Self(b)
+  }
+}
+
+impl<'a> flatbuffers::Verifiable for RaceEventKind {
+  #[inline]
+  fn run_verifier(
+    v: &mut flatbuffers::Verifier, pos: usize
+  ) -> Result<(), flatbuffers::InvalidFlatbuffer> {
+    use self::flatbuffers::Verifiable;
+    u8::run_verifier(v, pos)
+  }
+}
+
+impl flatbuffers::SimpleToVerifyInSlice for RaceEventKind {}
 // struct Vector2, aligned to 4
 #[repr(transparent)]
 #[derive(Clone, Copy, PartialEq)]
@@ -243,52 +336,880 @@ impl<'a> Vector2 {
       );
     }
   }
-
+
+}
+
+// struct QuantizedPosition, aligned to 2
+#[repr(transparent)]
+#[derive(Clone, Copy, PartialEq)]
+pub struct QuantizedPosition(pub [u8; 4]);
+impl Default for QuantizedPosition { 
+  fn default() -> Self { 
+    Self([0; 4])
+  }
+}
+impl core::fmt::Debug for QuantizedPosition {
+  fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+    f.debug_struct("QuantizedPosition")
+      .field("x", &self.x())
+      .field("y", &self.y())
+      .finish()
+  }
+}
+
+impl flatbuffers::SimpleToVerifyInSlice for QuantizedPosition {}
+impl<'a> flatbuffers::Follow<'a> for QuantizedPosition {
+  type Inner = &'a QuantizedPosition;
+  #[inline]
+  unsafe fn follow(buf: &'a [u8], loc: usize) -> Self::Inner {
+    <&'a QuantizedPosition>::follow(buf, loc)
+  }
+}
+impl<'a> flatbuffers::Follow<'a> for &'a QuantizedPosition {
+  type Inner = &'a QuantizedPosition;
+  #[inline]
+  unsafe fn follow(buf: &'a [u8], loc: usize) -> Self::Inner {
+    flatbuffers::follow_cast_ref::<QuantizedPosition>(buf, loc)
+  }
+}
+impl<'b> flatbuffers::Push for QuantizedPosition {
+    type Output = QuantizedPosition;
+    #[inline]
+    unsafe fn push(&self, dst: &mut [u8], _written_len: usize) {
+        let src = ::core::slice::from_raw_parts(self as *const QuantizedPosition as *const u8, <Self as flatbuffers::Push>::size());
+        dst.copy_from_slice(src);
+    }
+    #[inline]
+    fn alignment() -> flatbuffers::PushAlignment {
+        flatbuffers::PushAlignment::new(2)
+    }
+}
+
+impl<'a> flatbuffers::Verifiable for QuantizedPosition {
+  #[inline]
+  fn run_verifier(
+    v: &mut flatbuffers::Verifier, pos: usize
+  ) -> Result<(), flatbuffers::InvalidFlatbuffer> {
+    use self::flatbuffers::Verifiable;
+    v.in_buffer::<Self>(pos)
+  }
+}
+
+impl<'a> QuantizedPosition {
+  #[allow(clippy::too_many_arguments)]
+  pub fn new(
+    x: u16,
+    y: u16,
+  ) -> Self {
+    let mut s = Self([0; 4]);
+    s.set_x(x);
+    s.set_y(y);
+    s
+  }
+
+  pub fn x(&self) -> u16 {
+    let mut mem = core::mem::MaybeUninit::<<u16 as EndianScalar>::Scalar>::uninit();
+    // Safety:
+    // Created from a valid Table for this object
+    // Which contains a valid value in this slot
+    EndianScalar::from_little_endian(unsafe {
+      core::ptr::copy_nonoverlapping(
+        self.0[0..].as_ptr(),
+        mem.as_mut_ptr() as *mut u8,
+        core::mem::size_of::<<u16 as EndianScalar>::Scalar>(),
+      );
+      mem.assume_init()
+    })
+  }
+
+  pub fn set_x(&mut self, x: u16) {
+    let x_le = x.to_little_endian();
+    // Safety:
+    // Created from a valid Table for this object
+    // Which contains a valid value in this slot
+    unsafe {
+      core::ptr::copy_nonoverlapping(
+        &x_le as *const _ as *const u8,
+        self.0[0..].as_mut_ptr(),
+        core::mem::size_of::<<u16 as EndianScalar>::Scalar>(),
+      );
+    }
+  }
+
+  pub fn y(&self) -> u16 {
+    let mut mem = core::mem::MaybeUninit::<<u16 as EndianScalar>::Scalar>::uninit();
+    // Safety:
+    // Created from a valid Table for this object
+    // Which contains a valid value in this slot
+    EndianScalar::from_little_endian(unsafe {
+      core::ptr::copy_nonoverlapping(
+        self.0[2..].as_ptr(),
+        mem.as_mut_ptr() as *mut u8,
+        core::mem::size_of::<<u16 as EndianScalar>::Scalar>(),
+      );
+      mem.assume_init()
+    })
+  }
+
+  pub fn set_y(&mut self, x: u16) {
+    let x_le = x.to_little_endian();
+    // Safety:
+    // Created from a valid Table for this object
+    // Which contains a valid value in this slot
+    unsafe {
+      core::ptr::copy_nonoverlapping(
+        &x_le as *const _ as *const u8,
+        self.0[2..].as_mut_ptr(),
+        core::mem::size_of::<<u16 as EndianScalar>::Scalar>(),
+      );
+    }
+  }
+
+}
+
+pub enum ClientPlayerOffset {}
+#[derive(Copy, Clone, PartialEq)]
+
+pub struct ClientPlayer<'a> {
+  pub _tab: flatbuffers::Table<'a>,
+}
+
+impl<'a> flatbuffers::Follow<'a> for ClientPlayer<'a> {
+  type Inner = ClientPlayer<'a>;
+  #[inline]
+  unsafe fn follow(buf: &'a [u8], loc: usize) -> Self::Inner {
+    Self { _tab: flatbuffers::Table::new(buf, loc) }
+  }
+}
+
+impl<'a> ClientPlayer<'a> {
+  pub const VT_ID: flatbuffers::VOffsetT = 4;
+  pub const VT_NAME: flatbuffers::VOffsetT = 6;
+  pub const VT_POS: flatbuffers::VOffsetT = 8;
+  pub const VT_VEL: flatbuffers::VOffsetT = 10;
+  pub const VT_COLOR: flatbuffers::VOffsetT = 12;
+  pub const VT_GROUNDED: flatbuffers::VOffsetT = 14;
+  pub const VT_JUMP_TIMER: flatbuffers::VOffsetT = 16;
+  pub const VT_SIZE: flatbuffers::VOffsetT = 18;
+  pub const VT_DROP_TIMER: flatbuffers::VOffsetT = 20;
+  pub const VT_RESPAWN_POINT: flatbuffers::VOffsetT = 22;
+  pub const VT_IS_IT: flatbuffers::VOffsetT = 24;
+  pub const VT_FACING: flatbuffers::VOffsetT = 26;
+  pub const VT_WALL_DIR: flatbuffers::VOffsetT = 28;
+  pub const VT_AIR_JUMPS: flatbuffers::VOffsetT = 30;
+  pub const VT_DASH_TIMER: flatbuffers::VOffsetT = 32;
+  pub const VT_INPUT_AXIS: flatbuffers::VOffsetT = 34;
+  pub const VT_INPUT_JUMP: flatbuffers::VOffsetT = 36;
+  pub const VT_INPUT_DOWN: flatbuffers::VOffsetT = 38;
+  pub const VT_INPUT_DASH: flatbuffers::VOffsetT = 40;
+  pub const VT_PREV_INPUT_AXIS: flatbuffers::VOffsetT = 42;
+  pub const VT_PREV_INPUT_JUMP: flatbuffers::VOffsetT = 44;
+  pub const VT_PREV_INPUT_DOWN: flatbuffers::VOffsetT = 46;
+  pub const VT_PREV_INPUT_DASH: flatbuffers::VOffsetT = 48;
+  pub const VT_PENDING_JUMP: flatbuffers::VOffsetT = 50;
+  pub const VT_PENDING_DASH: flatbuffers::VOffsetT = 52;
+
+  #[inline]
+  pub unsafe fn init_from_table(table: flatbuffers::Table<'a>) -> Self {
+    ClientPlayer { _tab: table }
+  }
+  #[allow(unused_mut)]
+  pub fn create<'bldr: 'args, 'args: 'mut_bldr, 'mut_bldr, A: flatbuffers::Allocator + 'bldr>(
+    _fbb: &'mut_bldr mut flatbuffers::FlatBufferBuilder<'bldr, A>,
+    args: &'args ClientPlayerArgs<'args>
+  ) -> flatbuffers::WIPOffset<ClientPlayer<'bldr>> {
+    let mut builder = ClientPlayerBuilder::new(_fbb);
+    builder.add_prev_input_axis(args.prev_input_axis);
+    builder.add_input_axis(args.input_axis);
+    builder.add_dash_timer(args.dash_timer);
+    builder.add_air_jumps(args.air_jumps);
+    builder.add_wall_dir(args.wall_dir);
+    builder.add_facing(args.facing);
+    if let Some(x) = args.respawn_point { builder.add_respawn_point(x); }
+    builder.add_drop_timer(args.drop_timer);
+    builder.add_size(args.size);
+    builder.add_jump_timer(args.jump_timer);
+    if let Some(x) = args.vel { builder.add_vel(x); }
+    if let Some(x) = args.pos { builder.add_pos(x); }
+    if let Some(x) = args.name { builder.add_name(x); }
+    builder.add_id(args.id);
+    builder.add_pending_dash(args.pending_dash);
+    builder.add_pending_jump(args.pending_jump);
+    builder.add_prev_input_dash(args.prev_input_dash);
+    builder.add_prev_input_down(args.prev_input_down);
+    builder.add_prev_input_jump(args.prev_input_jump);
+    builder.add_input_dash(args.input_dash);
+    builder.add_input_down(args.input_down);
+    builder.add_input_jump(args.input_jump);
+    builder.add_is_it(args.is_it);
+    builder.add_grounded(args.grounded);
+    builder.add_color(args.color);
+    builder.finish()
+  }
+
+
+  #[inline]
+  pub fn id(&self) -> u32 {
+    // Safety:
+    // Created from valid Table for this object
+    // which contains a valid value in this slot
+    unsafe { self._tab.get::<u32>(ClientPlayer::VT_ID, Some(0)).unwrap()}
+  }
+  #[inline]
+  pub fn name(&self) -> Option<&'a str> {
+    // Safety:
+    // Created from valid Table for this object
+    // which contains a valid value in this slot
+    unsafe { self._tab.get::<flatbuffers::ForwardsUOffset<&str>>(ClientPlayer::VT_NAME, None)}
+  }
+  #[inline]
+  pub fn pos(&self) -> Option<&'a Vector2> {
+    // Safety:
+    // Created from valid Table for this object
+    // which contains a valid value in this slot
+    unsafe { self._tab.get::<Vector2>(ClientPlayer::VT_POS, None)}
+  }
+  #[inline]
+  pub fn vel(&self) -> Option<&'a Vector2> {
+    // Safety:
+    // Created from valid Table for this object
+    // which contains a valid value in this slot
+    unsafe { self._tab.get::<Vector2>(ClientPlayer::VT_VEL, None)}
+  }
+  #[inline]
+  pub fn color(&self) -> Color {
+    // Safety:
+    // Created from valid Table for this object
+    // which contains a valid value in this slot
+    unsafe { self._tab.get::<Color>(ClientPlayer::VT_COLOR, Some(Color::Red)).unwrap()}
+  }
+  #[inline]
+  pub fn grounded(&self) -> bool {
+    // Safety:
+    // Created from valid Table for this object
+    // which contains a valid value in this slot
+    unsafe { self._tab.get::<bool>(ClientPlayer::VT_GROUNDED, Some(false)).unwrap()}
+  }
+  #[inline]
+  pub fn jump_timer(&self) -> f32 {
+    // Safety:
+    // Created from valid Table for this object
+    // which contains a valid value in this slot
+    unsafe { self._tab.get::<f32>(ClientPlayer::VT_JUMP_TIMER, Some(0.0)).unwrap()}
+  }
+  #[inline]
+  pub fn size(&self) -> f32 {
+    // Safety:
+    // Created from valid Table for this object
+    // which contains a valid value in this slot
+    unsafe { self._tab.get::<f32>(ClientPlayer::VT_SIZE, Some(0.0)).unwrap()}
+  }
+  #[inline]
+  pub fn drop_timer(&self) -> f32 {
+    // Safety:
+    // Created from valid Table for this object
+    // which contains a valid value in this slot
+    unsafe { self._tab.get::<f32>(ClientPlayer::VT_DROP_TIMER, Some(0.0)).unwrap()}
+  }
+  #[inline]
+  pub fn respawn_point(&self) -> Option<&'a Vector2> {
+    // Safety:
+    // Created from valid Table for this object
+    // which contains a valid value in this slot
+    unsafe { self._tab.get::<Vector2>(ClientPlayer::VT_RESPAWN_POINT, None)}
+  }
+  #[inline]
+  pub fn is_it(&self) -> bool {
+    // Safety:
+    // Created from valid Table for this object
+    // which contains a valid value in this slot
+    unsafe { self._tab.get::<bool>(ClientPlayer::VT_IS_IT, Some(false)).unwrap()}
+  }
+  #[inline]
+  pub fn facing(&self) -> f32 {
+    // Safety:
+    // Created from valid Table for this object
+    // which contains a valid value in this slot
+    unsafe { self._tab.get::<f32>(ClientPlayer::VT_FACING, Some(1.0)).unwrap()}
+  }
+  #[inline]
+  pub fn wall_dir(&self) -> f32 {
+    // Safety:
+    // Created from valid Table for this object
+    // which contains a valid value in this slot
+    unsafe { self._tab.get::<f32>(ClientPlayer::VT_WALL_DIR, Some(0.0)).unwrap()}
+  }
+  #[inline]
+  pub fn air_jumps(&self) -> u32 {
+    // Safety:
+    // Created from valid Table for this object
+    // which contains a valid value in this slot
+    unsafe { self._tab.get::<u32>(ClientPlayer::VT_AIR_JUMPS, Some(0)).unwrap()}
+  }
+  #[inline]
+  pub fn dash_timer(&self) -> f32 {
+    // Safety:
+    // Created from valid Table for this object
+    // which contains a valid value in this slot
+    unsafe { self._tab.get::<f32>(ClientPlayer::VT_DASH_TIMER, Some(0.0)).unwrap()}
+  }
+  #[inline]
+  pub fn input_axis(&self) -> f32 {
+    // Safety:
+    // Created from valid Table for this object
+    // which contains a valid value in this slot
+    unsafe { self._tab.get::<f32>(ClientPlayer::VT_INPUT_AXIS, Some(0.0)).unwrap()}
+  }
+  #[inline]
+  pub fn input_jump(&self) -> bool {
+    // Safety:
+    // Created from valid Table for this object
+    // which contains a valid value in this slot
+    unsafe { self._tab.get::<bool>(ClientPlayer::VT_INPUT_JUMP, Some(false)).unwrap()}
+  }
+  #[inline]
+  pub fn input_down(&self) -> bool {
+    // Safety:
+    // Created from valid Table for this object
+    // which contains a valid value in this slot
+    unsafe { self._tab.get::<bool>(ClientPlayer::VT_INPUT_DOWN, Some(false)).unwrap()}
+  }
+  #[inline]
+  pub fn input_dash(&self) -> bool {
+    // Safety:
+    // Created from valid Table for this object
+    // which contains a valid value in this slot
+    unsafe { self._tab.get::<bool>(ClientPlayer::VT_INPUT_DASH, Some(false)).unwrap()}
+  }
+  #[inline]
+  pub fn prev_input_axis(&self) -> f32 {
+    // Safety:
+    // Created from valid Table for this object
+    // which contains a valid value in this slot
+    unsafe { self._tab.get::<f32>(ClientPlayer::VT_PREV_INPUT_AXIS, Some(0.0)).unwrap()}
+  }
+  #[inline]
+  pub fn prev_input_jump(&self) -> bool {
+    // Safety:
+    // Created from valid Table for this object
+    // which contains a valid value in this slot
+    unsafe { self._tab.get::<bool>(ClientPlayer::VT_PREV_INPUT_JUMP, Some(false)).unwrap()}
+  }
+  #[inline]
+  pub fn prev_input_down(&self) -> bool {
+    // Safety:
+    // Created from valid Table for this object
+    // which contains a valid value in this slot
+    unsafe { self._tab.get::<bool>(ClientPlayer::VT_PREV_INPUT_DOWN, Some(false)).unwrap()}
+  }
+  #[inline]
+  pub fn prev_input_dash(&self) -> bool {
+    // Safety:
+    // Created from valid Table for this object
+    // which contains a valid value in this slot
+    unsafe { self._tab.get::<bool>(ClientPlayer::VT_PREV_INPUT_DASH, Some(false)).unwrap()}
+  }
+  #[inline]
+  pub fn pending_jump(&self) -> bool {
+    // Safety:
+    // Created from valid Table for this object
+    // which contains a valid value in this slot
+    unsafe { self._tab.get::<bool>(ClientPlayer::VT_PENDING_JUMP, Some(false)).unwrap()}
+  }
+  #[inline]
+  pub fn pending_dash(&self) -> bool {
+    // Safety:
+    // Created from valid Table for this object
+    // which contains a valid value in this slot
+    unsafe { self._tab.get::<bool>(ClientPlayer::VT_PENDING_DASH, Some(false)).unwrap()}
+  }
+}
+
+impl flatbuffers::Verifiable for ClientPlayer<'_> {
+  #[inline]
+  fn run_verifier(
+    v: &mut flatbuffers::Verifier, pos: usize
+  ) -> Result<(), flatbuffers::InvalidFlatbuffer> {
+    use self::flatbuffers::Verifiable;
+    v.visit_table(pos)?
+     .visit_field::<u32>("id", Self::VT_ID, false)?
+     .visit_field::<flatbuffers::ForwardsUOffset<&str>>("name", Self::VT_NAME, false)?
+     .visit_field::<Vector2>("pos", Self::VT_POS, false)?
+     .visit_field::<Vector2>("vel", Self::VT_VEL, false)?
+     .visit_field::<Color>("color", Self::VT_COLOR, false)?
+     .visit_field::<bool>("grounded", Self::VT_GROUNDED, false)?
+     .visit_field::<f32>("jump_timer", Self::VT_JUMP_TIMER, false)?
+     .visit_field::<f32>("size", Self::VT_SIZE, false)?
+     .visit_field::<f32>("drop_timer", Self::VT_DROP_TIMER, false)?
+     .visit_field::<Vector2>("respawn_point", Self::VT_RESPAWN_POINT, false)?
+     .visit_field::<bool>("is_it", Self::VT_IS_IT, false)?
+     .visit_field::<f32>("facing", Self::VT_FACING, false)?
+     .visit_field::<f32>("wall_dir", Self::VT_WALL_DIR, false)?
+     .visit_field::<u32>("air_jumps", Self::VT_AIR_JUMPS, false)?
+     .visit_field::<f32>("dash_timer", Self::VT_DASH_TIMER, false)?
+     .visit_field::<f32>("input_axis", Self::VT_INPUT_AXIS, false)?
+     .visit_field::<bool>("input_jump", Self::VT_INPUT_JUMP, false)?
+     .visit_field::<bool>("input_down", Self::VT_INPUT_DOWN, false)?
+     .visit_field::<bool>("input_dash", Self::VT_INPUT_DASH, false)?
+     .visit_field::<f32>("prev_input_axis", Self::VT_PREV_INPUT_AXIS, false)?
+     .visit_field::<bool>("prev_input_jump", Self::VT_PREV_INPUT_JUMP, false)?
+     .visit_field::<bool>("prev_input_down", Self::VT_PREV_INPUT_DOWN, false)?
+     .visit_field::<bool>("prev_input_dash", Self::VT_PREV_INPUT_DASH, false)?
+     .visit_field::<bool>("pending_jump", Self::VT_PENDING_JUMP, false)?
+     .visit_field::<bool>("pending_dash", Self::VT_PENDING_DASH, false)?
+     .finish();
+    Ok(())
+  }
+}
+pub struct ClientPlayerArgs<'a> {
+    pub id: u32,
+    pub name: Option<flatbuffers::WIPOffset<&'a str>>,
+    pub pos: Option<&'a Vector2>,
+    pub vel: Option<&'a Vector2>,
+    pub color: Color,
+    pub grounded: bool,
+    pub jump_timer: f32,
+    pub size: f32,
+    pub drop_timer: f32,
+    pub respawn_point: Option<&'a Vector2>,
+    pub is_it: bool,
+    pub facing: f32,
+    pub wall_dir: f32,
+    pub air_jumps: u32,
+    pub dash_timer: f32,
+    pub input_axis: f32,
+    pub input_jump: bool,
+    pub input_down: bool,
+    pub input_dash: bool,
+    pub prev_input_axis: f32,
+    pub prev_input_jump: bool,
+    pub prev_input_down: bool,
+    pub prev_input_dash: bool,
+    pub pending_jump: bool,
+    pub pending_dash: bool,
+}
+impl<'a> Default for ClientPlayerArgs<'a> {
+  #[inline]
+  fn default() -> Self {
+    ClientPlayerArgs {
+      id: 0,
+      name: None,
+      pos: None,
+      vel: None,
+      color: Color::Red,
+      grounded: false,
+      jump_timer: 0.0,
+      size: 0.0,
+      drop_timer: 0.0,
+      respawn_point: None,
+      is_it: false,
+      facing: 1.0,
+      wall_dir: 0.0,
+      air_jumps: 0,
+      dash_timer: 0.0,
+      input_axis: 0.0,
+      input_jump: false,
+      input_down: false,
+      input_dash: false,
+      prev_input_axis: 0.0,
+      prev_input_jump: false,
+      prev_input_down: false,
+      prev_input_dash: false,
+      pending_jump: false,
+      pending_dash: false,
+    }
+  }
+}
+
+pub struct ClientPlayerBuilder<'a: 'b, 'b, A: flatbuffers::Allocator + 'a> {
+  fbb_: &'b mut flatbuffers::FlatBufferBuilder<'a, A>,
+  start_: flatbuffers::WIPOffset<flatbuffers::TableUnfinishedWIPOffset>,
+}
+impl<'a: 'b, 'b, A: flatbuffers::Allocator + 'a> ClientPlayerBuilder<'a, 'b, A> {
+  #[inline]
+  pub fn add_id(&mut self, id: u32) {
+    self.fbb_.push_slot::<u32>(ClientPlayer::VT_ID, id, 0);
+  }
+  #[inline]
+  pub fn add_name(&mut self, name: flatbuffers::WIPOffset<&'b  str>) {
+    self.fbb_.push_slot_always::<flatbuffers::WIPOffset<_>>(ClientPlayer::VT_NAME, name);
+  }
+  #[inline]
+  pub fn add_pos(&mut self, pos: &Vector2) {
+    self.fbb_.push_slot_always::<&Vector2>(ClientPlayer::VT_POS, pos);
+  }
+  #[inline]
+  pub fn add_vel(&mut self, vel: &Vector2) {
+    self.fbb_.push_slot_always::<&Vector2>(ClientPlayer::VT_VEL, vel);
+  }
+  #[inline]
+  pub fn add_color(&mut self, color: Color) {
+    self.fbb_.push_slot::<Color>(ClientPlayer::VT_COLOR, color, Color::Red);
+  }
+  #[inline]
+  pub fn add_grounded(&mut self, grounded: bool) {
+    self.fbb_.push_slot::<bool>(ClientPlayer::VT_GROUNDED, grounded, false);
+  }
+  #[inline]
+  pub fn add_jump_timer(&mut self, jump_timer: f32) {
+    self.fbb_.push_slot::<f32>(ClientPlayer::VT_JUMP_TIMER, jump_timer, 0.0);
+  }
+  #[inline]
+  pub fn add_size(&mut self, size: f32) {
+    self.fbb_.push_slot::<f32>(ClientPlayer::VT_SIZE, size, 0.0);
+  }
+  #[inline]
+  pub fn add_drop_timer(&mut self, drop_timer: f32) {
+    self.fbb_.push_slot::<f32>(ClientPlayer::VT_DROP_TIMER, drop_timer, 0.0);
+  }
+  #[inline]
+  pub fn add_respawn_point(&mut self, respawn_point: &Vector2) {
+    self.fbb_.push_slot_always::<&Vector2>(ClientPlayer::VT_RESPAWN_POINT, respawn_point);
+  }
+  #[inline]
+  pub fn add_is_it(&mut self, is_it: bool) {
+    self.fbb_.push_slot::<bool>(ClientPlayer::VT_IS_IT, is_it, false);
+  }
+  #[inline]
+  pub fn add_facing(&mut self, facing: f32) {
+    self.fbb_.push_slot::<f32>(ClientPlayer::VT_FACING, facing, 1.0);
+  }
+  #[inline]
+  pub fn add_wall_dir(&mut self, wall_dir: f32) {
+    self.fbb_.push_slot::<f32>(ClientPlayer::VT_WALL_DIR, wall_dir, 0.0);
+  }
+  #[inline]
+  pub fn add_air_jumps(&mut self, air_jumps: u32) {
+    self.fbb_.push_slot::<u32>(ClientPlayer::VT_AIR_JUMPS, air_jumps, 0);
+  }
+  #[inline]
+  pub fn add_dash_timer(&mut self, dash_timer: f32) {
+    self.fbb_.push_slot::<f32>(ClientPlayer::VT_DASH_TIMER, dash_timer, 0.0);
+  }
+  #[inline]
+  pub fn add_input_axis(&mut self, input_axis: f32) {
+    self.fbb_.push_slot::<f32>(ClientPlayer::VT_INPUT_AXIS, input_axis, 0.0);
+  }
+  #[inline]
+  pub fn add_input_jump(&mut self, input_jump: bool) {
+    self.fbb_.push_slot::<bool>(ClientPlayer::VT_INPUT_JUMP, input_jump, false);
+  }
+  #[inline]
+  pub fn add_input_down(&mut self, input_down: bool) {
+    self.fbb_.push_slot::<bool>(ClientPlayer::VT_INPUT_DOWN, input_down, false);
+  }
+  #[inline]
+  pub fn add_input_dash(&mut self, input_dash: bool) {
+    self.fbb_.push_slot::<bool>(ClientPlayer::VT_INPUT_DASH, input_dash, false);
+  }
+  #[inline]
+  pub fn add_prev_input_axis(&mut self, prev_input_axis: f32) {
+    self.fbb_.push_slot::<f32>(ClientPlayer::VT_PREV_INPUT_AXIS, prev_input_axis, 0.0);
+  }
+  #[inline]
+  pub fn add_prev_input_jump(&mut self, prev_input_jump: bool) {
+    self.fbb_.push_slot::<bool>(ClientPlayer::VT_PREV_INPUT_JUMP, prev_input_jump, false);
+  }
+  #[inline]
+  pub fn add_prev_input_down(&mut self, prev_input_down: bool) {
+    self.fbb_.push_slot::<bool>(ClientPlayer::VT_PREV_INPUT_DOWN, prev_input_down, false);
+  }
+  #[inline]
+  pub fn add_prev_input_dash(&mut self, prev_input_dash: bool) {
+    self.fbb_.push_slot::<bool>(ClientPlayer::VT_PREV_INPUT_DASH, prev_input_dash, false);
+  }
+  #[inline]
+  pub fn add_pending_jump(&mut self, pending_jump: bool) {
+    self.fbb_.push_slot::<bool>(ClientPlayer::VT_PENDING_JUMP, pending_jump, false);
+  }
+  #[inline]
+  pub fn add_pending_dash(&mut self, pending_dash: bool) {
+    self.fbb_.push_slot::<bool>(ClientPlayer::VT_PENDING_DASH, pending_dash, false);
+  }
+  #[inline]
+  pub fn new(_fbb: &'b mut flatbuffers::FlatBufferBuilder<'a, A>) -> ClientPlayerBuilder<'a, 'b, A> {
+    let start = _fbb.start_table();
+    ClientPlayerBuilder {
+      fbb_: _fbb,
+      start_: start,
+    }
+  }
+  #[inline]
+  pub fn finish(self) -> flatbuffers::WIPOffset<ClientPlayer<'a>> {
+    let o = self.fbb_.end_table(self.start_);
+    flatbuffers::WIPOffset::new(o.value())
+  }
+}
+
+impl core::fmt::Debug for ClientPlayer<'_> {
+  fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+    let mut ds = f.debug_struct("ClientPlayer");
+      ds.field("id", &self.id());
+      ds.field("name", &self.name());
+      ds.field("pos", &self.pos());
+      ds.field("vel", &self.vel());
+      ds.field("color", &self.color());
+      ds.field("grounded", &self.grounded());
+      ds.field("jump_timer", &self.jump_timer());
+      ds.field("size", &self.size());
+      ds.field("drop_timer", &self.drop_timer());
+      ds.field("respawn_point", &self.respawn_point());
+      ds.field("is_it", &self.is_it());
+      ds.field("facing", &self.facing());
+      ds.field("wall_dir", &self.wall_dir());
+      ds.field("air_jumps", &self.air_jumps());
+      ds.field("dash_timer", &self.dash_timer());
+      ds.field("input_axis", &self.input_axis());
+      ds.field("input_jump", &self.input_jump());
+      ds.field("input_down", &self.input_down());
+      ds.field("input_dash", &self.input_dash());
+      ds.field("prev_input_axis", &self.prev_input_axis());
+      ds.field("prev_input_jump", &self.prev_input_jump());
+      ds.field("prev_input_down", &self.prev_input_down());
+      ds.field("prev_input_dash", &self.prev_input_dash());
+      ds.field("pending_jump", &self.pending_jump());
+      ds.field("pending_dash", &self.pending_dash());
+      ds.finish()
+  }
+}
+pub enum PlayerOffset {}
+#[derive(Copy, Clone, PartialEq)]
+
+pub struct Player<'a> {
+  pub _tab: flatbuffers::Table<'a>,
+}
+
+impl<'a> flatbuffers::Follow<'a> for Player<'a> {
+  type Inner = Player<'a>;
+  #[inline]
+  unsafe fn follow(buf: &'a [u8], loc: usize) -> Self::Inner {
+    Self { _tab: flatbuffers::Table::new(buf, loc) }
+  }
+}
+
+impl<'a> Player<'a> {
+  pub const VT_ID: flatbuffers::VOffsetT = 4;
+  pub const VT_NAME: flatbuffers::VOffsetT = 6;
+  pub const VT_POS: flatbuffers::VOffsetT = 8;
+  pub const VT_COLOR: flatbuffers::VOffsetT = 10;
+  pub const VT_SIZE: flatbuffers::VOffsetT = 12;
+  pub const VT_QPOS: flatbuffers::VOffsetT = 14;
+  pub const VT_IS_IT: flatbuffers::VOffsetT = 16;
+
+  #[inline]
+  pub unsafe fn init_from_table(table: flatbuffers::Table<'a>) -> Self {
+    Player { _tab: table }
+  }
+  #[allow(unused_mut)]
+  pub fn create<'bldr: 'args, 'args: 'mut_bldr, 'mut_bldr, A: flatbuffers::Allocator + 'bldr>(
+    _fbb: &'mut_bldr mut flatbuffers::FlatBufferBuilder<'bldr, A>,
+    args: &'args PlayerArgs<'args>
+  ) -> flatbuffers::WIPOffset<Player<'bldr>> {
+    let mut builder = PlayerBuilder::new(_fbb);
+    builder.add_size(args.size);
+    if let Some(x) = args.pos { builder.add_pos(x); }
+    if let Some(x) = args.name { builder.add_name(x); }
+    builder.add_id(args.id);
+    if let Some(x) = args.qpos { builder.add_qpos(x); }
+    builder.add_is_it(args.is_it);
+    builder.add_color(args.color);
+    builder.finish()
+  }
+
+
+  #[inline]
+  pub fn id(&self) -> u32 {
+    // Safety:
+    // Created from valid Table for this object
+    // which contains a valid value in this slot
+    unsafe { self._tab.get::<u32>(Player::VT_ID, Some(0)).unwrap()}
+  }
+  #[inline]
+  pub fn name(&self) -> Option<&'a str> {
+    // Safety:
+    // Created from valid Table for this object
+    // which contains a valid value in this slot
+    unsafe { self._tab.get::<flatbuffers::ForwardsUOffset<&str>>(Player::VT_NAME, None)}
+  }
+  #[inline]
+  pub fn pos(&self) -> Option<&'a Vector2> {
+    // Safety:
+    // Created from valid Table for this object
+    // which contains a valid value in this slot
+    unsafe { self._tab.get::<Vector2>(Player::VT_POS, None)}
+  }
+  #[inline]
+  pub fn color(&self) -> Color {
+    // Safety:
+    // Created from valid Table for this object
+    // which contains a valid value in this slot
+    unsafe { self._tab.get::<Color>(Player::VT_COLOR, Some(Color::Red)).unwrap()}
+  }
+  #[inline]
+  pub fn size(&self) -> f32 {
+    // Safety:
+    // Created from valid Table for this object
+    // which contains a valid value in this slot
+    unsafe { self._tab.get::<f32>(Player::VT_SIZE, Some(0.0)).unwrap()}
+  }
+  #[inline]
+  pub fn qpos(&self) -> Option<&'a QuantizedPosition> {
+    // Safety:
+    // Created from valid Table for this object
+    // which contains a valid value in this slot
+    unsafe { self._tab.get::<QuantizedPosition>(Player::VT_QPOS, None)}
+  }
+  #[inline]
+  pub fn is_it(&self) -> bool {
+    // Safety:
+    // Created from valid Table for this object
+    // which contains a valid value in this slot
+    unsafe { self._tab.get::<bool>(Player::VT_IS_IT, Some(false)).unwrap()}
+  }
+}
+
+impl flatbuffers::Verifiable for Player<'_> {
+  #[inline]
+  fn run_verifier(
+    v: &mut flatbuffers::Verifier, pos: usize
+  ) -> Result<(), flatbuffers::InvalidFlatbuffer> {
+    use self::flatbuffers::Verifiable;
+    v.visit_table(pos)?
+     .visit_field::<u32>("id", Self::VT_ID, false)?
+     .visit_field::<flatbuffers::ForwardsUOffset<&str>>("name", Self::VT_NAME, false)?
+     .visit_field::<Vector2>("pos", Self::VT_POS, false)?
+     .visit_field::<Color>("color", Self::VT_COLOR, false)?
+     .visit_field::<f32>("size", Self::VT_SIZE, false)?
+     .visit_field::<QuantizedPosition>("qpos", Self::VT_QPOS, false)?
+     .visit_field::<bool>("is_it", Self::VT_IS_IT, false)?
+     .finish();
+    Ok(())
+  }
+}
+pub struct PlayerArgs<'a> {
+    pub id: u32,
+    pub name: Option<flatbuffers::WIPOffset<&'a str>>,
+    pub pos: Option<&'a Vector2>,
+    pub color: Color,
+    pub size: f32,
+    pub qpos: Option<&'a QuantizedPosition>,
+    pub is_it: bool,
+}
+impl<'a> Default for PlayerArgs<'a> {
+  #[inline]
+  fn default() -> Self {
+    PlayerArgs {
+      id: 0,
+      name: None,
+      pos: None,
+      color: Color::Red,
+      size: 0.0,
+      qpos: None,
+      is_it: false,
+    }
+  }
+}
+
+pub struct PlayerBuilder<'a: 'b, 'b, A: flatbuffers::Allocator + 'a> {
+  fbb_: &'b mut flatbuffers::FlatBufferBuilder<'a, A>,
+  start_: flatbuffers::WIPOffset<flatbuffers::TableUnfinishedWIPOffset>,
+}
+impl<'a: 'b, 'b, A: flatbuffers::Allocator + 'a> PlayerBuilder<'a, 'b, A> {
+  #[inline]
+  pub fn add_id(&mut self, id: u32) {
+    self.fbb_.push_slot::<u32>(Player::VT_ID, id, 0);
+  }
+  #[inline]
+  pub fn add_name(&mut self, name: flatbuffers::WIPOffset<&'b  str>) {
+    self.fbb_.push_slot_always::<flatbuffers::WIPOffset<_>>(Player::VT_NAME, name);
+  }
+  #[inline]
+  pub fn add_pos(&mut self, pos: &Vector2) {
+    self.fbb_.push_slot_always::<&Vector2>(Player::VT_POS, pos);
+  }
+  #[inline]
+  pub fn add_color(&mut self, color: Color) {
+    self.fbb_.push_slot::<Color>(Player::VT_COLOR, color, Color::Red);
+  }
+  #[inline]
+  pub fn add_size(&mut self, size: f32) {
+    self.fbb_.push_slot::<f32>(Player::VT_SIZE, size, 0.0);
+  }
+  #[inline]
+  pub fn add_qpos(&mut self, qpos: &QuantizedPosition) {
+    self.fbb_.push_slot_always::<&QuantizedPosition>(Player::VT_QPOS, qpos);
+  }
+  #[inline]
+  pub fn add_is_it(&mut self, is_it: bool) {
+    self.fbb_.push_slot::<bool>(Player::VT_IS_IT, is_it, false);
+  }
+  #[inline]
+  pub fn new(_fbb: &'b mut flatbuffers::FlatBufferBuilder<'a, A>) -> PlayerBuilder<'a, 'b, A> {
+    let start = _fbb.start_table();
+    PlayerBuilder {
+      fbb_: _fbb,
+      start_: start,
+    }
+  }
+  #[inline]
+  pub fn finish(self) -> flatbuffers::WIPOffset<Player<'a>> {
+    let o = self.fbb_.end_table(self.start_);
+    flatbuffers::WIPOffset::new(o.value())
+  }
 }
 
-pub enum ClientPlayerOffset {}
+impl core::fmt::Debug for Player<'_> {
+  fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+    let mut ds = f.debug_struct("Player");
+      ds.field("id", &self.id());
+      ds.field("name", &self.name());
+      ds.field("pos", &self.pos());
+      ds.field("color", &self.color());
+      ds.field("size", &self.size());
+      ds.field("qpos", &self.qpos());
+      ds.field("is_it", &self.is_it());
+      ds.finish()
+  }
+}
+pub enum RaceEventOffset {}
 #[derive(Copy, Clone, PartialEq)]
 
-pub struct ClientPlayer<'a> {
+pub struct RaceEvent<'a> {
   pub _tab: flatbuffers::Table<'a>,
 }
 
-impl<'a> flatbuffers::Follow<'a> for ClientPlayer<'a> {
-  type Inner = ClientPlayer<'a>;
+impl<'a> flatbuffers::Follow<'a> for RaceEvent<'a> {
+  type Inner = RaceEvent<'a>;
   #[inline]
   unsafe fn follow(buf: &'a [u8], loc: usize) -> Self::Inner {
     Self { _tab: flatbuffers::Table::new(buf, loc) }
   }
 }
 
-impl<'a> ClientPlayer<'a> {
+impl<'a> RaceEvent<'a> {
   pub const VT_ID: flatbuffers::VOffsetT = 4;
-  pub const VT_NAME: flatbuffers::VOffsetT = 6;
-  pub const VT_POS: flatbuffers::VOffsetT = 8;
-  pub const VT_VEL: flatbuffers::VOffsetT = 10;
-  pub const VT_COLOR: flatbuffers::VOffsetT = 12;
-  pub const VT_GROUNDED: flatbuffers::VOffsetT = 14;
-  pub const VT_JUMP_TIMER: flatbuffers::VOffsetT = 16;
-  pub const VT_SIZE: flatbuffers::VOffsetT = 18;
+  pub const VT_ROUND: flatbuffers::VOffsetT = 6;
+  pub const VT_KIND: flatbuffers::VOffsetT = 8;
+  pub const VT_PLAYER_ID: flatbuffers::VOffsetT = 10;
+  pub const VT_PLACE: flatbuffers::VOffsetT = 12;
+  pub const VT_SECONDS: flatbuffers::VOffsetT = 14;
 
   #[inline]
   pub unsafe fn init_from_table(table: flatbuffers::Table<'a>) -> Self {
-    ClientPlayer { _tab: table }
+    RaceEvent { _tab: table }
   }
   #[allow(unused_mut)]
   pub fn create<'bldr: 'args, 'args: 'mut_bldr, 'mut_bldr, A: flatbuffers::Allocator + 'bldr>(
     _fbb: &'mut_bldr mut flatbuffers::FlatBufferBuilder<'bldr, A>,
-    args: &'args ClientPlayerArgs<'args>
-  ) -> flatbuffers::WIPOffset<ClientPlayer<'bldr>> {
-    let mut builder = ClientPlayerBuilder::new(_fbb);
-    builder.add_size(args.size);
-    builder.add_jump_timer(args.jump_timer);
-    if let Some(x) = args.vel { builder.add_vel(x); }
-    if let Some(x) = args.pos { builder.add_pos(x); }
-    if let Some(x) = args.name { builder.add_name(x); }
+    args: &'args RaceEventArgs
+  ) -> flatbuffers::WIPOffset<RaceEvent<'bldr>> {
+    let mut builder = RaceEventBuilder::new(_fbb);
+    builder.add_seconds(args.seconds);
+    builder.add_place(args.place);
+    builder.add_player_id(args.player_id);
+    builder.add_round(args.round);
     builder.add_id(args.id);
-    builder.add_grounded(args.grounded);
-    builder.add_color(args.color);
+    builder.add_kind(args.kind);
     builder.finish()
   }
 
@@ -298,60 +1219,46 @@ impl<'a> ClientPlayer<'a> {
     // Safety:
     // Created from valid Table for this object
     // which contains a valid value in this slot
-    unsafe { self._tab.get::<u32>(ClientPlayer::VT_ID, Some(0)).unwrap()}
-  }
-  #[inline]
-  pub fn name(&self) -> Option<&'a str> {
-    // Safety:
-    // Created from valid Table for this object
-    // which contains a valid value in this slot
-    unsafe { self._tab.get::<flatbuffers::ForwardsUOffset<&str>>(ClientPlayer::VT_NAME, None)}
-  }
-  #[inline]
-  pub fn pos(&self) -> Option<&'a Vector2> {
-    // Safety:
-    // Created from valid Table for this object
-    // which contains a valid value in this slot
-    unsafe { self._tab.get::<Vector2>(ClientPlayer::VT_POS, None)}
+    unsafe { self._tab.get::<u32>(RaceEvent::VT_ID, Some(0)).unwrap()}
   }
   #[inline]
-  pub fn vel(&self) -> Option<&'a Vector2> {
+  pub fn round(&self) -> u32 {
     // Safety:
     // Created from valid Table for this object
     // which contains a valid value in this slot
-    unsafe { self._tab.get::<Vector2>(ClientPlayer::VT_VEL, None)}
+    unsafe { self._tab.get::<u32>(RaceEvent::VT_ROUND, Some(0)).unwrap()}
   }
   #[inline]
-  pub fn color(&self) -> Color {
+  pub fn kind(&self) -> RaceEventKind {
     // Safety:
     // Created from valid Table for this object
     // which contains a valid value in this slot
-    unsafe { self._tab.get::<Color>(ClientPlayer::VT_COLOR, Some(Color::Red)).unwrap()}
+    unsafe { self._tab.get::<RaceEventKind>(RaceEvent::VT_KIND, Some(RaceEventKind::Countdown)).unwrap()}
   }
   #[inline]
-  pub fn grounded(&self) -> bool {
+  pub fn player_id(&self) -> u32 {
     // Safety:
     // Created from valid Table for this object
     // which contains a valid value in this slot
-    unsafe { self._tab.get::<bool>(ClientPlayer::VT_GROUNDED, Some(false)).unwrap()}
+    unsafe { self._tab.get::<u32>(RaceEvent::VT_PLAYER_ID, Some(0)).unwrap()}
   }
   #[inline]
-  pub fn jump_timer(&self) -> f32 {
+  pub fn place(&self) -> u32 {
     // Safety:
     // Created from valid Table for this object
     // which contains a valid value in this slot
-    unsafe { self._tab.get::<f32>(ClientPlayer::VT_JUMP_TIMER, Some(0.0)).unwrap()}
+    unsafe { self._tab.get::<u32>(RaceEvent::VT_PLACE, Some(0)).unwrap()}
   }
   #[inline]
-  pub fn size(&self) -> f32 {
+  pub fn seconds(&self) -> f32 {
     // Safety:
     // Created from valid Table for this object
     // which contains a valid value in this slot
-    unsafe { self._tab.get::<f32>(ClientPlayer::VT_SIZE, Some(0.0)).unwrap()}
+    unsafe { self._tab.get::<f32>(RaceEvent::VT_SECONDS, Some(0.0)).unwrap()}
   }
 }
 
-impl flatbuffers::Verifiable for ClientPlayer<'_> {
+impl flatbuffers::Verifiable for RaceEvent<'_> {
   #[inline]
   fn run_verifier(
     v: &mut flatbuffers::Verifier, pos: usize
@@ -359,271 +1266,272 @@ impl flatbuffers::Verifiable for ClientPlayer<'_> {
     use self::flatbuffers::Verifiable;
     v.visit_table(pos)?
      .visit_field::<u32>("id", Self::VT_ID, false)?
-     .visit_field::<flatbuffers::ForwardsUOffset<&str>>("name", Self::VT_NAME, false)?
-     .visit_field::<Vector2>("pos", Self::VT_POS, false)?
-     .visit_field::<Vector2>("vel", Self::VT_VEL, false)?
-     .visit_field::<Color>("color", Self::VT_COLOR, false)?
-     .visit_field::<bool>("grounded", Self::VT_GROUNDED, false)?
-     .visit_field::<f32>("jump_timer", Self::VT_JUMP_TIMER, false)?
-     .visit_field::<f32>("size", Self::VT_SIZE, false)?
+     .visit_field::<u32>("round", Self::VT_ROUND, false)?
+     .visit_field::<RaceEventKind>("kind", Self::VT_KIND, false)?
+     .visit_field::<u32>("player_id", Self::VT_PLAYER_ID, false)?
+     .visit_field::<u32>("place", Self::VT_PLACE, false)?
+     .visit_field::<f32>("seconds", Self::VT_SECONDS, false)?
      .finish();
     Ok(())
   }
 }
-pub struct ClientPlayerArgs<'a> {
+pub struct RaceEventArgs {
     pub id: u32,
-    pub name: Option<flatbuffers::WIPOffset<&'a str>>,
-    pub pos: Option<&'a Vector2>,
-    pub vel: Option<&'a Vector2>,
-    pub color: Color,
-    pub grounded: bool,
-    pub jump_timer: f32,
-    pub size: f32,
+    pub round: u32,
+    pub kind: RaceEventKind,
+    pub player_id: u32,
+    pub place: u32,
+    pub seconds: f32,
 }
-impl<'a> Default for ClientPlayerArgs<'a> {
+impl<'a> Default for RaceEventArgs {
   #[inline]
   fn default() -> Self {
-    ClientPlayerArgs {
+    RaceEventArgs {
       id: 0,
-      name: None,
-      pos: None,
-      vel: None,
-      color: Color::Red,
-      grounded: false,
-      jump_timer: 0.0,
-      size: 0.0,
+      round: 0,
+      kind: RaceEventKind::Countdown,
+      player_id: 0,
+      place: 0,
+      seconds: 0.0,
     }
   }
 }
 
-pub struct ClientPlayerBuilder<'a: 'b, 'b, A: flatbuffers::Allocator + 'a> {
+pub struct RaceEventBuilder<'a: 'b, 'b, A: flatbuffers::Allocator + 'a> {
   fbb_: &'b mut flatbuffers::FlatBufferBuilder<'a, A>,
   start_: flatbuffers::WIPOffset<flatbuffers::TableUnfinishedWIPOffset>,
 }
-impl<'a: 'b, 'b, A: flatbuffers::Allocator + 'a> ClientPlayerBuilder<'a, 'b, A> {
+impl<'a: 'b, 'b, A: flatbuffers::Allocator + 'a> RaceEventBuilder<'a, 'b, A> {
   #[inline]
   pub fn add_id(&mut self, id: u32) {
-    self.fbb_.push_slot::<u32>(ClientPlayer::VT_ID, id, 0);
-  }
-  #[inline]
-  pub fn add_name(&mut self, name: flatbuffers::WIPOffset<&'b  str>) {
-    self.fbb_.push_slot_always::<flatbuffers::WIPOffset<_>>(ClientPlayer::VT_NAME, name);
-  }
-  #[inline]
-  pub fn add_pos(&mut self, pos: &Vector2) {
-    self.fbb_.push_slot_always::<&Vector2>(ClientPlayer::VT_POS, pos);
+    self.fbb_.push_slot::<u32>(RaceEvent::VT_ID, id, 0);
   }
   #[inline]
-  pub fn add_vel(&mut self, vel: &Vector2) {
-    self.fbb_.push_slot_always::<&Vector2>(ClientPlayer::VT_VEL, vel);
+  pub fn add_round(&mut self, round: u32) {
+    self.fbb_.push_slot::<u32>(RaceEvent::VT_ROUND, round, 0);
   }
   #[inline]
-  pub fn add_color(&mut self, color: Color) {
-    self.fbb_.push_slot::<Color>(ClientPlayer::VT_COLOR, color, Color::Red);
+  pub fn add_kind(&mut self, kind: RaceEventKind) {
+    self.fbb_.push_slot::<RaceEventKind>(RaceEvent::VT_KIND, kind, RaceEventKind::Countdown);
   }
   #[inline]
-  pub fn add_grounded(&mut self, grounded: bool) {
-    self.fbb_.push_slot::<bool>(ClientPlayer::VT_GROUNDED, grounded, false);
+  pub fn add_player_id(&mut self, player_id: u32) {
+    self.fbb_.push_slot::<u32>(RaceEvent::VT_PLAYER_ID, player_id, 0);
   }
   #[inline]
-  pub fn add_jump_timer(&mut self, jump_timer: f32) {
-    self.fbb_.push_slot::<f32>(ClientPlayer::VT_JUMP_TIMER, jump_timer, 0.0);
+  pub fn add_place(&mut self, place: u32) {
+    self.fbb_.push_slot::<u32>(RaceEvent::VT_PLACE, place, 0);
   }
   #[inline]
-  pub fn add_size(&mut self, size: f32) {
-    self.fbb_.push_slot::<f32>(ClientPlayer::VT_SIZE, size, 0.0);
+  pub fn add_seconds(&mut self, seconds: f32) {
+    self.fbb_.push_slot::<f32>(RaceEvent::VT_SECONDS, seconds, 0.0);
   }
   #[inline]
-  pub fn new(_fbb: &'b mut flatbuffers::FlatBufferBuilder<'a, A>) -> ClientPlayerBuilder<'a, 'b, A> {
+  pub fn new(_fbb: &'b mut flatbuffers::FlatBufferBuilder<'a, A>) -> RaceEventBuilder<'a, 'b, A> {
     let start = _fbb.start_table();
-    ClientPlayerBuilder {
+    RaceEventBuilder {
       fbb_: _fbb,
       start_: start,
     }
   }
   #[inline]
-  pub fn finish(self) -> flatbuffers::WIPOffset<ClientPlayer<'a>> {
+  pub fn finish(self) -> flatbuffers::WIPOffset<RaceEvent<'a>> {
     let o = self.fbb_.end_table(self.start_);
     flatbuffers::WIPOffset::new(o.value())
   }
 }
 
-impl core::fmt::Debug for ClientPlayer<'_> {
+impl core::fmt::Debug for RaceEvent<'_> {
   fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
-    let mut ds = f.debug_struct("ClientPlayer");
+    let mut ds = f.debug_struct("RaceEvent");
       ds.field("id", &self.id());
-      ds.field("name", &self.name());
-      ds.field("pos", &self.pos());
-      ds.field("vel", &self.vel());
-      ds.field("color", &self.color());
-      ds.field("grounded", &self.grounded());
-      ds.field("jump_timer", &self.jump_timer());
-      ds.field("size", &self.size());
+      ds.field("round", &self.round());
+      ds.field("kind", &self.kind());
+      ds.field("player_id", &self.player_id());
+      ds.field("place", &self.place());
+      ds.field("seconds", &self.seconds());
       ds.finish()
   }
 }
-pub enum PlayerOffset {}
+pub enum PhysicsParamsOffset {}
 #[derive(Copy, Clone, PartialEq)]
 
-pub struct Player<'a> {
+pub struct PhysicsParams<'a> {
   pub _tab: flatbuffers::Table<'a>,
 }
 
-impl<'a> flatbuffers::Follow<'a> for Player<'a> {
-  type Inner = Player<'a>;
+impl<'a> flatbuffers::Follow<'a> for PhysicsParams<'a> {
+  type Inner = PhysicsParams<'a>;
   #[inline]
   unsafe fn follow(buf: &'a [u8], loc: usize) -> Self::Inner {
     Self { _tab: flatbuffers::Table::new(buf, loc) }
   }
 }
 
-impl<'a> Player<'a> {
-  pub const VT_ID: flatbuffers::VOffsetT = 4;
-  pub const VT_NAME: flatbuffers::VOffsetT = 6;
-  pub const VT_POS: flatbuffers::VOffsetT = 8;
-  pub const VT_COLOR: flatbuffers::VOffsetT = 10;
-  pub const VT_SIZE: flatbuffers::VOffsetT = 12;
+impl<'a> PhysicsParams<'a> {
+  pub const VT_GRAVITY: flatbuffers::VOffsetT = 4;
+  pub const VT_JUMP_FORCE: flatbuffers::VOffsetT = 6;
+  pub const VT_PLAYER_ACCELERATION: flatbuffers::VOffsetT = 8;
+  pub const VT_GROUND_FRICTION: flatbuffers::VOffsetT = 10;
+  pub const VT_AIR_FRICTION: flatbuffers::VOffsetT = 12;
+  pub const VT_JUMP_CD: flatbuffers::VOffsetT = 14;
 
   #[inline]
   pub unsafe fn init_from_table(table: flatbuffers::Table<'a>) -> Self {
-    Player { _tab: table }
+    PhysicsParams { _tab: table }
   }
   #[allow(unused_mut)]
   pub fn create<'bldr: 'args, 'args: 'mut_bldr, 'mut_bldr, A: flatbuffers::Allocator + 'bldr>(
     _fbb: &'mut_bldr mut flatbuffers::FlatBufferBuilder<'bldr, A>,
-    args: &'args PlayerArgs<'args>
-  ) -> flatbuffers::WIPOffset<Player<'bldr>> {
-    let mut builder = PlayerBuilder::new(_fbb);
-    builder.add_size(args.size);
-    if let Some(x) = args.pos { builder.add_pos(x); }
-    if let Some(x) = args.name { builder.add_name(x); }
-    builder.add_id(args.id);
-    builder.add_color(args.color);
+    args: &'args PhysicsParamsArgs
+  ) -> flatbuffers::WIPOffset<PhysicsParams<'bldr>> {
+    let mut builder = PhysicsParamsBuilder::new(_fbb);
+    builder.add_jump_cd(args.jump_cd);
+    builder.add_air_friction(args.air_friction);
+    builder.add_ground_friction(args.ground_friction);
+    builder.add_player_acceleration(args.player_acceleration);
+    builder.add_jump_force(args.jump_force);
+    builder.add_gravity(args.gravity);
     builder.finish()
   }
 
 
   #[inline]
-  pub fn id(&self) -> u32 {
+  pub fn gravity(&self) -> f32 {
     // Safety:
     // Created from valid Table for this object
     // which contains a valid value in this slot
-    unsafe { self._tab.get::<u32>(Player::VT_ID, Some(0)).unwrap()}
+    unsafe { self._tab.get::<f32>(PhysicsParams::VT_GRAVITY, Some(0.0)).unwrap()}
   }
   #[inline]
-  pub fn name(&self) -> Option<&'a str> {
+  pub fn jump_force(&self) -> f32 {
     // Safety:
     // Created from valid Table for this object
     // which contains a valid value in this slot
-    unsafe { self._tab.get::<flatbuffers::ForwardsUOffset<&str>>(Player::VT_NAME, None)}
+    unsafe { self._tab.get::<f32>(PhysicsParams::VT_JUMP_FORCE, Some(0.0)).unwrap()}
   }
   #[inline]
-  pub fn pos(&self) -> Option<&'a Vector2> {
+  pub fn player_acceleration(&self) -> f32 {
     // Safety:
     // Created from valid Table for this object
     // which contains a valid value in this slot
-    unsafe { self._tab.get::<Vector2>(Player::VT_POS, None)}
+    unsafe { self._tab.get::<f32>(PhysicsParams::VT_PLAYER_ACCELERATION, Some(0.0)).unwrap()}
   }
   #[inline]
-  pub fn color(&self) -> Color {
+  pub fn ground_friction(&self) -> f32 {
     // Safety:
     // Created from valid Table for this object
     // which contains a valid value in this slot
-    unsafe { self._tab.get::<Color>(Player::VT_COLOR, Some(Color::Red)).unwrap()}
+    unsafe { self._tab.get::<f32>(PhysicsParams::VT_GROUND_FRICTION, Some(0.0)).unwrap()}
   }
   #[inline]
-  pub fn size(&self) -> f32 {
+  pub fn air_friction(&self) -> f32 {
     // Safety:
     // Created from valid Table for this object
     // which contains a valid value in this slot
-    unsafe { self._tab.get::<f32>(Player::VT_SIZE, Some(0.0)).unwrap()}
+    unsafe { self._tab.get::<f32>(PhysicsParams::VT_AIR_FRICTION, Some(0.0)).unwrap()}
+  }
+  #[inline]
+  pub fn jump_cd(&self) -> f32 {
+    // Safety:
+    // Created from valid Table for this object
+    // which contains a valid value in this slot
+    unsafe { self._tab.get::<f32>(PhysicsParams::VT_JUMP_CD, Some(0.0)).unwrap()}
   }
 }
 
-impl flatbuffers::Verifiable for Player<'_> {
+impl flatbuffers::Verifiable for PhysicsParams<'_> {
   #[inline]
   fn run_verifier(
     v: &mut flatbuffers::Verifier, pos: usize
   ) -> Result<(), flatbuffers::InvalidFlatbuffer> {
     use self::flatbuffers::Verifiable;
     v.visit_table(pos)?
-     .visit_field::<u32>("id", Self::VT_ID, false)?
-     .visit_field::<flatbuffers::ForwardsUOffset<&str>>("name", Self::VT_NAME, false)?
-     .visit_field::<Vector2>("pos", Self::VT_POS, false)?
-     .visit_field::<Color>("color", Self::VT_COLOR, false)?
-     .visit_field::<f32>("size", Self::VT_SIZE, false)?
+     .visit_field::<f32>("gravity", Self::VT_GRAVITY, false)?
+     .visit_field::<f32>("jump_force", Self::VT_JUMP_FORCE, false)?
+     .visit_field::<f32>("player_acceleration", Self::VT_PLAYER_ACCELERATION, false)?
+     .visit_field::<f32>("ground_friction", Self::VT_GROUND_FRICTION, false)?
+     .visit_field::<f32>("air_friction", Self::VT_AIR_FRICTION, false)?
+     .visit_field::<f32>("jump_cd", Self::VT_JUMP_CD, false)?
      .finish();
     Ok(())
   }
 }
-pub struct PlayerArgs<'a> {
-    pub id: u32,
-    pub name: Option<flatbuffers::WIPOffset<&'a str>>,
-    pub pos: Option<&'a Vector2>,
-    pub color: Color,
-    pub size: f32,
+pub struct PhysicsParamsArgs {
+    pub gravity: f32,
+    pub jump_force: f32,
+    pub player_acceleration: f32,
+    pub ground_friction: f32,
+    pub air_friction: f32,
+    pub jump_cd: f32,
 }
-impl<'a> Default for PlayerArgs<'a> {
+impl<'a> Default for PhysicsParamsArgs {
   #[inline]
   fn default() -> Self {
-    PlayerArgs {
-      id: 0,
-      name: None,
-      pos: None,
-      color: Color::Red,
-      size: 0.0,
+    PhysicsParamsArgs {
+      gravity: 0.0,
+      jump_force: 0.0,
+      player_acceleration: 0.0,
+      ground_friction: 0.0,
+      air_friction: 0.0,
+      jump_cd: 0.0,
     }
   }
 }
 
-pub struct PlayerBuilder<'a: 'b, 'b, A: flatbuffers::Allocator + 'a> {
+pub struct PhysicsParamsBuilder<'a: 'b, 'b, A: flatbuffers::Allocator + 'a> {
   fbb_: &'b mut flatbuffers::FlatBufferBuilder<'a, A>,
   start_: flatbuffers::WIPOffset<flatbuffers::TableUnfinishedWIPOffset>,
 }
-impl<'a: 'b, 'b, A: flatbuffers::Allocator + 'a> PlayerBuilder<'a, 'b, A> {
+impl<'a: 'b, 'b, A: flatbuffers::Allocator + 'a> PhysicsParamsBuilder<'a, 'b, A> {
   #[inline]
-  pub fn add_id(&mut self, id: u32) {
-    self.fbb_.push_slot::<u32>(Player::VT_ID, id, 0);
+  pub fn add_gravity(&mut self, gravity: f32) {
+    self.fbb_.push_slot::<f32>(PhysicsParams::VT_GRAVITY, gravity, 0.0);
   }
   #[inline]
-  pub fn add_name(&mut self, name: flatbuffers::WIPOffset<&'b  str>) {
-    self.fbb_.push_slot_always::<flatbuffers::WIPOffset<_>>(Player::VT_NAME, name);
+  pub fn add_jump_force(&mut self, jump_force: f32) {
+    self.fbb_.push_slot::<f32>(PhysicsParams::VT_JUMP_FORCE, jump_force, 0.0);
   }
   #[inline]
-  pub fn add_pos(&mut self, pos: &Vector2) {
-    self.fbb_.push_slot_always::<&Vector2>(Player::VT_POS, pos);
+  pub fn add_player_acceleration(&mut self, player_acceleration: f32) {
+    self.fbb_.push_slot::<f32>(PhysicsParams::VT_PLAYER_ACCELERATION, player_acceleration, 0.0);
   }
   #[inline]
-  pub fn add_color(&mut self, color: Color) {
-    self.fbb_.push_slot::<Color>(Player::VT_COLOR, color, Color::Red);
+  pub fn add_ground_friction(&mut self, ground_friction: f32) {
+    self.fbb_.push_slot::<f32>(PhysicsParams::VT_GROUND_FRICTION, ground_friction, 0.0);
   }
   #[inline]
-  pub fn add_size(&mut self, size: f32) {
-    self.fbb_.push_slot::<f32>(Player::VT_SIZE, size, 0.0);
+  pub fn add_air_friction(&mut self, air_friction: f32) {
+    self.fbb_.push_slot::<f32>(PhysicsParams::VT_AIR_FRICTION, air_friction, 0.0);
   }
   #[inline]
-  pub fn new(_fbb: &'b mut flatbuffers::FlatBufferBuilder<'a, A>) -> PlayerBuilder<'a, 'b, A> {
+  pub fn add_jump_cd(&mut self, jump_cd: f32) {
+    self.fbb_.push_slot::<f32>(PhysicsParams::VT_JUMP_CD, jump_cd, 0.0);
+  }
+  #[inline]
+  pub fn new(_fbb: &'b mut flatbuffers::FlatBufferBuilder<'a, A>) -> PhysicsParamsBuilder<'a, 'b, A> {
     let start = _fbb.start_table();
-    PlayerBuilder {
+    PhysicsParamsBuilder {
       fbb_: _fbb,
       start_: start,
     }
   }
   #[inline]
-  pub fn finish(self) -> flatbuffers::WIPOffset<Player<'a>> {
+  pub fn finish(self) -> flatbuffers::WIPOffset<PhysicsParams<'a>> {
     let o = self.fbb_.end_table(self.start_);
     flatbuffers::WIPOffset::new(o.value())
   }
 }
 
-impl core::fmt::Debug for Player<'_> {
+impl core::fmt::Debug for PhysicsParams<'_> {
   fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
-    let mut ds = f.debug_struct("Player");
-      ds.field("id", &self.id());
-      ds.field("name", &self.name());
-      ds.field("pos", &self.pos());
-      ds.field("color", &self.color());
-      ds.field("size", &self.size());
+    let mut ds = f.debug_struct("PhysicsParams");
+      ds.field("gravity", &self.gravity());
+      ds.field("jump_force", &self.jump_force());
+      ds.field("player_acceleration", &self.player_acceleration());
+      ds.field("ground_friction", &self.ground_friction());
+      ds.field("air_friction", &self.air_friction());
+      ds.field("jump_cd", &self.jump_cd());
       ds.finish()
   }
 }
@@ -647,6 +1555,14 @@ impl<'a> GameState<'a> {
   pub const VT_PLAYERS: flatbuffers::VOffsetT = 6;
   pub const VT_SEQUENCE: flatbuffers::VOffsetT = 8;
   pub const VT_SERVER_TIMESTAMP: flatbuffers::VOffsetT = 10;
+  pub const VT_REMOVED_PLAYERS: flatbuffers::VOffsetT = 12;
+  pub const VT_TICK: flatbuffers::VOffsetT = 14;
+  pub const VT_CLIENT_PLAYER_CHECKSUM: flatbuffers::VOffsetT = 16;
+  pub const VT_PHYSICS_STEP: flatbuffers::VOffsetT = 18;
+  pub const VT_RACE_EVENTS: flatbuffers::VOffsetT = 20;
+  pub const VT_PHYSICS_PARAMS: flatbuffers::VOffsetT = 22;
+  pub const VT_ACK_HOLD_MICROS: flatbuffers::VOffsetT = 24;
+  pub const VT_ACK_STEPS: flatbuffers::VOffsetT = 26;
 
   #[inline]
   pub unsafe fn init_from_table(table: flatbuffers::Table<'a>) -> Self {
@@ -658,7 +1574,15 @@ impl<'a> GameState<'a> {
     args: &'args GameStateArgs<'args>
   ) -> flatbuffers::WIPOffset<GameState<'bldr>> {
     let mut builder = GameStateBuilder::new(_fbb);
+    builder.add_ack_hold_micros(args.ack_hold_micros);
     builder.add_server_timestamp(args.server_timestamp);
+    builder.add_ack_steps(args.ack_steps);
+    if let Some(x) = args.physics_params { builder.add_physics_params(x); }
+    if let Some(x) = args.race_events { builder.add_race_events(x); }
+    builder.add_physics_step(args.physics_step);
+    builder.add_client_player_checksum(args.client_player_checksum);
+    builder.add_tick(args.tick);
+    if let Some(x) = args.removed_players { builder.add_removed_players(x); }
     builder.add_sequence(args.sequence);
     if let Some(x) = args.players { builder.add_players(x); }
     if let Some(x) = args.client_player { builder.add_client_player(x); }
@@ -694,6 +1618,62 @@ impl<'a> GameState<'a> {
     // which contains a valid value in this slot
     unsafe { self._tab.get::<u64>(GameState::VT_SERVER_TIMESTAMP, Some(0)).unwrap()}
   }
+  #[inline]
+  pub fn removed_players(&self) -> Option<flatbuffers::Vector<'a, u32>> {
+    // Safety:
+    // Created from valid Table for this object
+    // which contains a valid value in this slot
+    unsafe { self._tab.get::<flatbuffers::ForwardsUOffset<flatbuffers::Vector<'a, u32>>>(GameState::VT_REMOVED_PLAYERS, None)}
+  }
+  #[inline]
+  pub fn tick(&self) -> u32 {
+    // Safety:
+    // Created from valid Table for this object
+    // which contains a valid value in this slot
+    unsafe { self._tab.get::<u32>(GameState::VT_TICK, Some(0)).unwrap()}
+  }
+  #[inline]
+  pub fn client_player_checksum(&self) -> u32 {
+    // Safety:
+    // Created from valid Table for this object
+    // which contains a valid value in this slot
+    unsafe { self._tab.get::<u32>(GameState::VT_CLIENT_PLAYER_CHECKSUM, Some(0)).unwrap()}
+  }
+  #[inline]
+  pub fn physics_step(&self) -> u32 {
+    // Safety:
+    // Created from valid Table for this object
+    // which contains a valid value in this slot
+    unsafe { self._tab.get::<u32>(GameState::VT_PHYSICS_STEP, Some(0)).unwrap()}
+  }
+  #[inline]
+  pub fn race_events(&self) -> Option<flatbuffers::Vector<'a, flatbuffers::ForwardsUOffset<RaceEvent<'a>>>> {
+    // Safety:
+    // Created from valid Table for this object
+    // which contains a valid value in this slot
+    unsafe { self._tab.get::<flatbuffers::ForwardsUOffset<flatbuffers::Vector<'a, flatbuffers::ForwardsUOffset<RaceEvent>>>>(GameState::VT_RACE_EVENTS, None)}
+  }
+  #[inline]
+  pub fn physics_params(&self) -> Option<PhysicsParams<'a>> {
+    // Safety:
+    // Created from valid Table for this object
+    // which contains a valid value in this slot
+    unsafe { self._tab.get::<flatbuffers::ForwardsUOffset<PhysicsParams>>(GameState::VT_PHYSICS_PARAMS, None)}
+  }
+  #[inline]
+  pub fn ack_hold_micros(&self) -> u64 {
+    // Safety:
+    // Created from valid Table for this object
+    // which contains a valid value in this slot
+    unsafe { self._tab.get::<u64>(GameState::VT_ACK_HOLD_MICROS, Some(0)).unwrap()}
+  }
+  #[inline]
+  pub fn ack_steps(&self) -> u32 {
+    // Safety:
+    // Created from valid Table for this object
+    // which contains a valid value in this slot
+    unsafe { self._tab.get::<u32>(GameState::VT_ACK_STEPS, Some(0)).unwrap()}
+  }
 }
 
 impl flatbuffers::Verifiable for GameState<'_> {
@@ -707,6 +1687,14 @@ impl flatbuffers::Verifiable for GameState<'_> {
      .visit_field::<flatbuffers::ForwardsUOffset<flatbuffers::Vector<'_, flatbuffers::ForwardsUOffset<Player>>>>("players", Self::VT_PLAYERS, false)?
      .visit_field::<u32>("sequence", Self::VT_SEQUENCE, false)?
      .visit_field::<u64>("server_timestamp", Self::VT_SERVER_TIMESTAMP, false)?
+     .visit_field::<flatbuffers::ForwardsUOffset<flatbuffers::Vector<'_, u32>>>("removed_players", Self::VT_REMOVED_PLAYERS, false)?
+     .visit_field::<u32>("tick", Self::VT_TICK, false)?
+     .visit_field::<u32>("client_player_checksum", Self::VT_CLIENT_PLAYER_CHECKSUM, false)?
+     .visit_field::<u32>("physics_step", Self::VT_PHYSICS_STEP, false)?
+     .visit_field::<flatbuffers::ForwardsUOffset<flatbuffers::Vector<'_, flatbuffers::ForwardsUOffset<RaceEvent>>>>("race_events", Self::VT_RACE_EVENTS, false)?
+     .visit_field::<flatbuffers::ForwardsUOffset<PhysicsParams>>("physics_params", Self::VT_PHYSICS_PARAMS, false)?
+     .visit_field::<u64>("ack_hold_micros", Self::VT_ACK_HOLD_MICROS, false)?
+     .visit_field::<u32>("ack_steps", Self::VT_ACK_STEPS, false)?
      .finish();
     Ok(())
   }
@@ -716,6 +1704,14 @@ pub struct GameStateArgs<'a> {
     pub players: Option<flatbuffers::WIPOffset<flatbuffers::Vector<'a, flatbuffers::ForwardsUOffset<Player<'a>>>>>,
     pub sequence: u32,
     pub server_timestamp: u64,
+    pub removed_players: Option<flatbuffers::WIPOffset<flatbuffers::Vector<'a, u32>>>,
+    pub tick: u32,
+    pub client_player_checksum: u32,
+    pub physics_step: u32,
+    pub race_events: Option<flatbuffers::WIPOffset<flatbuffers::Vector<'a, flatbuffers::ForwardsUOffset<RaceEvent<'a>>>>>,
+    pub physics_params: Option<flatbuffers::WIPOffset<PhysicsParams<'a>>>,
+    pub ack_hold_micros: u64,
+    pub ack_steps: u32,
 }
 impl<'a> Default for GameStateArgs<'a> {
   #[inline]
@@ -725,6 +1721,14 @@ impl<'a> Default for GameStateArgs<'a> {
       players: None,
       sequence: 0,
       server_timestamp: 0,
+      removed_players: None,
+      tick: 0,
+      client_player_checksum: 0,
+      physics_step: 0,
+      race_events: None,
+      physics_params: None,
+      ack_hold_micros: 0,
+      ack_steps: 0,
     }
   }
 }
@@ -751,6 +1755,38 @@ impl<'a: 'b, 'b, A: flatbuffers::Allocator + 'a> GameStateBuilder<'a, 'b, A> {
     self.fbb_.push_slot::<u64>(GameState::VT_SERVER_TIMESTAMP, server_timestamp, 0);
   }
   #[inline]
+  pub fn add_removed_players(&mut self, removed_players: flatbuffers::WIPOffset<flatbuffers::Vector<'b , u32>>) {
+    self.fbb_.push_slot_always::<flatbuffers::WIPOffset<_>>(GameState::VT_REMOVED_PLAYERS, removed_players);
+  }
+  #[inline]
+  pub fn add_tick(&mut self, tick: u32) {
+    self.fbb_.push_slot::<u32>(GameState::VT_TICK, tick, 0);
+  }
+  #[inline]
+  pub fn add_client_player_checksum(&mut self, client_player_checksum: u32) {
+    self.fbb_.push_slot::<u32>(GameState::VT_CLIENT_PLAYER_CHECKSUM, client_player_checksum, 0);
+  }
+  #[inline]
+  pub fn add_physics_step(&mut self, physics_step: u32) {
+    self.fbb_.push_slot::<u32>(GameState::VT_PHYSICS_STEP, physics_step, 0);
+  }
+  #[inline]
+  pub fn add_race_events(&mut self, race_events: flatbuffers::WIPOffset<flatbuffers::Vector<'b , flatbuffers::ForwardsUOffset<RaceEvent<'b >>>>) {
+    self.fbb_.push_slot_always::<flatbuffers::WIPOffset<_>>(GameState::VT_RACE_EVENTS, race_events);
+  }
+  #[inline]
+  pub fn add_physics_params(&mut self, physics_params: flatbuffers::WIPOffset<PhysicsParams<'b >>) {
+    self.fbb_.push_slot_always::<flatbuffers::WIPOffset<PhysicsParams>>(GameState::VT_PHYSICS_PARAMS, physics_params);
+  }
+  #[inline]
+  pub fn add_ack_hold_micros(&mut self, ack_hold_micros: u64) {
+    self.fbb_.push_slot::<u64>(GameState::VT_ACK_HOLD_MICROS, ack_hold_micros, 0);
+  }
+  #[inline]
+  pub fn add_ack_steps(&mut self, ack_steps: u32) {
+    self.fbb_.push_slot::<u32>(GameState::VT_ACK_STEPS, ack_steps, 0);
+  }
+  #[inline]
   pub fn new(_fbb: &'b mut flatbuffers::FlatBufferBuilder<'a, A>) -> GameStateBuilder<'a, 'b, A> {
     let start = _fbb.start_table();
     GameStateBuilder {
@@ -772,6 +1808,14 @@ impl core::fmt::Debug for GameState<'_> {
       ds.field("players", &self.players());
       ds.field("sequence", &self.sequence());
       ds.field("server_timestamp", &self.server_timestamp());
+      ds.field("removed_players", &self.removed_players());
+      ds.field("tick", &self.tick());
+      ds.field("client_player_checksum", &self.client_player_checksum());
+      ds.field("physics_step", &self.physics_step());
+      ds.field("race_events", &self.race_events());
+      ds.field("physics_params", &self.physics_params());
+      ds.field("ack_hold_micros", &self.ack_hold_micros());
+      ds.field("ack_steps", &self.ack_steps());
       ds.finish()
   }
 }
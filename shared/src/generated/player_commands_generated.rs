@@ -12,13 +12,15 @@ use self::flatbuffers::{EndianScalar, Follow};
 #[deprecated(since = "2.0.0", note = "Use associated constants instead. This will no longer be generated in 2021.")]
 pub const ENUM_MIN_PLAYER_COMMAND: u8 = 0;
 #[deprecated(since = "2.0.0", note = "Use associated constants instead. This will no longer be generated in 2021.")]
-pub const ENUM_MAX_PLAYER_COMMAND: u8 = 2;
+pub const ENUM_MAX_PLAYER_COMMAND: u8 = 4;
 #[deprecated(since = "2.0.0", note = "Use associated constants instead. This will no longer be generated in 2021.")]
 #[allow(non_camel_case_types)]
-pub const ENUM_VALUES_PLAYER_COMMAND: [PlayerCommand; 3] = [
+pub const ENUM_VALUES_PLAYER_COMMAND: [PlayerCommand; 5] = [
   PlayerCommand::MoveRight,
   PlayerCommand::MoveLeft,
   PlayerCommand::Jump,
+  PlayerCommand::MoveDown,
+  PlayerCommand::Dash,
 ];
 
 #[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
@@ -29,13 +31,17 @@ impl PlayerCommand {
   pub const MoveRight: Self = Self(0);
   pub const MoveLeft: Self = Self(1);
   pub const Jump: Self = Self(2);
+  pub const MoveDown: Self = Self(3);
+  pub const Dash: Self = Self(4);
 
   pub const ENUM_MIN: u8 = 0;
-  pub const ENUM_MAX: u8 = 2;
+  pub const ENUM_MAX: u8 = 4;
   pub const ENUM_VALUES: &'static [Self] = &[
     Self::MoveRight,
     Self::MoveLeft,
     Self::Jump,
+    Self::MoveDown,
+    Self::Dash,
   ];
   /// Returns the variant's name or "" if unknown.
   pub fn variant_name(self) -> Option<&'static str> {
@@ -43,6 +49,8 @@ impl PlayerCommand {
       Self::MoveRight => Some("MoveRight"),
       Self::MoveLeft => Some("MoveLeft"),
       Self::Jump => Some("Jump"),
+      Self::MoveDown => Some("MoveDown"),
+      Self::Dash => Some("Dash"),
       _ => None,
     }
   }
@@ -118,6 +126,8 @@ impl<'a> PlayerCommands<'a> {
   pub const VT_DT_MICRO: flatbuffers::VOffsetT = 6;
   pub const VT_COMMANDS: flatbuffers::VOffsetT = 8;
   pub const VT_CLIENT_TIMESTAMP_MICRO: flatbuffers::VOffsetT = 10;
+  pub const VT_MOVE_AXIS: flatbuffers::VOffsetT = 12;
+  pub const VT_ACKED_TICK: flatbuffers::VOffsetT = 14;
 
   #[inline]
   pub unsafe fn init_from_table(table: flatbuffers::Table<'a>) -> Self {
@@ -131,8 +141,10 @@ impl<'a> PlayerCommands<'a> {
     let mut builder = PlayerCommandsBuilder::new(_fbb);
     builder.add_client_timestamp_micro(args.client_timestamp_micro);
     builder.add_dt_micro(args.dt_micro);
+    builder.add_acked_tick(args.acked_tick);
     if let Some(x) = args.commands { builder.add_commands(x); }
     builder.add_sequence(args.sequence);
+    builder.add_move_axis(args.move_axis);
     builder.finish()
   }
 
@@ -165,6 +177,20 @@ impl<'a> PlayerCommands<'a> {
     // which contains a valid value in this slot
     unsafe { self._tab.get::<u64>(PlayerCommands::VT_CLIENT_TIMESTAMP_MICRO, Some(0)).unwrap()}
   }
+  #[inline]
+  pub fn move_axis(&self) -> i8 {
+    // Safety:
+    // Created from valid Table for this object
+    // which contains a valid value in this slot
+    unsafe { self._tab.get::<i8>(PlayerCommands::VT_MOVE_AXIS, Some(0)).unwrap()}
+  }
+  #[inline]
+  pub fn acked_tick(&self) -> u32 {
+    // Safety:
+    // Created from valid Table for this object
+    // which contains a valid value in this slot
+    unsafe { self._tab.get::<u32>(PlayerCommands::VT_ACKED_TICK, Some(4294967295)).unwrap()}
+  }
 }
 
 impl flatbuffers::Verifiable for PlayerCommands<'_> {
@@ -178,6 +204,8 @@ impl flatbuffers::Verifiable for PlayerCommands<'_> {
      .visit_field::<u64>("dt_micro", Self::VT_DT_MICRO, false)?
      .visit_field::<flatbuffers::ForwardsUOffset<flatbuffers::Vector<'_, PlayerCommand>>>("commands", Self::VT_COMMANDS, false)?
      .visit_field::<u64>("client_timestamp_micro", Self::VT_CLIENT_TIMESTAMP_MICRO, false)?
+     .visit_field::<i8>("move_axis", Self::VT_MOVE_AXIS, false)?
+     .visit_field::<u32>("acked_tick", Self::VT_ACKED_TICK, false)?
      .finish();
     Ok(())
   }
@@ -187,6 +215,8 @@ pub struct PlayerCommandsArgs<'a> {
     pub dt_micro: u64,
     pub commands: Option<flatbuffers::WIPOffset<flatbuffers::Vector<'a, PlayerCommand>>>,
     pub client_timestamp_micro: u64,
+    pub move_axis: i8,
+    pub acked_tick: u32,
 }
 impl<'a> Default for PlayerCommandsArgs<'a> {
   #[inline]
@@ -196,6 +226,8 @@ impl<'a> Default for PlayerCommandsArgs<'a> {
       dt_micro: 0,
       commands: None,
       client_timestamp_micro: 0,
+      move_axis: 0,
+      acked_tick: 4294967295,
     }
   }
 }
@@ -222,6 +254,14 @@ impl<'a: 'b, 'b, A: flatbuffers::Allocator + 'a> PlayerCommandsBuilder<'a, 'b, A
     self.fbb_.push_slot::<u64>(PlayerCommands::VT_CLIENT_TIMESTAMP_MICRO, client_timestamp_micro, 0);
   }
   #[inline]
+  pub fn add_move_axis(&mut self, move_axis: i8) {
+    self.fbb_.push_slot::<i8>(PlayerCommands::VT_MOVE_AXIS, move_axis, 0);
+  }
+  #[inline]
+  pub fn add_acked_tick(&mut self, acked_tick: u32) {
+    self.fbb_.push_slot::<u32>(PlayerCommands::VT_ACKED_TICK, acked_tick, 4294967295);
+  }
+  #[inline]
   pub fn new(_fbb: &'b mut flatbuffers::FlatBufferBuilder<'a, A>) -> PlayerCommandsBuilder<'a, 'b, A> {
     let start = _fbb.start_table();
     PlayerCommandsBuilder {
@@ -243,6 +283,8 @@ impl core::fmt::Debug for PlayerCommands<'_> {
       ds.field("dt_micro", &self.dt_micro());
       ds.field("commands", &self.commands());
       ds.field("client_timestamp_micro", &self.client_timestamp_micro());
+      ds.field("move_axis", &self.move_axis());
+      ds.field("acked_tick", &self.acked_tick());
       ds.finish()
   }
 }
@@ -1,2 +1,4 @@
 pub mod generated;
+pub mod net_stats;
 pub mod state;
+pub mod transport;
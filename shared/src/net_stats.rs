@@ -0,0 +1,232 @@
+use std::collections::{HashMap, VecDeque};
+use std::time::{Duration, Instant};
+
+/// Rates are averaged over this window
+const RATE_WINDOW: Duration = Duration::from_secs(1);
+/// How often a summary is stored for graphing
+const SAMPLE_INTERVAL: Duration = Duration::from_millis(250);
+/// Number of stored summaries (15 seconds)
+const HISTORY_LEN: usize = 60;
+/// Smoothing factor for RTT and jitter, same as TCP/RTP use
+const SMOOTHING: f32 = 1.0 / 16.0;
+
+/// Point-in-time view of the connection.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct NetSummary {
+    pub bytes_sent_per_sec: f32,
+    pub bytes_received_per_sec: f32,
+    pub packets_sent_per_sec: f32,
+    pub packets_received_per_sec: f32,
+    pub avg_packet_size: f32,
+    /// Fraction of packets lost, estimated from sequence gaps
+    pub loss: f32,
+    pub rtt_ms: f32,
+    pub jitter_ms: f32,
+}
+
+/// Traffic counters for one end of the connection.
+pub struct NetStats {
+    sent: VecDeque<(Instant, usize)>,
+    received: VecDeque<(Instant, usize)>,
+    last_sequence: HashMap<u32, u32>,
+    expected_packets: u64,
+    lost_packets: u64,
+    rtt_ms: Option<f32>,
+    jitter_ms: f32,
+    history: VecDeque<NetSummary>,
+    last_sample: Option<Instant>,
+}
+
+impl Default for NetStats {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl NetStats {
+    pub fn new() -> Self {
+        NetStats {
+            sent: VecDeque::new(),
+            received: VecDeque::new(),
+            last_sequence: HashMap::new(),
+            expected_packets: 0,
+            lost_packets: 0,
+            rtt_ms: None,
+            jitter_ms: 0.0,
+            history: VecDeque::with_capacity(HISTORY_LEN),
+            last_sample: None,
+        }
+    }
+
+    pub fn record_sent(&mut self, bytes: usize, now: Instant) {
+        self.sent.push_back((now, bytes));
+        self.update(now);
+    }
+
+    pub fn record_received(&mut self, bytes: usize, now: Instant) {
+        self.received.push_back((now, bytes));
+        self.update(now);
+    }
+
+    /// Track a packet sequence number from `peer_id`; skipped numbers count as lost.
+    /// Old or duplicate numbers are ignored.
+    pub fn record_sequence(&mut self, peer_id: u32, sequence: u32) {
+        match self.last_sequence.get(&peer_id) {
+            Some(&last) if sequence <= last => {}
+            Some(&last) => {
+                let gap = (sequence - last) as u64;
+                self.expected_packets += gap;
+                self.lost_packets += gap - 1;
+                self.last_sequence.insert(peer_id, sequence);
+            }
+            None => {
+                self.expected_packets += 1;
+                self.last_sequence.insert(peer_id, sequence);
+            }
+        }
+    }
+
+    pub fn forget_peer(&mut self, peer_id: u32) {
+        self.last_sequence.remove(&peer_id);
+    }
+
+    /// Add a round trip time sample, updating the smoothed RTT and jitter
+    pub fn record_rtt(&mut self, rtt: Duration) {
+        let sample = rtt.as_secs_f32() * 1000.0;
+        match self.rtt_ms {
+            Some(rtt_ms) => {
+                self.jitter_ms += ((sample - rtt_ms).abs() - self.jitter_ms) * SMOOTHING;
+                self.rtt_ms = Some(rtt_ms + (sample - rtt_ms) * SMOOTHING);
+            }
+            None => self.rtt_ms = Some(sample),
+        }
+    }
+
+    pub fn summary(&self) -> NetSummary {
+        let window = RATE_WINDOW.as_secs_f32();
+        let bytes_sent: usize = self.sent.iter().map(|(_, b)| b).sum();
+        let bytes_received: usize = self.received.iter().map(|(_, b)| b).sum();
+        let packets = self.sent.len() + self.received.len();
+
+        NetSummary {
+            bytes_sent_per_sec: bytes_sent as f32 / window,
+            bytes_received_per_sec: bytes_received as f32 / window,
+            packets_sent_per_sec: self.sent.len() as f32 / window,
+            packets_received_per_sec: self.received.len() as f32 / window,
+            avg_packet_size: if packets > 0 {
+                (bytes_sent + bytes_received) as f32 / packets as f32
+            } else {
+                0.0
+            },
+            loss: if self.expected_packets > 0 {
+                self.lost_packets as f32 / self.expected_packets as f32
+            } else {
+                0.0
+            },
+            rtt_ms: self.rtt_ms.unwrap_or(0.0),
+            jitter_ms: self.jitter_ms,
+        }
+    }
+
+    /// Past summaries, oldest first
+    pub fn history(&self) -> impl Iterator<Item = &NetSummary> {
+        self.history.iter()
+    }
+
+    /// Drop packets that left the rate window and store a summary if one is due.
+    /// Called by the record functions, but also worth calling when idle.
+    pub fn update(&mut self, now: Instant) {
+        for queue in [&mut self.sent, &mut self.received] {
+            while let Some((at, _)) = queue.front() {
+                if now.duration_since(*at) > RATE_WINDOW {
+                    queue.pop_front();
+                } else {
+                    break;
+                }
+            }
+        }
+
+        let due = self
+            .last_sample
+            .is_none_or(|last| now.duration_since(last) >= SAMPLE_INTERVAL);
+        if due {
+            if self.history.len() == HISTORY_LEN {
+                self.history.pop_front();
+            }
+            self.history.push_back(self.summary());
+            self.last_sample = Some(now);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rates_use_sliding_window() {
+        let mut stats = NetStats::new();
+        let start = Instant::now();
+        for i in 0..10 {
+            stats.record_sent(100, start + Duration::from_millis(i * 100));
+        }
+        stats.record_received(300, start + Duration::from_millis(950));
+
+        let summary = stats.summary();
+        assert_eq!(summary.bytes_sent_per_sec, 1000.0);
+        assert_eq!(summary.packets_sent_per_sec, 10.0);
+        assert_eq!(summary.bytes_received_per_sec, 300.0);
+        assert!((summary.avg_packet_size - 1300.0 / 11.0).abs() < 0.01);
+
+        // Everything but the received packet and the last sent one falls out of the window
+        stats.update(start + Duration::from_millis(1850));
+        let summary = stats.summary();
+        assert_eq!(summary.packets_sent_per_sec, 1.0);
+        assert_eq!(summary.packets_received_per_sec, 1.0);
+    }
+
+    #[test]
+    fn test_loss_from_sequence_gaps() {
+        let mut stats = NetStats::new();
+        for sequence in [1, 2, 3, 5, 6, 9, 10] {
+            stats.record_sequence(1, sequence);
+        }
+        // Reordered and duplicate packets are not counted
+        stats.record_sequence(1, 4);
+        stats.record_sequence(1, 10);
+        // Another peer with no loss
+        for sequence in 0..10 {
+            stats.record_sequence(2, sequence);
+        }
+
+        // Peer 1 expected 1..=10, lost 4, 7 and 8
+        let summary = stats.summary();
+        assert!((summary.loss - 3.0 / 20.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_rtt_and_jitter_smoothing() {
+        let mut stats = NetStats::new();
+        stats.record_rtt(Duration::from_millis(100));
+        assert_eq!(stats.summary().rtt_ms, 100.0);
+        assert_eq!(stats.summary().jitter_ms, 0.0);
+
+        for _ in 0..200 {
+            stats.record_rtt(Duration::from_millis(80));
+            stats.record_rtt(Duration::from_millis(120));
+        }
+        let summary = stats.summary();
+        assert!((summary.rtt_ms - 100.0).abs() < 5.0);
+        assert!(summary.jitter_ms > 10.0 && summary.jitter_ms < 40.0);
+    }
+
+    #[test]
+    fn test_history_is_bounded() {
+        let mut stats = NetStats::new();
+        let start = Instant::now();
+        for i in 0..(HISTORY_LEN as u64 * 2) {
+            stats.record_sent(10, start + SAMPLE_INTERVAL * i as u32);
+        }
+        assert_eq!(stats.history().count(), HISTORY_LEN);
+    }
+}
@@ -0,0 +1,165 @@
+use std::collections::HashMap;
+
+use super::{SceneObject, Trigger};
+
+/// Side of a grid cell in px, a few player sizes wide
+pub const GRID_CELL_SIZE: f32 = 128.0;
+
+/// Static scene object stored in the grid.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum GridEntry {
+    /// Index into `GameState::collidables`
+    Collidable(usize),
+    /// Index into `GameState::triggers`
+    Trigger(usize),
+    WinPoint,
+}
+
+/// Uniform grid over the static parts of a scene, so physics only has to look at the
+/// objects near a player instead of every object in the level.
+#[derive(Debug, Clone)]
+pub struct CollisionGrid {
+    cell_size: f32,
+    cells: HashMap<(i32, i32), Vec<GridEntry>>,
+}
+
+impl Default for CollisionGrid {
+    fn default() -> Self {
+        CollisionGrid {
+            cell_size: GRID_CELL_SIZE,
+            cells: HashMap::new(),
+        }
+    }
+}
+
+impl CollisionGrid {
+    pub fn new(collidables: &[SceneObject], triggers: &[Trigger], win_point: &SceneObject) -> Self {
+        Self::with_cell_size(collidables, triggers, win_point, GRID_CELL_SIZE)
+    }
+
+    pub fn with_cell_size(
+        collidables: &[SceneObject],
+        triggers: &[Trigger],
+        win_point: &SceneObject,
+        cell_size: f32,
+    ) -> Self {
+        let mut grid = CollisionGrid {
+            cell_size,
+            cells: HashMap::new(),
+        };
+        for (index, col) in collidables.iter().enumerate() {
+            grid.insert(col, GridEntry::Collidable(index));
+        }
+        for (index, trigger) in triggers.iter().enumerate() {
+            grid.insert(&trigger.rect(), GridEntry::Trigger(index));
+        }
+        grid.insert(win_point, GridEntry::WinPoint);
+        grid
+    }
+
+    /// Entries whose cells overlap `rect`, sorted and without duplicates. This is a
+    /// broadphase: the entries still need an exact test.
+    pub fn query(&self, rect: &SceneObject) -> Vec<GridEntry> {
+        let mut entries = Vec::new();
+        let (x1, y1, x2, y2) = self.cell_range(rect);
+        for cx in x1..=x2 {
+            for cy in y1..=y2 {
+                if let Some(cell) = self.cells.get(&(cx, cy)) {
+                    entries.extend_from_slice(cell);
+                }
+            }
+        }
+        entries.sort_unstable();
+        entries.dedup();
+        entries
+    }
+
+    /// Indices of the collidables that may overlap `rect`, in ascending order
+    pub fn query_collidables(&self, rect: &SceneObject) -> impl Iterator<Item = usize> {
+        self.query(rect)
+            .into_iter()
+            .filter_map(|entry| match entry {
+                GridEntry::Collidable(index) => Some(index),
+                _ => None,
+            })
+    }
+
+    fn insert(&mut self, rect: &SceneObject, entry: GridEntry) {
+        let (x1, y1, x2, y2) = self.cell_range(rect);
+        for cx in x1..=x2 {
+            for cy in y1..=y2 {
+                self.cells.entry((cx, cy)).or_default().push(entry);
+            }
+        }
+    }
+
+    fn cell_range(&self, rect: &SceneObject) -> (i32, i32, i32, i32) {
+        let cell = |value: f32| (value / self.cell_size).floor() as i32;
+        (
+            cell(rect.x),
+            cell(rect.y),
+            cell(rect.x + rect.w),
+            cell(rect.y + rect.h),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::CollidableKind;
+
+    fn rect(x: f32, y: f32, w: f32, h: f32) -> SceneObject {
+        SceneObject {
+            x,
+            y,
+            w,
+            h,
+            kind: CollidableKind::Solid,
+        }
+    }
+
+    #[test]
+    fn test_query_finds_nearby_objects_only() {
+        let collidables = vec![
+            rect(0.0, 0.0, 50.0, 20.0),
+            rect(1000.0, 0.0, 50.0, 20.0),
+            rect(100.0, 100.0, 20.0, 20.0),
+        ];
+        let grid = CollisionGrid::new(&collidables, &[], &rect(5000.0, 0.0, 50.0, 50.0));
+
+        let found: Vec<usize> = grid
+            .query_collidables(&rect(10.0, 10.0, 16.0, 16.0))
+            .collect();
+
+        assert_eq!(found, vec![0, 2]);
+    }
+
+    #[test]
+    fn test_large_objects_are_reported_once() {
+        let collidables = vec![rect(0.0, 500.0, 3000.0, 100.0)];
+        let win_point = rect(2750.0, 450.0, 50.0, 50.0);
+        let grid = CollisionGrid::new(&collidables, &[], &win_point);
+
+        let entries = grid.query(&rect(2700.0, 400.0, 300.0, 150.0));
+
+        assert_eq!(entries, vec![GridEntry::Collidable(0), GridEntry::WinPoint]);
+    }
+
+    #[test]
+    fn test_negative_coordinates() {
+        let collidables = vec![rect(-300.0, -300.0, 50.0, 50.0)];
+        let grid = CollisionGrid::new(&collidables, &[], &rect(0.0, 0.0, 1.0, 1.0));
+
+        assert_eq!(
+            grid.query_collidables(&rect(-280.0, -280.0, 10.0, 10.0))
+                .count(),
+            1
+        );
+        assert_eq!(
+            grid.query_collidables(&rect(280.0, 280.0, 10.0, 10.0))
+                .count(),
+            0
+        );
+    }
+}
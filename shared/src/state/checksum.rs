@@ -0,0 +1,156 @@
+use std::collections::BTreeMap;
+
+use super::PlayerState;
+
+/// Predicted checksums older than this many sequences are dropped unchecked
+const MAX_PENDING_CHECKSUMS: usize = 256;
+
+const FNV_OFFSET: u32 = 0x811c9dc5;
+const FNV_PRIME: u32 = 0x01000193;
+
+/// FNV-1a, cheap enough to run every frame
+struct Hasher(u32);
+
+impl Hasher {
+    fn new() -> Self {
+        Hasher(FNV_OFFSET)
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.0 ^= *byte as u32;
+            self.0 = self.0.wrapping_mul(FNV_PRIME);
+        }
+    }
+
+    fn write_u32(&mut self, value: u32) {
+        self.write(&value.to_le_bytes());
+    }
+
+    /// The exact bits the server sends, client and server run the same simulation so any
+    /// difference is a desync. Rounding would split values that are nearly equal whenever
+    /// they straddle a rounding boundary.
+    fn write_f32(&mut self, value: f32) {
+        // -0.0 and 0.0 are the same position
+        let value = if value == 0.0 { 0.0 } else { value };
+        self.write(&value.to_bits().to_le_bytes());
+    }
+}
+
+/// Hash of the simulated part of a player
+pub fn player_checksum(player: &PlayerState) -> u32 {
+    let mut hasher = Hasher::new();
+    hasher.write_u32(player.id);
+    hasher.write_f32(player.pos.x);
+    hasher.write_f32(player.pos.y);
+    hasher.write_f32(player.vel.x);
+    hasher.write_f32(player.vel.y);
+    hasher.write(&[player.grounded as u8]);
+    hasher.0
+}
+
+/// Compares the checksums of predicted client states against the ones the server
+/// computed for the same command sequence.
+#[derive(Debug, Default)]
+pub struct DesyncDetector {
+    predicted: BTreeMap<u32, u32>,
+    checks: u32,
+    mismatches: u32,
+}
+
+impl DesyncDetector {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Remember the predicted state right after applying command `sequence`
+    pub fn record_predicted(&mut self, sequence: u32, checksum: u32) {
+        self.predicted.insert(sequence, checksum);
+        while self.predicted.len() > MAX_PENDING_CHECKSUMS {
+            self.predicted.pop_first();
+        }
+    }
+
+    /// Check the server's checksum for the acked `sequence`. Returns `None` when there is
+    /// no prediction to compare with, otherwise whether the two matched.
+    pub fn check(&mut self, sequence: u32, server_checksum: u32) -> Option<bool> {
+        let predicted = self.predicted.get(&sequence).copied();
+        self.predicted.retain(|s, _| *s > sequence);
+
+        let matches = predicted? == server_checksum;
+        self.checks += 1;
+        if !matches {
+            self.mismatches += 1;
+        }
+        Some(matches)
+    }
+
+    pub fn checks(&self) -> u32 {
+        self.checks
+    }
+
+    pub fn mismatches(&self) -> u32 {
+        self.mismatches
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::{SpawnPoint, Vec2};
+
+    fn create_test_player() -> PlayerState {
+        PlayerState::new(1, &SpawnPoint { x: 100.0, y: 200.0 })
+    }
+
+    #[test]
+    fn test_checksum_has_no_rounding_boundaries() {
+        let at = |x: f32| {
+            let mut player = create_test_player();
+            player.pos.x = x;
+            player_checksum(&player)
+        };
+
+        // Equal values match on either side of x.5, nearly equal ones never do
+        for x in [100.4999, 100.5, 100.5001] {
+            assert_eq!(at(x), at(x));
+        }
+        assert_ne!(at(100.4999), at(100.5001));
+        assert_ne!(at(100.5001), at(100.5003));
+
+        let mut still = create_test_player();
+        still.vel = Vec2::new(-0.0, 0.0);
+        let mut stopped = create_test_player();
+        stopped.vel = Vec2::new(0.0, -0.0);
+        assert_eq!(player_checksum(&still), player_checksum(&stopped));
+    }
+
+    #[test]
+    fn test_checksum_detects_divergence() {
+        let player = create_test_player();
+
+        let mut moved = player.clone();
+        moved.pos.y += 5.0;
+        assert_ne!(player_checksum(&player), player_checksum(&moved));
+
+        let mut landed = player.clone();
+        landed.grounded = true;
+        assert_ne!(player_checksum(&player), player_checksum(&landed));
+    }
+
+    #[test]
+    fn test_detector_counts_mismatches() {
+        let mut detector = DesyncDetector::new();
+        detector.record_predicted(1, 10);
+        detector.record_predicted(2, 20);
+        detector.record_predicted(3, 30);
+
+        assert_eq!(detector.check(1, 10), Some(true));
+        assert_eq!(detector.check(3, 31), Some(false));
+        // Sequence 2 was skipped by the server ack and dropped
+        assert_eq!(detector.check(2, 20), None);
+
+        assert_eq!(detector.checks(), 2);
+        assert_eq!(detector.mismatches(), 1);
+    }
+}
@@ -0,0 +1,240 @@
+use std::collections::HashMap;
+
+use crate::generated::QuantizedPosition;
+
+use super::Vec2;
+
+/// How many snapshots in a row carry a player's name, in case the first ones are lost
+pub const NAME_REPEATS: u32 = 3;
+
+/// How a snapshot encodes players.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Encoding<'a> {
+    /// Float positions, names in every snapshot
+    #[default]
+    Full,
+    /// Fixed-point positions of other players; only the listed players carry their name
+    Compact { named_player_ids: &'a [u32] },
+}
+
+impl Encoding<'_> {
+    pub fn includes_name(&self, player_id: u32) -> bool {
+        match self {
+            Encoding::Full => true,
+            Encoding::Compact { named_player_ids } => named_player_ids.contains(&player_id),
+        }
+    }
+}
+
+pub fn quantize_position(pos: Vec2, width: f32, height: f32) -> QuantizedPosition {
+    QuantizedPosition::new(quantize_unit(pos.x, width), quantize_unit(pos.y, height))
+}
+
+pub fn dequantize_position(qpos: &QuantizedPosition, width: f32, height: f32) -> Vec2 {
+    Vec2::new(
+        qpos.x() as f32 / u16::MAX as f32 * width,
+        qpos.y() as f32 / u16::MAX as f32 * height,
+    )
+}
+
+/// Analog stick deflection from -1 to 1 as sent in `PlayerCommands::move_axis`
+pub fn quantize_axis(value: f32) -> i8 {
+    (value.clamp(-1.0, 1.0) * i8::MAX as f32).round() as i8
+}
+
+pub fn dequantize_axis(value: i8) -> f32 {
+    (value as f32 / i8::MAX as f32).max(-1.0)
+}
+
+fn quantize_unit(value: f32, extent: f32) -> u16 {
+    if extent <= 0.0 {
+        return 0;
+    }
+    ((value / extent).clamp(0.0, 1.0) * u16::MAX as f32).round() as u16
+}
+
+/// Remembers which player names each client has been sent, so compact snapshots only
+/// carry a name for the first few snapshots after a player shows up.
+#[derive(Default)]
+pub struct NameTracker {
+    sent: HashMap<u32, HashMap<u32, u32>>,
+}
+
+impl NameTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Of `player_ids`, the ones whose name should go to `client_id` this snapshot
+    pub fn names_to_send(&self, client_id: u32, player_ids: &[u32]) -> Vec<u32> {
+        let sent = self.sent.get(&client_id);
+        player_ids
+            .iter()
+            .copied()
+            .filter(|id| sent.and_then(|sent| sent.get(id)).copied().unwrap_or(0) < NAME_REPEATS)
+            .collect()
+    }
+
+    /// Count a name as sent for the players that made it into the snapshot, players cut
+    /// by the byte budget keep theirs for later
+    pub fn mark_sent(&mut self, client_id: u32, player_ids: &[u32]) {
+        let sent = self.sent.entry(client_id).or_default();
+        for id in player_ids {
+            *sent.entry(*id).or_insert(0) += 1;
+        }
+    }
+
+    /// Start over for players the client dropped, so they get their name again on return
+    pub fn forget(&mut self, client_id: u32, player_ids: &[u32]) {
+        if let Some(sent) = self.sent.get_mut(&client_id) {
+            for id in player_ids {
+                sent.remove(id);
+            }
+        }
+    }
+
+    pub fn remove_client(&mut self, client_id: u32) {
+        self.sent.remove(&client_id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::{GameState, PlayerState, SendContext, SpawnPoint};
+    use flatbuffers::FlatBufferBuilder;
+
+    const WIDTH: f32 = 3000.0;
+    const HEIGHT: f32 = 600.0;
+
+    fn create_test_state() -> GameState {
+        GameState::empty(WIDTH, HEIGHT)
+    }
+
+    #[test]
+    fn test_position_round_trip_error() {
+        let max_error_x = WIDTH / u16::MAX as f32 / 2.0;
+        let max_error_y = HEIGHT / u16::MAX as f32 / 2.0;
+
+        for i in 0..1000 {
+            let pos = Vec2::new(i as f32 * 2.9971, i as f32 * 0.5993);
+            let decoded =
+                dequantize_position(&quantize_position(pos, WIDTH, HEIGHT), WIDTH, HEIGHT);
+            assert!((decoded.x - pos.x).abs() <= max_error_x + 1e-3);
+            assert!((decoded.y - pos.y).abs() <= max_error_y + 1e-3);
+        }
+    }
+
+    #[test]
+    fn test_axis_round_trip() {
+        assert_eq!(quantize_axis(0.0), 0);
+        assert_eq!(dequantize_axis(quantize_axis(1.0)), 1.0);
+        assert_eq!(dequantize_axis(quantize_axis(-1.0)), -1.0);
+        assert_eq!(dequantize_axis(i8::MIN), -1.0);
+        assert_eq!(quantize_axis(3.0), i8::MAX);
+    }
+
+    #[test]
+    fn test_names_are_repeated_then_dropped() {
+        let mut names = NameTracker::new();
+        for _ in 0..NAME_REPEATS {
+            assert_eq!(names.names_to_send(1, &[2, 3]), vec![2, 3]);
+            names.mark_sent(1, &[2, 3]);
+        }
+        assert!(names.names_to_send(1, &[2, 3]).is_empty());
+
+        names.forget(1, &[3]);
+        assert_eq!(names.names_to_send(1, &[2, 3]), vec![3]);
+    }
+
+    #[test]
+    fn test_names_of_unsent_players_are_kept() {
+        let mut names = NameTracker::new();
+        for _ in 0..NAME_REPEATS {
+            assert_eq!(names.names_to_send(1, &[2, 3]), vec![2, 3]);
+            // Player 3 never fits in the snapshot
+            names.mark_sent(1, &[2]);
+        }
+
+        assert_eq!(names.names_to_send(1, &[2, 3]), vec![3]);
+    }
+
+    fn snapshot_size(state: &GameState, player_ids: &[u32], encoding: Encoding) -> usize {
+        let mut builder = FlatBufferBuilder::new();
+        let context = SendContext {
+            encoding,
+            ..Default::default()
+        };
+        state
+            .serialize_players(&mut builder, &context, player_ids, &[])
+            .len()
+    }
+
+    #[test]
+    fn test_compact_bytes_per_player() {
+        let mut state = create_test_state();
+        for id in 0..=50 {
+            let mut player = PlayerState::new(id, &SpawnPoint { x: 17.3, y: 420.1 });
+            player.pos.x += id as f32 * 31.7;
+            state.players.insert(id, player);
+        }
+        let others: Vec<u32> = (1..=50).collect();
+        let compact = Encoding::Compact {
+            named_player_ids: &[],
+        };
+
+        let full_per_player = (snapshot_size(&state, &others, Encoding::Full)
+            - snapshot_size(&state, &[], Encoding::Full)) as f32
+            / others.len() as f32;
+        let compact_per_player = (snapshot_size(&state, &others, compact)
+            - snapshot_size(&state, &[], compact)) as f32
+            / others.len() as f32;
+
+        println!(
+            "bytes per player: full {:.1}, compact {:.1}",
+            full_per_player, compact_per_player
+        );
+        assert!(compact_per_player <= 24.0);
+        assert!(compact_per_player < full_per_player * 0.6);
+
+        // And the compact snapshot still decodes to the same world
+        let mut builder = FlatBufferBuilder::new();
+        let context = SendContext {
+            encoding: compact,
+            ..Default::default()
+        };
+        let bytes = state.serialize_players(&mut builder, &context, &others, &[]);
+        let decoded = state.deserialize(bytes);
+        for id in &others {
+            let original = &state.players[id];
+            let player = &decoded.players[id];
+            assert!((player.pos.x - original.pos.x).abs() < 0.05);
+            assert!((player.pos.y - original.pos.y).abs() < 0.05);
+            assert!(player.name.is_empty());
+        }
+    }
+
+    #[test]
+    fn test_physics_params_are_sent_on_request() {
+        let mut state = create_test_state();
+        state
+            .players
+            .insert(0, PlayerState::new(0, &state.spawn_point));
+        state.physics_params.gravity = 1500.0;
+        state.physics_params.air_friction = 0.5;
+
+        let mut builder = FlatBufferBuilder::new();
+        let context = SendContext {
+            send_physics_params: true,
+            ..Default::default()
+        };
+        let bytes = state.serialize_players(&mut builder, &context, &[], &[]);
+        let physics_params = state.deserialize(bytes).physics_params;
+        assert_eq!(physics_params, Some(state.physics_params));
+
+        let mut builder = FlatBufferBuilder::new();
+        let bytes = state.serialize_players(&mut builder, &SendContext::default(), &[], &[]);
+        let physics_params = state.deserialize(bytes).physics_params;
+        assert_eq!(physics_params, None);
+    }
+}
@@ -0,0 +1,295 @@
+use std::collections::{HashMap, VecDeque};
+use std::time::Duration;
+
+use super::{CollidableKind, GameState, SceneObject, Vec2};
+
+/// Where a single player was at the end of a recorded tick.
+#[derive(Clone, Copy)]
+pub struct PlayerSnapshot {
+    pub pos: Vec2,
+    pub size: f32,
+}
+
+impl PlayerSnapshot {
+    pub fn rect(&self) -> SceneObject {
+        SceneObject {
+            x: self.pos.x,
+            y: self.pos.y,
+            w: self.size,
+            h: self.size,
+            kind: CollidableKind::Solid,
+        }
+    }
+}
+
+/// Player positions at the end of one server tick.
+#[derive(Clone)]
+pub struct HistoryFrame {
+    pub tick: u32,
+    pub timestamp_micros: u64,
+    pub players: HashMap<u32, PlayerSnapshot>,
+}
+
+/// The world reconstructed the way a given player saw it when they acted.
+pub struct RewoundState {
+    pub tick: u32,
+    pub timestamp_micros: u64,
+    pub players: HashMap<u32, PlayerSnapshot>,
+}
+
+impl RewoundState {
+    /// Ids of all players (except `ignore_id`) whose box overlaps `rect`, sorted by id
+    pub fn players_overlapping(&self, rect: &SceneObject, ignore_id: u32) -> Vec<u32> {
+        let mut hits: Vec<u32> = self
+            .players
+            .iter()
+            .filter(|(id, p)| **id != ignore_id && overlaps(&p.rect(), rect))
+            .map(|(id, _)| *id)
+            .collect();
+        hits.sort_unstable();
+        hits
+    }
+}
+
+/// Ring of past ticks used by the server to judge interactions against what the acting
+/// client actually saw (lag compensation).
+pub struct StateHistory {
+    frames: VecDeque<HistoryFrame>,
+    max_rewind_micros: u64,
+}
+
+impl StateHistory {
+    pub fn new(max_rewind: Duration) -> Self {
+        StateHistory {
+            frames: VecDeque::new(),
+            max_rewind_micros: max_rewind.as_micros() as u64,
+        }
+    }
+
+    /// Store player positions for `tick`, dropping frames that fall outside the rewind window
+    pub fn record(&mut self, tick: u32, timestamp_micros: u64, state: &GameState) {
+        let players = state
+            .players
+            .iter()
+            .map(|(&id, p)| {
+                (
+                    id,
+                    PlayerSnapshot {
+                        pos: p.pos,
+                        size: p.size,
+                    },
+                )
+            })
+            .collect();
+
+        self.frames.push_back(HistoryFrame {
+            tick,
+            timestamp_micros,
+            players,
+        });
+
+        let oldest_allowed = timestamp_micros.saturating_sub(self.max_rewind_micros);
+        while let Some(frame) = self.frames.front() {
+            if frame.timestamp_micros < oldest_allowed {
+                self.frames.pop_front();
+            } else {
+                break;
+            }
+        }
+    }
+
+    pub fn latest(&self) -> Option<&HistoryFrame> {
+        self.frames.back()
+    }
+
+    pub fn frame(&self, tick: u32) -> Option<&HistoryFrame> {
+        self.frames.iter().find(|f| f.tick == tick)
+    }
+
+    /// Rebuild the world at `tick` as seen by `viewer_id`.
+    ///
+    /// The viewer is placed where they were at `tick`, while every other player is placed
+    /// `interp_delay` earlier, since that is where the viewer's interpolator was drawing them.
+    /// Targets older than the rewind window are clamped to the oldest stored frame.
+    pub fn rewind(
+        &self,
+        tick: u32,
+        viewer_id: u32,
+        interp_delay: Duration,
+    ) -> Option<RewoundState> {
+        let frame = self.frame(tick)?;
+        let oldest = self.frames.front()?;
+
+        let target_micros = frame
+            .timestamp_micros
+            .saturating_sub(interp_delay.as_micros() as u64)
+            .max(oldest.timestamp_micros);
+
+        let mut players = self.players_at(target_micros);
+        match frame.players.get(&viewer_id) {
+            Some(viewer) => {
+                players.insert(viewer_id, *viewer);
+            }
+            None => {
+                players.remove(&viewer_id);
+            }
+        }
+
+        Some(RewoundState {
+            tick,
+            timestamp_micros: target_micros,
+            players,
+        })
+    }
+
+    /// Run `query` against the world at `tick` as seen by `viewer_id`.
+    /// Returns `None` if the tick has already left the rewind window.
+    pub fn query_as_seen_by<R>(
+        &self,
+        tick: u32,
+        viewer_id: u32,
+        interp_delay: Duration,
+        query: impl FnOnce(&RewoundState) -> R,
+    ) -> Option<R> {
+        self.rewind(tick, viewer_id, interp_delay)
+            .map(|state| query(&state))
+    }
+
+    fn players_at(&self, timestamp_micros: u64) -> HashMap<u32, PlayerSnapshot> {
+        let after_index = self
+            .frames
+            .iter()
+            .position(|f| f.timestamp_micros >= timestamp_micros);
+
+        let (before, after) = match after_index {
+            Some(0) => return self.frames[0].players.clone(),
+            Some(i) => (&self.frames[i - 1], &self.frames[i]),
+            None => {
+                return self
+                    .frames
+                    .back()
+                    .map(|f| f.players.clone())
+                    .unwrap_or_default();
+            }
+        };
+
+        let span = (after.timestamp_micros - before.timestamp_micros) as f32;
+        let t = if span > 0.0 {
+            (timestamp_micros - before.timestamp_micros) as f32 / span
+        } else {
+            1.0
+        };
+
+        // Players that only exist in one of the frames are taken as-is
+        let mut players = before.players.clone();
+        for (id, new) in &after.players {
+            let snapshot = match before.players.get(id) {
+                Some(old) => PlayerSnapshot {
+                    pos: old.pos + (new.pos - old.pos) * t,
+                    size: new.size,
+                },
+                None => *new,
+            };
+            players.insert(*id, snapshot);
+        }
+        players
+    }
+}
+
+fn overlaps(rect1: &SceneObject, rect2: &SceneObject) -> bool {
+    rect1.x < rect2.x + rect2.w
+        && rect1.x + rect1.w > rect2.x
+        && rect1.y < rect2.y + rect2.h
+        && rect1.y + rect1.h > rect2.y
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::{PlayerState, SpawnPoint};
+
+    fn create_test_state() -> GameState {
+        GameState::empty(800.0, 600.0)
+    }
+
+    fn set_player(state: &mut GameState, id: u32, x: f32) {
+        let mut player = PlayerState::new(id, &SpawnPoint { x, y: 100.0 });
+        player.size = 16.0;
+        state.players.insert(id, player);
+    }
+
+    /// Player 1 stands still at x = 0, player 2 runs 100px per 100ms tick.
+    fn create_history(ticks: u32, max_rewind: Duration) -> StateHistory {
+        let mut history = StateHistory::new(max_rewind);
+        let mut state = create_test_state();
+        for tick in 0..ticks {
+            set_player(&mut state, 1, 0.0);
+            set_player(&mut state, 2, tick as f32 * 100.0);
+            history.record(tick, tick as u64 * 100_000, &state);
+        }
+        history
+    }
+
+    #[test]
+    fn test_rewind_interpolates_other_players() {
+        let history = create_history(10, Duration::from_secs(1));
+
+        let rewound = history
+            .rewind(5, 1, Duration::from_millis(150))
+            .expect("Tick 5 should be in the window");
+
+        // Tick 5 is at 500ms, so player 2 is seen at 350ms, halfway between tick 3 and 4
+        let other = rewound.players.get(&2).unwrap();
+        assert!((other.pos.x - 350.0).abs() < 0.01);
+        assert_eq!(rewound.timestamp_micros, 350_000);
+    }
+
+    #[test]
+    fn test_viewer_uses_own_position_at_tick() {
+        let history = create_history(10, Duration::from_secs(1));
+
+        let rewound = history.rewind(5, 2, Duration::from_millis(150)).unwrap();
+
+        let viewer = rewound.players.get(&2).unwrap();
+        assert!((viewer.pos.x - 500.0).abs() < 0.01);
+    }
+
+    #[test]
+    fn test_rewind_is_clamped_to_window() {
+        let history = create_history(10, Duration::from_millis(300));
+
+        // Only ticks 6..=9 survive a 300ms window ending at 900ms
+        assert!(history.frame(5).is_none());
+        assert!(history.rewind(5, 1, Duration::ZERO).is_none());
+
+        let rewound = history.rewind(7, 1, Duration::from_secs(5)).unwrap();
+        let other = rewound.players.get(&2).unwrap();
+        assert!((other.pos.x - 600.0).abs() < 0.01);
+    }
+
+    #[test]
+    fn test_query_as_seen_by() {
+        let history = create_history(10, Duration::from_secs(1));
+        let probe = SceneObject {
+            x: 352.0,
+            y: 100.0,
+            w: 4.0,
+            h: 4.0,
+            kind: CollidableKind::Solid,
+        };
+
+        let hits = history
+            .query_as_seen_by(5, 1, Duration::from_millis(150), |state| {
+                state.players_overlapping(&probe, 1)
+            })
+            .unwrap();
+        assert_eq!(hits, vec![2]);
+
+        let hits_without_delay = history
+            .query_as_seen_by(5, 1, Duration::ZERO, |state| {
+                state.players_overlapping(&probe, 1)
+            })
+            .unwrap();
+        assert!(hits_without_delay.is_empty());
+    }
+}
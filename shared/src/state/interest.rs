@@ -0,0 +1,273 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use super::GameState;
+
+/// Tuning for per-client relevancy filtering.
+#[derive(Debug, Clone)]
+pub struct InterestConfig {
+    /// Players closer than this become relevant to a client
+    pub radius: f32,
+    /// Extra distance a relevant player may move away before being dropped, so players
+    /// hovering around `radius` don't pop in and out every tick
+    pub hysteresis: f32,
+    /// Relevant players inside this distance are sent every tick
+    pub near_radius: f32,
+    /// Relevant players beyond `near_radius` are only sent every n-th tick
+    pub far_update_interval: u32,
+}
+
+impl Default for InterestConfig {
+    fn default() -> Self {
+        // The client shows a 640x360 viewport, so anything within ~370px of the player
+        // can be on screen
+        InterestConfig {
+            radius: 600.0,
+            hysteresis: 100.0,
+            near_radius: 400.0,
+            far_update_interval: 3,
+        }
+    }
+}
+
+/// Which players to put in a client's next snapshot.
+#[derive(Debug, Default, PartialEq)]
+pub struct InterestUpdate {
+    /// Players to send this tick, sorted by id
+    pub send: Vec<u32>,
+    /// Players the client should forget about, sorted by id. Repeated every tick until the
+    /// client acks a snapshot that carried them, since a lost removal leaves a ghost behind.
+    pub removed: Vec<u32>,
+}
+
+/// Tracks, per client, which other players are currently relevant.
+pub struct InterestManager {
+    config: InterestConfig,
+    relevant: HashMap<u32, HashSet<u32>>,
+    /// Per client, removed players the client hasn't acked yet with the tick they were
+    /// first sent in
+    unacked_removals: HashMap<u32, BTreeMap<u32, u32>>,
+}
+
+impl InterestManager {
+    pub fn new(config: InterestConfig) -> Self {
+        InterestManager {
+            config,
+            relevant: HashMap::new(),
+            unacked_removals: HashMap::new(),
+        }
+    }
+
+    /// Decide which players `client_id` should receive this tick
+    pub fn update(&mut self, state: &GameState, client_id: u32, tick: u32) -> InterestUpdate {
+        let Some(client_player) = state.players.get(&client_id) else {
+            return InterestUpdate::default();
+        };
+        let center_x = client_player.pos.x + client_player.size / 2.0;
+        let center_y = client_player.pos.y + client_player.size / 2.0;
+
+        let relevant = self.relevant.entry(client_id).or_default();
+        let unacked_removals = self.unacked_removals.entry(client_id).or_default();
+        let mut update = InterestUpdate::default();
+
+        let player_ids: Vec<u32> = state
+            .players
+            .keys()
+            .copied()
+            .filter(|id| *id != client_id)
+            .collect();
+
+        for id in player_ids {
+            let player = &state.players[&id];
+            let dx = player.pos.x + player.size / 2.0 - center_x;
+            let dy = player.pos.y + player.size / 2.0 - center_y;
+            let distance = (dx * dx + dy * dy).sqrt();
+
+            let was_relevant = relevant.contains(&id);
+            let is_relevant = if was_relevant {
+                distance <= self.config.radius + self.config.hysteresis
+            } else {
+                distance <= self.config.radius
+            };
+
+            if !is_relevant {
+                if was_relevant {
+                    relevant.remove(&id);
+                    unacked_removals.insert(id, tick);
+                }
+                continue;
+            }
+            if !was_relevant {
+                unacked_removals.remove(&id);
+            }
+
+            // Spread far updates over ticks by offsetting with the player id
+            let interval = self.config.far_update_interval.max(1);
+            let due = (tick.wrapping_add(id)) % interval == 0;
+            if !was_relevant || distance <= self.config.near_radius || due {
+                update.send.push(id);
+            }
+            relevant.insert(id);
+        }
+
+        // Players that left the game
+        let gone: Vec<u32> = relevant
+            .iter()
+            .copied()
+            .filter(|id| !state.players.contains_key(id))
+            .collect();
+        for id in gone {
+            relevant.remove(&id);
+            unacked_removals.insert(id, tick);
+        }
+        update.removed = unacked_removals.keys().copied().collect();
+
+        update
+    }
+
+    /// The client received the snapshot of `tick`, so removals sent up to then arrived
+    pub fn acknowledge(&mut self, client_id: u32, tick: u32) {
+        if let Some(unacked_removals) = self.unacked_removals.get_mut(&client_id) {
+            unacked_removals.retain(|_, removed_at| *removed_at > tick);
+        }
+    }
+
+    pub fn remove_client(&mut self, client_id: u32) {
+        self.relevant.remove(&client_id);
+        self.unacked_removals.remove(&client_id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::{PlayerState, SpawnPoint};
+
+    fn create_test_state() -> GameState {
+        GameState::empty(3000.0, 600.0)
+    }
+
+    fn set_player(state: &mut GameState, id: u32, x: f32) {
+        let player = PlayerState::new(id, &SpawnPoint { x, y: 100.0 });
+        state.players.insert(id, player);
+    }
+
+    fn create_manager() -> InterestManager {
+        InterestManager::new(InterestConfig {
+            radius: 500.0,
+            hysteresis: 100.0,
+            near_radius: 200.0,
+            far_update_interval: 4,
+        })
+    }
+
+    #[test]
+    fn test_only_nearby_players_are_sent() {
+        let mut state = create_test_state();
+        set_player(&mut state, 1, 0.0);
+        set_player(&mut state, 2, 100.0);
+        set_player(&mut state, 3, 2000.0);
+        let mut interest = create_manager();
+
+        let update = interest.update(&state, 1, 0);
+
+        assert_eq!(update.send, vec![2]);
+        assert!(update.removed.is_empty());
+    }
+
+    #[test]
+    fn test_hysteresis_keeps_player_relevant() {
+        let mut state = create_test_state();
+        set_player(&mut state, 1, 0.0);
+        set_player(&mut state, 2, 450.0);
+        let mut interest = create_manager();
+        assert_eq!(interest.update(&state, 1, 0).send, vec![2]);
+
+        // Inside radius + hysteresis: still relevant, but not yet due for a far update
+        set_player(&mut state, 2, 550.0);
+        let update = interest.update(&state, 1, 1);
+        assert!(update.send.is_empty());
+        assert!(update.removed.is_empty());
+
+        // Beyond radius + hysteresis: dropped
+        set_player(&mut state, 2, 650.0);
+        let update = interest.update(&state, 1, 2);
+        assert_eq!(update.removed, vec![2]);
+        interest.acknowledge(1, 2);
+
+        // Has to come back inside radius (not radius + hysteresis) to be relevant again
+        set_player(&mut state, 2, 550.0);
+        let update = interest.update(&state, 1, 3);
+        assert!(update.send.is_empty());
+        assert!(update.removed.is_empty());
+    }
+
+    #[test]
+    fn test_far_players_are_sent_less_often() {
+        let mut state = create_test_state();
+        set_player(&mut state, 1, 0.0);
+        set_player(&mut state, 2, 100.0);
+        set_player(&mut state, 3, 400.0);
+        let mut interest = create_manager();
+
+        let mut near_sends = 0;
+        let mut far_sends = 0;
+        for tick in 0..40 {
+            let update = interest.update(&state, 1, tick);
+            near_sends += update.send.iter().filter(|id| **id == 2).count();
+            far_sends += update.send.iter().filter(|id| **id == 3).count();
+        }
+
+        assert_eq!(near_sends, 40);
+        // First tick (newly relevant) plus every fourth tick after that
+        assert_eq!(far_sends, 11);
+    }
+
+    #[test]
+    fn test_left_players_are_removed() {
+        let mut state = create_test_state();
+        set_player(&mut state, 1, 0.0);
+        set_player(&mut state, 2, 100.0);
+        let mut interest = create_manager();
+        interest.update(&state, 1, 0);
+
+        state.players.remove(&2);
+        let update = interest.update(&state, 1, 1);
+
+        assert_eq!(update.removed, vec![2]);
+    }
+
+    #[test]
+    fn test_removals_repeat_until_acked() {
+        let mut state = create_test_state();
+        set_player(&mut state, 1, 0.0);
+        set_player(&mut state, 2, 100.0);
+        let mut interest = create_manager();
+        interest.update(&state, 1, 0);
+        set_player(&mut state, 2, 2000.0);
+        assert_eq!(interest.update(&state, 1, 1).removed, vec![2]);
+
+        // The snapshot of tick 1 was lost
+        interest.acknowledge(1, 0);
+        assert_eq!(interest.update(&state, 1, 2).removed, vec![2]);
+
+        interest.acknowledge(1, 2);
+        assert!(interest.update(&state, 1, 3).removed.is_empty());
+    }
+
+    #[test]
+    fn test_returning_player_is_no_longer_removed() {
+        let mut state = create_test_state();
+        set_player(&mut state, 1, 0.0);
+        set_player(&mut state, 2, 100.0);
+        let mut interest = create_manager();
+        interest.update(&state, 1, 0);
+        set_player(&mut state, 2, 2000.0);
+        interest.update(&state, 1, 1);
+
+        set_player(&mut state, 2, 100.0);
+        let update = interest.update(&state, 1, 2);
+
+        assert_eq!(update.send, vec![2]);
+        assert!(update.removed.is_empty());
+    }
+}
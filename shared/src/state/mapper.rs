@@ -1,10 +1,75 @@
 use crate::generated;
 use flatbuffers::{FlatBufferBuilder, WIPOffset, root};
-use std::collections::HashMap;
+use std::collections::BTreeMap;
 
-use super::{GameState, PlayerState, PlayerStateCommand, Vec2};
+use super::checksum::player_checksum;
+use super::compact::{Encoding, dequantize_position, quantize_position};
+use super::{
+    GameState, PhysicsParams, PlayerInput, PlayerState, PlayerStateCommand, RaceEvent,
+    RaceEventKind, Vec2,
+};
 
-const SCENE_NAME: &str = "scene_3";
+// Root table, its vtable and vector length prefixes
+const SNAPSHOT_OVERHEAD_BYTES: usize = 48;
+/// `PlayerCommands::acked_tick` of a client that hasn't received a snapshot yet
+const NO_ACKED_TICK: u32 = u32::MAX;
+
+/// Who a snapshot is for and what it acks, everything but the players it carries
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SendContext<'a> {
+    pub client_player_id: u32,
+    /// Newest command sequence of the client the server applied
+    pub sequence: u32,
+    /// How long the server held the acked command
+    pub ack_hold_micros: u64,
+    pub server_timestamp: u64,
+    pub encoding: Encoding<'a>,
+    /// Add the scene's physics, which clients need once after joining
+    pub send_physics_params: bool,
+}
+
+/// A decoded snapshot, as sent to one client. Only players and round state, the scene is
+/// loaded by the client itself.
+pub struct Snapshot {
+    pub tick: u32,
+    pub physics_step: u32,
+    /// Everyone but the client player
+    pub players: BTreeMap<u32, PlayerState>,
+    pub race_events: Vec<RaceEvent>,
+    pub client_player: PlayerState,
+    /// Last command sequence the server applied for this client
+    pub sequence: u32,
+    pub server_timestamp: u64,
+    pub removed_players: Vec<u32>,
+    /// Checksum of the client player right after the acked command was applied
+    pub client_player_checksum: u32,
+    /// The scene physics, if the server sent them with this snapshot
+    pub physics_params: Option<PhysicsParams>,
+    /// How long the server held the acked command before applying it
+    pub ack_hold_micros: u64,
+    /// Physics steps the server simulated after applying the acked command
+    pub ack_steps: u32,
+}
+
+/// What the network layer reads from a snapshot without decoding it
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SnapshotHeader {
+    pub tick: u32,
+    pub sequence: u32,
+    pub ack_hold_micros: u64,
+}
+
+impl SnapshotHeader {
+    /// `None` if `packet` isn't a snapshot
+    pub fn read(packet: &[u8]) -> Option<SnapshotHeader> {
+        let snapshot = root::<generated::GameState>(packet).ok()?;
+        Some(SnapshotHeader {
+            tick: snapshot.tick(),
+            sequence: snapshot.sequence(),
+            ack_hold_micros: snapshot.ack_hold_micros(),
+        })
+    }
+}
 
 impl GameState {
     pub fn serialize<'a>(
@@ -14,38 +79,169 @@ impl GameState {
         sequence: u32,
         server_timestamp: u64,
     ) -> &'a [u8] {
+        let player_ids: Vec<u32> = self
+            .players
+            .keys()
+            .copied()
+            .filter(|id| *id != client_player_id)
+            .collect();
+        let context = SendContext {
+            client_player_id,
+            sequence,
+            server_timestamp,
+            send_physics_params: true,
+            ..Default::default()
+        };
+        self.serialize_players(builder, &context, &player_ids, &[])
+    }
+
+    /// Serialize a snapshot for `context` that only contains `player_ids` and tells the
+    /// client to drop `removed_player_ids`
+    pub fn serialize_players<'a>(
+        &self,
+        builder: &'a mut FlatBufferBuilder,
+        context: &SendContext,
+        player_ids: &[u32],
+        removed_player_ids: &[u32],
+    ) -> &'a [u8] {
+        let SendContext {
+            client_player_id,
+            sequence,
+            ack_hold_micros,
+            server_timestamp,
+            encoding,
+            send_physics_params,
+        } = *context;
         let client_player = self
             .players
             .get(&client_player_id)
             .expect("Game state should always contain the client player");
-        let players_offsets: Vec<_> = self
-            .players
+        let players_offsets: Vec<_> = player_ids
             .iter()
-            .filter(|p| *p.0 != client_player_id)
-            .map(|(&_, player_state)| player_state.offset_player(builder))
+            .filter(|id| **id != client_player_id)
+            .filter_map(|id| self.players.get(id))
+            .map(|player_state| {
+                player_state.offset_player(builder, encoding, self.width, self.height)
+            })
             .collect();
 
         let players_vec = builder.create_vector(&players_offsets);
+        let removed_players_vec = builder.create_vector(removed_player_ids);
+        let race_event_offsets: Vec<_> = self
+            .race_events
+            .iter()
+            .map(|event| event.offset(builder))
+            .collect();
+        let race_events_vec = builder.create_vector(&race_event_offsets);
+        let physics_params_offset =
+            send_physics_params.then(|| self.physics_params.offset(builder));
 
-        let client_player_offset = client_player.offset_client_player(builder);
+        let (client_player_checksum, ack_steps) = self.acked_command(client_player, sequence);
+        let client_player_offset = client_player.offset_client_player(builder, encoding);
         let players_list = generated::GameState::create(
             builder,
             &generated::GameStateArgs {
                 players: Some(players_vec),
                 client_player: Some(client_player_offset),
                 sequence,
-                server_timestamp: server_timestamp,
+                server_timestamp,
+                removed_players: Some(removed_players_vec),
+                tick: self.tick,
+                client_player_checksum,
+                physics_step: self.physics_step,
+                race_events: Some(race_events_vec),
+                physics_params: physics_params_offset,
+                ack_hold_micros,
+                ack_steps,
             },
         );
         builder.finish(players_list, None);
-        let bytes = builder.finished_data();
-        bytes
+        builder.finished_data()
+    }
+
+    /// Checksum of the client player as it was when command `sequence` was applied and the
+    /// physics steps simulated since, or its checksum now when that command wasn't applied
+    fn acked_command(&self, client_player: &PlayerState, sequence: u32) -> (u32, u32) {
+        match self.applied_commands.get(&client_player.id) {
+            Some(applied) if applied.sequence == sequence => (
+                applied.checksum,
+                self.physics_step.wrapping_sub(applied.physics_step),
+            ),
+            _ => (player_checksum(client_player), 0),
+        }
+    }
+
+    /// Serialize as many of `ranked_player_ids` (most important first) as fit in
+    /// `byte_budget` bytes. The client player and removals are always included.
+    /// Returns how many of the ranked players made it into the snapshot.
+    pub fn serialize_within_budget(
+        &self,
+        builder: &mut FlatBufferBuilder,
+        context: &SendContext,
+        ranked_player_ids: &[u32],
+        removed_player_ids: &[u32],
+        byte_budget: usize,
+    ) -> usize {
+        let SendContext {
+            client_player_id,
+            encoding,
+            send_physics_params,
+            ..
+        } = *context;
+        // Estimate how many fit by measuring each player table as it is written, leaving room
+        // for its vector slot
+        builder.reset();
+        builder.create_vector(removed_player_ids);
+        for event in &self.race_events {
+            event.offset(builder);
+        }
+        if send_physics_params {
+            self.physics_params.offset(builder);
+        }
+        if let Some(client_player) = self.players.get(&client_player_id) {
+            client_player.offset_client_player(builder, encoding);
+        }
+        let mut count = 0;
+        for id in ranked_player_ids {
+            let Some(player) = self.players.get(id) else {
+                break;
+            };
+            player.offset_player(builder, encoding, self.width, self.height);
+            let estimate = builder.unfinished_data().len()
+                + (count + 1) * size_of::<u32>()
+                + SNAPSHOT_OVERHEAD_BYTES;
+            if estimate > byte_budget {
+                break;
+            }
+            count += 1;
+        }
+
+        // Write the real snapshot, backing off if the estimate was optimistic
+        loop {
+            builder.reset();
+            let len = self
+                .serialize_players(
+                    builder,
+                    context,
+                    &ranked_player_ids[..count],
+                    removed_player_ids,
+                )
+                .len();
+            if len <= byte_budget || count == 0 {
+                return count;
+            }
+            count -= 1;
+        }
     }
 
-    pub fn deserialize(packet: &[u8]) -> (GameState, PlayerState, u32, u64) {
+    /// Decode a snapshot the server sent for this scene
+    pub fn deserialize(&self, packet: &[u8]) -> Snapshot {
         let game_state_packet = root::<generated::GameState>(packet).expect("No state received.");
+        let (width, height) = (self.width, self.height);
+        let spawn_point = Vec2::new(self.spawn_point.x, self.spawn_point.y);
 
-        let players: HashMap<u32, PlayerState> = game_state_packet
+        // Compact snapshots leave out the float fields and only name new players
+        let players: BTreeMap<u32, PlayerState> = game_state_packet
             .players()
             .expect("Should have players array")
             .into_iter()
@@ -54,11 +250,25 @@ impl GameState {
                     p.id(),
                     PlayerState {
                         id: p.id(),
-                        name: p.name().unwrap().to_string(),
-                        pos: p.pos().unwrap().to_owned().into(),
+                        name: p.name().unwrap_or_default().to_string(),
+                        pos: match (p.pos(), p.qpos()) {
+                            (Some(pos), _) => pos.to_owned().into(),
+                            (None, Some(qpos)) => dequantize_position(qpos, width, height),
+                            (None, None) => Vec2::ZERO,
+                        },
                         vel: Vec2::ZERO,
                         grounded: false,
                         jump_timer: 0.,
+                        drop_timer: 0.,
+                        respawn_point: Vec2::ZERO,
+                        is_it: p.is_it(),
+                        facing: 1.0,
+                        wall_dir: 0.0,
+                        air_jumps: 0,
+                        dash_timer: 0.,
+                        input: PlayerInput::default(),
+                        prev_input: PlayerInput::default(),
+                        pending_press: PlayerInput::default(),
                         color: p.color(),
                         size: p.size(),
                     },
@@ -70,26 +280,163 @@ impl GameState {
             .client_player()
             .map(|p| PlayerState {
                 id: p.id(),
-                name: p.name().unwrap().to_string(),
-                pos: p.pos().unwrap().to_owned().into(),
-                vel: p.vel().unwrap().to_owned().into(),
+                name: p.name().unwrap_or_default().to_string(),
+                pos: p
+                    .pos()
+                    .map(|pos| pos.to_owned().into())
+                    .unwrap_or(Vec2::ZERO),
+                vel: p
+                    .vel()
+                    .map(|vel| vel.to_owned().into())
+                    .unwrap_or(Vec2::ZERO),
                 grounded: p.grounded(),
                 jump_timer: p.jump_timer(),
+                drop_timer: p.drop_timer(),
+                respawn_point: p
+                    .respawn_point()
+                    .map(|point| point.to_owned().into())
+                    .unwrap_or(spawn_point),
+                is_it: p.is_it(),
+                facing: p.facing(),
+                wall_dir: p.wall_dir(),
+                air_jumps: p.air_jumps(),
+                dash_timer: p.dash_timer(),
+                input: PlayerInput {
+                    axis: p.input_axis(),
+                    jump: p.input_jump(),
+                    down: p.input_down(),
+                    dash: p.input_dash(),
+                },
+                prev_input: PlayerInput {
+                    axis: p.prev_input_axis(),
+                    jump: p.prev_input_jump(),
+                    down: p.prev_input_down(),
+                    dash: p.prev_input_dash(),
+                },
+                pending_press: PlayerInput {
+                    jump: p.pending_jump(),
+                    dash: p.pending_dash(),
+                    ..PlayerInput::default()
+                },
                 color: p.color(),
                 size: p.size(),
             })
             .expect("Should have client player");
 
-        let mut game_state = GameState::new(SCENE_NAME);
-        game_state.players = players;
+        let removed_players: Vec<u32> = game_state_packet
+            .removed_players()
+            .map(|ids| ids.into_iter().collect())
+            .unwrap_or_default();
 
-        (
-            game_state,
+        Snapshot {
+            tick: game_state_packet.tick(),
+            physics_step: game_state_packet.physics_step(),
+            players,
+            race_events: game_state_packet
+                .race_events()
+                .map(|events| events.iter().filter_map(RaceEvent::from_packet).collect())
+                .unwrap_or_default(),
             client_player,
-            game_state_packet.sequence(),
-            game_state_packet.server_timestamp(),
+            sequence: game_state_packet.sequence(),
+            server_timestamp: game_state_packet.server_timestamp(),
+            removed_players,
+            client_player_checksum: game_state_packet.client_player_checksum(),
+            physics_params: game_state_packet
+                .physics_params()
+                .map(PhysicsParams::from_packet),
+            ack_hold_micros: game_state_packet.ack_hold_micros(),
+            ack_steps: game_state_packet.ack_steps(),
+        }
+    }
+}
+
+impl PhysicsParams {
+    fn offset<'fbb>(
+        &self,
+        builder: &mut flatbuffers::FlatBufferBuilder<'fbb>,
+    ) -> WIPOffset<generated::PhysicsParams<'fbb>> {
+        generated::PhysicsParams::create(
+            builder,
+            &generated::PhysicsParamsArgs {
+                gravity: self.gravity,
+                jump_force: self.jump_force,
+                player_acceleration: self.player_acceleration,
+                ground_friction: self.ground_friction,
+                air_friction: self.air_friction,
+                jump_cd: self.jump_cd,
+            },
         )
     }
+
+    fn from_packet(params: generated::PhysicsParams) -> PhysicsParams {
+        PhysicsParams {
+            gravity: params.gravity(),
+            jump_force: params.jump_force(),
+            player_acceleration: params.player_acceleration(),
+            ground_friction: params.ground_friction(),
+            air_friction: params.air_friction(),
+            jump_cd: params.jump_cd(),
+        }
+    }
+}
+
+impl RaceEvent {
+    fn offset<'fbb>(
+        &self,
+        builder: &mut flatbuffers::FlatBufferBuilder<'fbb>,
+    ) -> WIPOffset<generated::RaceEvent<'fbb>> {
+        let mut args = generated::RaceEventArgs {
+            id: self.id,
+            round: self.round,
+            ..Default::default()
+        };
+        match self.kind {
+            RaceEventKind::Countdown { seconds } => {
+                args.kind = generated::RaceEventKind::Countdown;
+                args.seconds = seconds;
+            }
+            RaceEventKind::Started => args.kind = generated::RaceEventKind::Started,
+            RaceEventKind::Finished {
+                player_id,
+                place,
+                time,
+            } => {
+                args.kind = generated::RaceEventKind::Finished;
+                args.player_id = player_id;
+                args.place = place;
+                args.seconds = time;
+            }
+            RaceEventKind::Results { seconds } => {
+                args.kind = generated::RaceEventKind::Results;
+                args.seconds = seconds;
+            }
+        }
+        generated::RaceEvent::create(builder, &args)
+    }
+
+    /// `None` for event kinds this build doesn't know about
+    fn from_packet(event: generated::RaceEvent) -> Option<RaceEvent> {
+        let kind = match event.kind() {
+            generated::RaceEventKind::Countdown => RaceEventKind::Countdown {
+                seconds: event.seconds(),
+            },
+            generated::RaceEventKind::Started => RaceEventKind::Started,
+            generated::RaceEventKind::Finished => RaceEventKind::Finished {
+                player_id: event.player_id(),
+                place: event.place(),
+                time: event.seconds(),
+            },
+            generated::RaceEventKind::Results => RaceEventKind::Results {
+                seconds: event.seconds(),
+            },
+            _ => return None,
+        };
+        Some(RaceEvent {
+            id: event.id(),
+            round: event.round(),
+            kind,
+        })
+    }
 }
 
 impl From<generated::Vector2> for Vec2 {
@@ -105,38 +452,82 @@ impl PlayerState {
     pub fn offset_client_player<'fbb>(
         &self,
         builder: &mut flatbuffers::FlatBufferBuilder<'fbb>,
+        encoding: Encoding,
     ) -> WIPOffset<generated::ClientPlayer<'fbb>> {
-        let name_offset = builder.create_string(&self.name);
-        generated::ClientPlayer::create(
-            builder,
-            &generated::ClientPlayerArgs {
-                id: self.id,
-                name: Some(name_offset),
-                pos: Some(&generated::Vector2::new(self.pos.x, self.pos.y)),
-                vel: Some(&generated::Vector2::new(self.vel.x, self.vel.y)),
-                grounded: self.grounded,
-                jump_timer: self.jump_timer,
-                size: self.size,
-                color: self.color,
-            },
-        )
+        let name_offset = encoding
+            .includes_name(self.id)
+            .then(|| builder.create_string(&self.name));
+        let mut args = generated::ClientPlayerArgs {
+            id: self.id,
+            name: name_offset,
+            grounded: self.grounded,
+            jump_timer: self.jump_timer,
+            drop_timer: self.drop_timer,
+            is_it: self.is_it,
+            facing: self.facing,
+            wall_dir: self.wall_dir,
+            air_jumps: self.air_jumps,
+            dash_timer: self.dash_timer,
+            input_axis: self.input.axis,
+            input_jump: self.input.jump,
+            input_down: self.input.down,
+            input_dash: self.input.dash,
+            prev_input_axis: self.prev_input.axis,
+            prev_input_jump: self.prev_input.jump,
+            prev_input_down: self.prev_input.down,
+            prev_input_dash: self.prev_input.dash,
+            pending_jump: self.pending_press.jump,
+            pending_dash: self.pending_press.dash,
+            size: self.size,
+            color: self.color,
+            ..Default::default()
+        };
+
+        let respawn_point = generated::Vector2::new(self.respawn_point.x, self.respawn_point.y);
+        args.respawn_point = Some(&respawn_point);
+
+        // Never quantized, the client replays its prediction on top of it and a rounded
+        // position can end up on the other side of a wall
+        let pos = generated::Vector2::new(self.pos.x, self.pos.y);
+        let vel = generated::Vector2::new(self.vel.x, self.vel.y);
+        args.pos = Some(&pos);
+        args.vel = Some(&vel);
+
+        generated::ClientPlayer::create(builder, &args)
     }
 
     pub fn offset_player<'fbb>(
         &self,
         builder: &mut flatbuffers::FlatBufferBuilder<'fbb>,
+        encoding: Encoding,
+        width: f32,
+        height: f32,
     ) -> WIPOffset<generated::Player<'fbb>> {
-        let name_offset = builder.create_string(&self.name);
-        generated::Player::create(
-            builder,
-            &generated::PlayerArgs {
-                id: self.id,
-                name: Some(name_offset),
-                pos: Some(&generated::Vector2::new(self.pos.x, self.pos.y)),
-                size: self.size,
-                color: self.color,
-            },
-        )
+        let name_offset = encoding
+            .includes_name(self.id)
+            .then(|| builder.create_string(&self.name));
+        let mut args = generated::PlayerArgs {
+            id: self.id,
+            name: name_offset,
+            size: self.size,
+            color: self.color,
+            is_it: self.is_it,
+            ..Default::default()
+        };
+
+        let (pos, qpos);
+        match encoding {
+            Encoding::Full => {
+                pos = generated::Vector2::new(self.pos.x, self.pos.y);
+                args.pos = Some(&pos);
+            }
+            Encoding::Compact { .. } => {
+                qpos = quantize_position(self.pos, width, height);
+                args.qpos = Some(&qpos);
+            }
+        }
+
+        generated::Player::create(builder, &args)
     }
 }
 
@@ -153,6 +544,8 @@ impl PlayerStateCommand {
                 commands: Some(commands_vec),
                 dt_micro: self.dt_micros,
                 client_timestamp_micro: self.client_timestamp_micros,
+                move_axis: self.move_axis,
+                acked_tick: self.acked_tick.unwrap_or(NO_ACKED_TICK),
             },
         )
     }
@@ -174,6 +567,8 @@ impl PlayerStateCommand {
             commands,
             dt_micros: dt_micro,
             client_timestamp_micros: client_timestamp_micro,
+            move_axis: player_commands.move_axis(),
+            acked_tick: Some(player_commands.acked_tick()).filter(|tick| *tick != NO_ACKED_TICK),
         }
     }
 }
@@ -1,10 +1,38 @@
+mod broadphase;
+mod checksum;
+mod compact;
+mod history;
+mod interest;
 mod mapper;
+mod mode;
 mod mutate;
 mod physics;
+mod platforms;
+mod priority;
+mod race;
+mod tag;
+mod triggers;
+
+pub use broadphase::{CollisionGrid, GRID_CELL_SIZE, GridEntry};
+pub use checksum::{DesyncDetector, player_checksum};
+pub use compact::{Encoding, NAME_REPEATS, NameTracker, dequantize_axis, quantize_axis};
+pub use history::{HistoryFrame, PlayerSnapshot, RewoundState, StateHistory};
+pub use interest::{InterestConfig, InterestManager, InterestUpdate};
+pub use mapper::{SendContext, Snapshot, SnapshotHeader};
+pub use mode::{ClassicMode, GameMode, Touch, TouchTarget, game_mode_by_name};
+pub use mutate::AppliedCommand;
+pub use platforms::{MovingPlatform, PathMode};
+pub use priority::{PriorityConfig, SnapshotPrioritizer};
+pub use race::{
+    COUNTDOWN_SECS, FINISH_GRACE_SECS, RESULTS_SECS, Race, RaceEvent, RaceEventKind, RacePhase,
+    RaceResult, RaceView,
+};
+pub use tag::{TAG_IMMUNITY_SECS, TAG_RESULTS_SECS, TAG_ROUND_SECS, Tag};
+pub use triggers::{Trigger, TriggerKind};
 
 use crate::generated::{self, Color};
 use serde::Deserialize;
-use std::collections::{BinaryHeap, HashMap};
+use std::collections::{BTreeMap, BinaryHeap};
 use std::fmt::Display;
 use std::fs::File;
 use std::ops::{Add, Mul, Sub};
@@ -15,15 +43,25 @@ pub const SCREEN_WIDTH: usize = 640;
 
 // Player
 pub const JUMP_CD: f32 = 0.3;
+pub const DROP_THROUGH_TIME: f32 = 0.25;
+/// Max fall speed while sliding down a wall, in px/s
+pub const WALL_SLIDE_SPEED: f32 = 150.0;
+/// Horizontal speed a wall jump pushes the player away from the wall with
+pub const WALL_JUMP_PUSH: f32 = 450.0;
+pub const DASH_SPEED: f32 = 1200.0;
+pub const DASH_CD: f32 = 0.8;
 
-// Physics
+// Physics defaults, a scene can override them in its `physics` object
 pub const GROUND_FRICTION: f32 = 0.0001;
-pub const AIR_FRICTION: f32 = 0.9;
+/// Players slowed down in the air like on the ground before scenes could set the two apart.
+/// The old 0.9 was never read, as friction it would let players gain speed without bound
+/// while airborne.
+pub const AIR_FRICTION: f32 = GROUND_FRICTION;
 pub const GRAVITY: f32 = 2200.0;
 pub const JUMP_FORCE: f32 = 800.0;
-pub const PLAYER_ACCELERATION: f32 = 3.0;
+pub const PLAYER_ACCELERATION: f32 = 3000.0;
 
-#[derive(Clone, Copy)]
+#[derive(Debug, Deserialize, Clone, Copy)]
 pub struct Vec2 {
     pub x: f32,
     pub y: f32,
@@ -76,6 +114,17 @@ impl Mul<f32> for Vec2 {
     }
 }
 
+/// Buttons a player holds. Set by the player's commands and applied on every physics step
+/// until the next command, so movement doesn't depend on how often the client sends input.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct PlayerInput {
+    /// -1 is full left, 1 full right
+    pub axis: f32,
+    pub jump: bool,
+    pub down: bool,
+    pub dash: bool,
+}
+
 #[derive(Clone)]
 pub struct PlayerState {
     pub id: u32,
@@ -84,16 +133,95 @@ pub struct PlayerState {
     pub vel: Vec2,
     pub grounded: bool,
     pub jump_timer: f32,
+    /// Seconds left of falling through one-way platforms after pressing down
+    pub drop_timer: f32,
+    /// Where the player comes back after a hazard, the spawn point until a checkpoint
+    pub respawn_point: Vec2,
+    /// Whether the player is "it" in tag, set by the server's game mode
+    pub is_it: bool,
+    /// 1 facing right, -1 facing left, the direction of the last horizontal input
+    pub facing: f32,
+    /// 1 touching a wall on the right, -1 on the left, 0 not touching one
+    pub wall_dir: f32,
+    /// Jumps used since the player last stood on something
+    pub air_jumps: u32,
+    pub dash_timer: f32,
+    pub input: PlayerInput,
+    /// Input held on the previous physics step. Jump and dash only fire on a new press.
+    pub prev_input: PlayerInput,
+    /// Jump and dash pressed since the last physics step. They fire on the next step even
+    /// when released before it.
+    pub pending_press: PlayerInput,
     pub color: Color,
     pub size: f32,
 }
 
+/// How a collidable reacts to players. Scene JSON uses `"kind": "one_way"` or
+/// `"kind": { "slope": { "left": 0, "right": 40 } }`; leaving it out gives a solid box.
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum CollidableKind {
+    #[default]
+    Solid,
+    /// Only the top is solid: players jump through from below and drop through with down
+    OneWay,
+    /// Walkable surface going from `left` px above the bottom of the box at its left edge
+    /// to `right` px above the bottom at its right edge
+    Slope { left: f32, right: f32 },
+}
+
+/// Movement abilities a scene enables on top of running and jumping. All are off unless
+/// the scene has an `abilities` object.
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq, Default)]
+#[serde(default)]
+pub struct Abilities {
+    /// Slide slowly down walls and jump off them
+    pub wall_jump: bool,
+    /// Extra jumps in the air before landing again
+    pub air_jumps: u32,
+    pub dash: bool,
+}
+
+/// Tuning the simulation runs with. Server and clients must use the same values or
+/// prediction drifts, so the server sends them to clients when they join.
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq)]
+#[serde(default)]
+pub struct PhysicsParams {
+    /// Downward acceleration in px/s²
+    pub gravity: f32,
+    /// Upward speed of a jump in px/s
+    pub jump_force: f32,
+    /// Acceleration while holding left or right, in px/s²
+    pub player_acceleration: f32,
+    /// Fraction of horizontal speed kept after one second on the ground
+    pub ground_friction: f32,
+    /// Fraction of horizontal speed kept after one second in the air
+    pub air_friction: f32,
+    /// Seconds between jumps
+    pub jump_cd: f32,
+}
+
+impl Default for PhysicsParams {
+    fn default() -> Self {
+        PhysicsParams {
+            gravity: GRAVITY,
+            jump_force: JUMP_FORCE,
+            player_acceleration: PLAYER_ACCELERATION,
+            ground_friction: GROUND_FRICTION,
+            air_friction: AIR_FRICTION,
+            jump_cd: JUMP_CD,
+        }
+    }
+}
+
 #[derive(Debug, Deserialize, Clone)]
 pub struct SceneObject {
     pub x: f32,
     pub y: f32,
     pub w: f32,
     pub h: f32,
+    #[serde(default)]
+    pub kind: CollidableKind,
 }
 
 #[derive(Debug, Deserialize, Clone)]
@@ -104,15 +232,24 @@ pub struct SpawnPoint {
 
 #[derive(Debug, Deserialize)]
 struct Scene {
-    collidables: HashMap<u32, SceneObject>,
+    /// Ordered by scene key, collisions are resolved in this order
+    collidables: BTreeMap<u32, SceneObject>,
     width: f32,
     height: f32,
     spawn_point: SpawnPoint,
     win_point: SceneObject,
+    #[serde(default)]
+    moving_platforms: BTreeMap<u32, MovingPlatform>,
+    #[serde(default)]
+    triggers: BTreeMap<u32, Trigger>,
+    #[serde(default)]
+    abilities: Abilities,
+    #[serde(default)]
+    physics: PhysicsParams,
 }
 
 impl PlayerState {
-    fn new(id: u32, spawn_point: &SpawnPoint) -> PlayerState {
+    pub fn new(id: u32, spawn_point: &SpawnPoint) -> PlayerState {
         PlayerState {
             id,
             name: "player".to_string(),
@@ -120,6 +257,16 @@ impl PlayerState {
             vel: Vec2::ZERO,
             grounded: false,
             jump_timer: 0.0,
+            drop_timer: 0.0,
+            respawn_point: Vec2::new(spawn_point.x, spawn_point.y),
+            is_it: false,
+            facing: 1.0,
+            wall_dir: 0.0,
+            air_jumps: 0,
+            dash_timer: 0.0,
+            input: PlayerInput::default(),
+            prev_input: PlayerInput::default(),
+            pending_press: PlayerInput::default(),
             color: Color::Red,
             size: 16.0,
         }
@@ -133,18 +280,44 @@ pub struct PlayerStateCommand {
     // Mutliple commands because the player can for example jump and move in the same frame
     pub commands: Vec<generated::PlayerCommand>,
     pub client_timestamp_micros: u64,
+    /// Quantized analog horizontal input, see `quantize_axis`. Overrides the direction of
+    /// `MoveLeft`/`MoveRight` when not 0.
+    pub move_axis: i8,
+    /// Tick of the newest snapshot the client received, so the server knows what arrived
+    pub acked_tick: Option<u32>,
 }
 
 #[derive(Clone)]
 pub struct GameState {
-    pub players: HashMap<u32, PlayerState>,
+    /// Ordered by id, so every simulation of a state visits players in the same order
+    pub players: BTreeMap<u32, PlayerState>,
     pub collidables: Vec<SceneObject>,
     pub width: f32,
     pub height: f32,
     pub spawn_point: SpawnPoint,
     pub win_point: SceneObject,
+    /// Ordered by scene key
+    pub triggers: Vec<Trigger>,
+    /// Broadphase over `collidables`, `triggers` and `win_point`, see `rebuild_collision_grid`
+    pub collision_grid: CollisionGrid,
+    /// Ordered by scene key
+    pub moving_platforms: Vec<MovingPlatform>,
+    pub abilities: Abilities,
+    pub physics_params: PhysicsParams,
+    /// Fixed physics steps simulated so far, moving platforms are positioned from it
+    pub physics_step: u32,
     pub cached_dt_micros: u64,
     pub scheduled_commands: BinaryHeap<mutate::ScheduledCommand>,
+    /// Per player, the newest command applied. Snapshots report the player's checksum at
+    /// that moment, the one the client hashed its prediction at.
+    pub applied_commands: BTreeMap<u32, AppliedCommand>,
+    /// Server tick the state belongs to, also used by clients to spot lost snapshots
+    pub tick: u32,
+    /// Win point, trigger and player contacts in the server's simulation, in the order they
+    /// happened. Drained every tick by the server and handed to its `GameMode`.
+    pub touches: Vec<Touch>,
+    /// Race events sent with (or received in) the snapshot of this state
+    pub race_events: Vec<RaceEvent>,
 }
 
 #[derive(Clone)]
@@ -163,20 +336,109 @@ impl GameState {
         let collidables: Vec<SceneObject> = scene.collidables.into_values().collect();
         let spawn_point: SpawnPoint = scene.spawn_point.clone();
         let win_point: SceneObject = scene.win_point.clone();
+        let triggers: Vec<Trigger> = scene.triggers.into_values().collect();
+        let collision_grid = CollisionGrid::new(&collidables, &triggers, &win_point);
 
         GameState {
-            players: HashMap::new(),
+            players: BTreeMap::new(),
             collidables,
             width: scene.width,
             height: scene.height,
             spawn_point,
             win_point,
+            triggers,
+            collision_grid,
+            moving_platforms: scene.moving_platforms.into_values().collect(),
+            abilities: scene.abilities,
+            physics_params: scene.physics,
+            physics_step: 0,
             cached_dt_micros: 0,
             scheduled_commands: BinaryHeap::new(),
+            applied_commands: BTreeMap::new(),
+            tick: 0,
+            touches: Vec::new(),
+            race_events: Vec::new(),
         }
     }
 
+    /// A `width` by `height` level with nothing in it but a 50px win point in the top right
+    /// corner, for tests and benchmarks that build their own scene
+    pub fn empty(width: f32, height: f32) -> GameState {
+        let mut state = GameState {
+            players: BTreeMap::new(),
+            collidables: Vec::new(),
+            width,
+            height,
+            spawn_point: SpawnPoint { x: 0.0, y: 0.0 },
+            win_point: SceneObject {
+                x: width - 50.0,
+                y: 50.0,
+                w: 50.0,
+                h: 50.0,
+                kind: CollidableKind::Solid,
+            },
+            triggers: Vec::new(),
+            collision_grid: CollisionGrid::default(),
+            moving_platforms: Vec::new(),
+            abilities: Abilities::default(),
+            physics_params: PhysicsParams::default(),
+            physics_step: 0,
+            cached_dt_micros: 0,
+            scheduled_commands: BinaryHeap::new(),
+            applied_commands: BTreeMap::new(),
+            tick: 0,
+            touches: Vec::new(),
+            race_events: Vec::new(),
+        };
+        state.rebuild_collision_grid();
+        state
+    }
+
+    /// Has to be called after changing `collidables`, `triggers` or `win_point`
+    pub fn rebuild_collision_grid(&mut self) {
+        self.collision_grid =
+            CollisionGrid::new(&self.collidables, &self.triggers, &self.win_point);
+    }
+
     pub fn update_state(&mut self, new_state: GameState) {
         self.players = new_state.players;
     }
+
+    /// Apply a partial snapshot: `players` are overwritten, players the server
+    /// didn't mention are kept, and `removed_players` are dropped
+    pub fn merge_players(&mut self, players: BTreeMap<u32, PlayerState>, removed_players: &[u32]) {
+        for (id, mut player) in players {
+            // Compact snapshots only carry a name the first few times a player is sent
+            if player.name.is_empty()
+                && let Some(known) = self.players.get(&id)
+            {
+                player.name = known.name.clone();
+            }
+            self.players.insert(id, player);
+        }
+        for id in removed_players {
+            self.players.remove(id);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_scene_collidables_load_in_the_same_order() {
+        let rects = |state: &GameState| -> Vec<(f32, f32, f32, f32)> {
+            state
+                .collidables
+                .iter()
+                .map(|c| (c.x, c.y, c.w, c.h))
+                .collect()
+        };
+        let first = GameState::new("scene_3");
+        assert!(first.collidables.len() > 1);
+        for _ in 0..5 {
+            assert_eq!(rects(&GameState::new("scene_3")), rects(&first));
+        }
+    }
 }
@@ -0,0 +1,163 @@
+use super::{GameState, Race, Tag, Vec2};
+
+/// What a player touched.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum TouchTarget {
+    WinPoint,
+    /// Index into `GameState::triggers`
+    Trigger(usize),
+    /// Another player, by id. The physics reports it once per pair, by the player with the
+    /// lower id. The server also reports players a client saw itself overlap, by that client.
+    Player(u32),
+}
+
+/// A player overlapping the win point, a trigger or another player during one physics step
+/// of the server's simulation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Touch {
+    pub player_id: u32,
+    /// Physics step the overlap happened in
+    pub step: u32,
+    pub target: TouchTarget,
+}
+
+/// Rules of a game on top of the shared physics. The server calls the hooks around
+/// `GameState::mutate` every tick: `accepts_input` before, then `on_join`/`on_leave` for
+/// players that appeared or disappeared, `on_trigger` for every touch in the order they
+/// happened, and finally `on_tick`.
+pub trait GameMode: Send {
+    fn name(&self) -> &'static str;
+
+    /// Whether commands from `player_id` are applied this tick
+    fn accepts_input(&self, _player_id: u32) -> bool {
+        true
+    }
+
+    fn on_join(&mut self, _state: &mut GameState, _player_id: u32) {}
+
+    fn on_leave(&mut self, _state: &mut GameState, _player_id: u32) {}
+
+    /// Touching the win point sends everyone back to the spawn point
+    fn on_trigger(&mut self, state: &mut GameState, touch: Touch) {
+        if touch.target == TouchTarget::WinPoint {
+            let spawn = Vec2::new(state.spawn_point.x, state.spawn_point.y);
+            for player in state.players.values_mut() {
+                player.respawn_point = spawn;
+                player.respawn();
+            }
+        }
+    }
+
+    /// Called once per tick after the state was simulated for `dt` seconds
+    fn on_tick(&mut self, _state: &mut GameState, _dt: f32) {}
+
+    /// Score per player, best first. What the number means is up to the mode.
+    fn scores(&self) -> Vec<(u32, f32)> {
+        Vec::new()
+    }
+
+    /// Winner of the current round, once there is one
+    fn winner(&self) -> Option<u32> {
+        None
+    }
+}
+
+/// The original rules: no rounds or scores, the win point just resets everyone
+#[derive(Debug, Default)]
+pub struct ClassicMode;
+
+impl GameMode for ClassicMode {
+    fn name(&self) -> &'static str {
+        "classic"
+    }
+}
+
+/// Mode for a name used in the server config, `None` if there is no such mode
+pub fn game_mode_by_name(name: &str) -> Option<Box<dyn GameMode>> {
+    match name {
+        "classic" => Some(Box::new(ClassicMode)),
+        "race" => Some(Box::new(Race::new())),
+        "tag" => Some(Box::new(Tag::new())),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::{PlayerState, SpawnPoint};
+
+    fn create_test_state() -> GameState {
+        let mut state = GameState::empty(800.0, 600.0);
+        state.spawn_point = SpawnPoint { x: 10.0, y: 500.0 };
+        state.win_point.y = 500.0;
+        state.rebuild_collision_grid();
+        for id in 1..=2 {
+            let mut player = PlayerState::new(id, &state.spawn_point);
+            player.pos = Vec2::new(700.0, 100.0);
+            state.players.insert(id, player);
+        }
+        state
+    }
+
+    #[test]
+    fn test_classic_win_point_resets_everyone() {
+        let mut state = create_test_state();
+        let mut mode = ClassicMode;
+
+        mode.on_trigger(
+            &mut state,
+            Touch {
+                player_id: 1,
+                step: 10,
+                target: TouchTarget::WinPoint,
+            },
+        );
+
+        for player in state.players.values() {
+            assert_eq!(player.pos.x, 10.0);
+            assert_eq!(player.pos.y, 500.0);
+        }
+    }
+
+    #[test]
+    fn test_physics_reports_the_moved_win_point() {
+        let mut state = create_test_state();
+        state.players.get_mut(&1).unwrap().pos = Vec2::new(760.0, 510.0);
+
+        state.mutate(&[], 16_000, None);
+
+        assert!(
+            state
+                .touches
+                .iter()
+                .any(|touch| touch.player_id == 1 && touch.target == TouchTarget::WinPoint)
+        );
+        assert!(state.touches.iter().all(|touch| touch.player_id != 2));
+    }
+
+    #[test]
+    fn test_classic_ignores_scene_triggers() {
+        let mut state = create_test_state();
+        let mut mode = ClassicMode;
+
+        mode.on_trigger(
+            &mut state,
+            Touch {
+                player_id: 1,
+                step: 10,
+                target: TouchTarget::Trigger(0),
+            },
+        );
+
+        assert_eq!(state.players[&1].pos.x, 700.0);
+    }
+
+    #[test]
+    fn test_modes_by_name() {
+        assert_eq!(game_mode_by_name("race").unwrap().name(), "race");
+        assert_eq!(game_mode_by_name("classic").unwrap().name(), "classic");
+        assert_eq!(game_mode_by_name("tag").unwrap().name(), "tag");
+        assert!(game_mode_by_name("golf").is_none());
+    }
+}
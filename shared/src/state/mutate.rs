@@ -1,16 +1,28 @@
 use std::time::{SystemTime, UNIX_EPOCH};
 
-use super::{CommandContent, GameState, PlayerState, physics::*};
+use super::{
+    Abilities, CommandContent, GameState, PhysicsParams, PlayerInput, PlayerState,
+    PlayerStateCommand, dequantize_axis, physics::*, player_checksum,
+};
 use crate::generated::PlayerCommand;
 
-use super::{JUMP_CD, JUMP_FORCE, PLAYER_ACCELERATION};
+use super::{DASH_CD, DASH_SPEED, DROP_THROUGH_TIME, WALL_JUMP_PUSH};
 
 #[derive(Debug, Clone)]
 pub struct ScheduledCommand {
     execute_at_timestamp: u64,
     player_id: u32,
-    client_dt: f32,
-    command: PlayerCommand,
+    sequence: u32,
+    input: PlayerInput,
+}
+
+/// A player's newest command the simulation applied, and the player as it was right then
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AppliedCommand {
+    pub sequence: u32,
+    pub checksum: u32,
+    /// `GameState::physics_step` when it was applied
+    pub physics_step: u32,
 }
 
 impl Eq for ScheduledCommand {}
@@ -33,7 +45,7 @@ impl Ord for ScheduledCommand {
     }
 }
 
-const FIXED_DT_MICROS: u64 = 16000; // 16 ms
+pub(super) const FIXED_DT_MICROS: u64 = 16000; // 16 ms
 
 impl GameState {
     pub fn mutate(
@@ -42,30 +54,36 @@ impl GameState {
         dt_micros: u64,
         client_player_id: Option<u32>,
     ) {
-        let dt_micros = self.cached_dt_micros + dt_micros;
-        let end_tick = SystemTime::now()
+        let now_micros = SystemTime::now()
             .duration_since(UNIX_EPOCH)
             .unwrap_or_default()
             .as_micros() as u64;
+        self.mutate_at(commands, dt_micros, client_player_id, now_micros);
+    }
 
+    /// `mutate` with the simulated time ending at `end_tick`, a unix timestamp in
+    /// microseconds, instead of now
+    pub fn mutate_at(
+        &mut self,
+        commands: &[CommandContent],
+        dt_micros: u64,
+        client_player_id: Option<u32>,
+        end_tick: u64,
+    ) {
+        let dt_micros = self.cached_dt_micros + dt_micros;
+
+        // Every command replaces what the player holds from its timestamp on, an empty one
+        // releases everything
         for mutate_command in commands {
-            let client_dt = (mutate_command.player_state_command.dt_micros / 1000) as f32;
-            // Get player, add to game state if not exists
-
-            // Add commands to scheduled binary heap
-            for command in &mutate_command.player_state_command.commands {
-                let scheduled_command = ScheduledCommand {
-                    execute_at_timestamp: mutate_command
-                        .player_state_command
-                        .client_timestamp_micros
-                        + mutate_command.client_delay_micros,
-                    player_id: mutate_command.player_id,
-                    client_dt,
-                    command: *command,
-                };
-
-                self.scheduled_commands.push(scheduled_command);
-            }
+            let scheduled_command = ScheduledCommand {
+                execute_at_timestamp: mutate_command.player_state_command.client_timestamp_micros
+                    + mutate_command.client_delay_micros,
+                player_id: mutate_command.player_id,
+                sequence: mutate_command.player_state_command.sequence,
+                input: PlayerInput::from_command(&mutate_command.player_state_command),
+            };
+
+            self.scheduled_commands.push(scheduled_command);
         }
 
         // Physics
@@ -83,21 +101,16 @@ impl GameState {
             accumulator -= step;
         }
 
-        // Execute remaining commands
-        while let Some(command) = self.scheduled_commands.pop() {
-            self.execute_scheduled_command(command);
+        // A client applies the command of the frame it just predicted right away, it is
+        // stamped with the frame's end. The server keeps commands that aren't due yet for the
+        // step they are due in, next tick, so it holds every input as long as the client did.
+        if client_player_id.is_some() {
+            while let Some(command) = self.scheduled_commands.pop() {
+                self.execute_scheduled_command(command);
+            }
         }
 
         self.cached_dt_micros = accumulator;
-
-        // Collision
-        // let player_forces = collision(players);
-        // for (i, force, pos) in player_forces {
-        //     players[i].vel.x = force.x;
-        //     players[i].vel.y = force.y;
-        //     players[i].pos.x = pos.x;
-        //     players[i].pos.y = pos.y;
-        // }
     }
 
     fn execute_commands(&mut self, execute_time: u64) {
@@ -124,11 +137,24 @@ impl GameState {
             }
         };
 
-        match scheduled.command {
-            PlayerCommand::MoveRight => player.handle_move_right(scheduled.client_dt),
-            PlayerCommand::MoveLeft => player.handle_move_left(scheduled.client_dt),
-            PlayerCommand::Jump => player.handle_jump(),
-            _ => {}
+        player.hold(scheduled.input);
+        let applied = AppliedCommand {
+            sequence: scheduled.sequence,
+            checksum: player_checksum(player),
+            physics_step: self.physics_step,
+        };
+        self.applied_commands.insert(player_id, applied);
+    }
+
+    /// Simulate `steps` fixed steps with the client player holding `input`, for replaying
+    /// the client's own commands on top of a snapshot
+    pub fn mutate_steps(&mut self, client_player_id: u32, input: PlayerInput, steps: u32) {
+        if let Some(player) = self.players.get_mut(&client_player_id) {
+            player.hold(input);
+        }
+        let dt = FIXED_DT_MICROS as f32 / 1000000.0;
+        for _ in 0..steps {
+            physics(self, dt, Some(client_player_id));
         }
     }
 
@@ -138,19 +164,426 @@ impl GameState {
     }
 }
 
+impl PlayerInput {
+    /// What a player holds when this is all it sent for a frame
+    pub fn from_command(command: &PlayerStateCommand) -> PlayerInput {
+        let mut input = PlayerInput::default();
+        for command in &command.commands {
+            match *command {
+                PlayerCommand::MoveRight => input.axis += 1.0,
+                PlayerCommand::MoveLeft => input.axis -= 1.0,
+                PlayerCommand::Jump => input.jump = true,
+                PlayerCommand::MoveDown => input.down = true,
+                PlayerCommand::Dash => input.dash = true,
+                _ => {}
+            }
+        }
+        input.axis = match command.move_axis {
+            0 => input.axis.clamp(-1.0, 1.0),
+            move_axis => dequantize_axis(move_axis),
+        };
+        input
+    }
+}
+
 impl PlayerState {
-    fn handle_move_right(&mut self, dt: f32) {
-        self.vel.x += PLAYER_ACCELERATION * dt;
+    /// Hold `input` from now on. A press is remembered until a physics step fires it, so a
+    /// tap released before the next step isn't lost.
+    pub(super) fn hold(&mut self, input: PlayerInput) {
+        self.pending_press.jump |= input.jump && !self.input.jump;
+        self.pending_press.dash |= input.dash && !self.input.dash;
+        self.input = input;
+    }
+
+    /// Apply the held input for one physics step of `dt` seconds
+    pub(super) fn apply_input(&mut self, abilities: &Abilities, params: &PhysicsParams, dt: f32) {
+        let input = self.input;
+        if input.axis != 0.0 {
+            self.handle_move(input.axis, dt, params);
+        }
+        if (input.jump && !self.prev_input.jump) || self.pending_press.jump {
+            self.handle_jump(abilities, params);
+        }
+        if input.down {
+            self.handle_move_down();
+        }
+        if (input.dash && !self.prev_input.dash) || self.pending_press.dash {
+            self.handle_dash(abilities);
+        }
+        self.prev_input = input;
+        self.pending_press = PlayerInput::default();
+    }
+
+    fn handle_move(&mut self, axis: f32, dt: f32, params: &PhysicsParams) {
+        self.vel.x += params.player_acceleration * axis * dt;
+        self.facing = axis.signum();
+    }
+
+    /// Jump off the ground, else off a wall, else use up one of the air jumps
+    fn handle_jump(&mut self, abilities: &Abilities, params: &PhysicsParams) {
+        if self.jump_timer <= params.jump_cd {
+            return;
+        }
+
+        if self.grounded {
+            self.vel.y -= params.jump_force;
+        } else if abilities.wall_jump && self.wall_dir != 0.0 {
+            self.vel.y = -params.jump_force;
+            self.vel.x = -self.wall_dir * WALL_JUMP_PUSH;
+            self.wall_dir = 0.0;
+        } else if self.air_jumps < abilities.air_jumps {
+            self.vel.y = -params.jump_force;
+            self.air_jumps += 1;
+        } else {
+            return;
+        }
+        self.jump_timer = 0.0;
+    }
+
+    /// Burst of speed in the direction the player is facing, which also stops a fall
+    fn handle_dash(&mut self, abilities: &Abilities) {
+        if abilities.dash && self.dash_timer > DASH_CD {
+            self.vel.x = self.facing * DASH_SPEED;
+            self.vel.y = self.vel.y.min(0.0);
+            self.dash_timer = 0.0;
+        }
+    }
+
+    fn handle_move_down(&mut self) {
+        self.drop_timer = DROP_THROUGH_TIME;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::{
+        Abilities, CollidableKind, JUMP_FORCE, PhysicsParams, PlayerStateCommand, SceneObject,
+        SpawnPoint, quantize_axis,
+    };
+
+    fn create_test_state() -> GameState {
+        let mut state = GameState::empty(800.0, 600.0);
+        state.collidables = vec![
+            SceneObject {
+                x: 0.0,
+                y: 500.0,
+                w: 800.0,
+                h: 20.0,
+                kind: CollidableKind::Solid,
+            },
+            SceneObject {
+                x: 300.0,
+                y: 420.0,
+                w: 100.0,
+                h: 20.0,
+                kind: CollidableKind::Solid,
+            },
+        ];
+        state.spawn_point = SpawnPoint { x: 320.0, y: 400.0 };
+        state.rebuild_collision_grid();
+        state
+    }
+
+    /// Varied but reproducible input for every player on every frame
+    fn inputs(frame: u32) -> Vec<CommandContent> {
+        (1..=6)
+            .map(|player_id| {
+                let mut commands = Vec::new();
+                match (frame / 10 + player_id) % 3 {
+                    0 => commands.push(PlayerCommand::MoveLeft),
+                    1 => commands.push(PlayerCommand::MoveRight),
+                    _ => {}
+                }
+                if (frame + player_id * 7).is_multiple_of(25) {
+                    commands.push(PlayerCommand::Jump);
+                }
+                CommandContent {
+                    player_id,
+                    player_state_command: PlayerStateCommand {
+                        sequence: frame,
+                        dt_micros: FIXED_DT_MICROS,
+                        commands,
+                        client_timestamp_micros: 0,
+                        move_axis: 0,
+                        acked_tick: None,
+                    },
+                    client_delay_micros: 0,
+                }
+            })
+            .collect()
+    }
+
+    fn fingerprint(state: &GameState) -> Vec<(u32, [u32; 5], bool)> {
+        state
+            .players
+            .values()
+            .map(|p| {
+                let bits = [
+                    p.pos.x.to_bits(),
+                    p.pos.y.to_bits(),
+                    p.vel.x.to_bits(),
+                    p.vel.y.to_bits(),
+                    p.jump_timer.to_bits(),
+                ];
+                (p.id, bits, p.grounded)
+            })
+            .collect()
+    }
+
+    fn simulate() -> Vec<(u32, [u32; 5], bool)> {
+        let mut state = create_test_state();
+        for frame in 0..300 {
+            state.mutate(&inputs(frame), FIXED_DT_MICROS, None);
+        }
+        fingerprint(&state)
+    }
+
+    /// Hold right for a second and jump from 0.5 to 0.6 s, sending a command every frame
+    fn hold_right_and_jump(fps: u64) -> Vec<(u32, [u32; 5], bool)> {
+        let mut state = create_test_state();
+        let frame_micros = 1_000_000 / fps;
+        for frame in 0..fps {
+            let time = frame as f32 / fps as f32;
+            let mut commands = vec![PlayerCommand::MoveRight];
+            if (0.5..0.6).contains(&time) {
+                commands.push(PlayerCommand::Jump);
+            }
+            let command = CommandContent {
+                player_id: 1,
+                player_state_command: PlayerStateCommand {
+                    sequence: frame as u32,
+                    dt_micros: frame_micros,
+                    commands,
+                    client_timestamp_micros: 0,
+                    move_axis: 0,
+                    acked_tick: None,
+                },
+                client_delay_micros: 0,
+            };
+            state.mutate(&[command], frame_micros, None);
+        }
+        fingerprint(&state)
+    }
+
+    #[test]
+    fn test_movement_does_not_depend_on_frame_rate() {
+        let at_60 = hold_right_and_jump(60);
+        assert!(
+            f32::from_bits(at_60[0].1[0]) > 400.0,
+            "Player should have moved"
+        );
+
+        assert_eq!(hold_right_and_jump(30), at_60);
+        assert_eq!(hold_right_and_jump(240), at_60);
     }
 
-    fn handle_move_left(&mut self, dt: f32) {
-        self.vel.x -= PLAYER_ACCELERATION * dt;
+    #[test]
+    fn test_empty_command_releases_input() {
+        let mut state = create_test_state();
+        let command = |commands| CommandContent {
+            player_id: 1,
+            player_state_command: PlayerStateCommand {
+                sequence: 0,
+                dt_micros: FIXED_DT_MICROS,
+                commands,
+                client_timestamp_micros: 0,
+                move_axis: 0,
+                acked_tick: None,
+            },
+            client_delay_micros: 0,
+        };
+
+        state.mutate(
+            &[command(vec![PlayerCommand::MoveLeft])],
+            FIXED_DT_MICROS,
+            None,
+        );
+        assert_eq!(state.players[&1].input.axis, -1.0);
+        state.mutate(&[], FIXED_DT_MICROS, None);
+        assert_eq!(
+            state.players[&1].input.axis, -1.0,
+            "Input is held between commands"
+        );
+
+        state.mutate(&[command(vec![])], FIXED_DT_MICROS, None);
+        assert_eq!(state.players[&1].input, PlayerInput::default());
     }
 
-    fn handle_jump(&mut self) {
-        if self.grounded && self.jump_timer > JUMP_CD {
-            self.vel.y -= JUMP_FORCE;
-            self.jump_timer = 0.0;
+    #[test]
+    fn test_analog_axis_overrides_digital_direction() {
+        let mut state = create_test_state();
+        let command = CommandContent {
+            player_id: 1,
+            player_state_command: PlayerStateCommand {
+                sequence: 0,
+                dt_micros: FIXED_DT_MICROS,
+                commands: vec![PlayerCommand::MoveRight],
+                client_timestamp_micros: 0,
+                move_axis: quantize_axis(-0.5),
+                acked_tick: None,
+            },
+            client_delay_micros: 0,
         };
+
+        state.mutate(&[command], FIXED_DT_MICROS, None);
+
+        let axis = state.players[&1].input.axis;
+        assert!(
+            (axis + 0.5).abs() < 1.0 / i8::MAX as f32,
+            "axis was {}",
+            axis
+        );
+    }
+
+    #[test]
+    fn test_same_inputs_give_bit_identical_state() {
+        // All players spawn on top of each other, so the outcome depends on the order
+        // collisions are resolved in
+        let first = simulate();
+        assert_eq!(first.len(), 6);
+        for _ in 0..10 {
+            assert_eq!(simulate(), first);
+        }
+    }
+
+    const ALL_ABILITIES: Abilities = Abilities {
+        wall_jump: true,
+        air_jumps: 1,
+        dash: true,
+    };
+
+    /// Player in mid-air whose jump and dash cooldowns have run out
+    fn airborne_player() -> PlayerState {
+        let mut player = PlayerState::new(1, &SpawnPoint { x: 100.0, y: 100.0 });
+        player.jump_timer = 1.0;
+        player.dash_timer = 1.0;
+        player
+    }
+
+    #[test]
+    fn test_air_jumps_are_limited() {
+        let mut player = airborne_player();
+        player.handle_jump(&Abilities::default(), &PhysicsParams::default());
+        assert_eq!(player.vel.y, 0.0, "No air jumps without the ability");
+
+        player.handle_jump(&ALL_ABILITIES, &PhysicsParams::default());
+        assert_eq!(player.vel.y, -JUMP_FORCE);
+        assert_eq!(player.air_jumps, 1);
+
+        player.vel.y = 0.0;
+        player.jump_timer = 1.0;
+        player.handle_jump(&ALL_ABILITIES, &PhysicsParams::default());
+        assert_eq!(player.vel.y, 0.0, "The only air jump is used up");
+    }
+
+    #[test]
+    fn test_wall_jump_pushes_off_the_wall() {
+        let mut player = airborne_player();
+        player.wall_dir = 1.0;
+        player.handle_jump(&ALL_ABILITIES, &PhysicsParams::default());
+
+        assert_eq!(player.vel.x, -WALL_JUMP_PUSH);
+        assert_eq!(player.vel.y, -JUMP_FORCE);
+        assert_eq!(player.air_jumps, 0, "Wall jumps don't use air jumps");
+    }
+
+    #[test]
+    fn test_dash_has_cooldown() {
+        let mut player = airborne_player();
+        player.handle_move(-1.0, 0.016, &PhysicsParams::default());
+        player.handle_dash(&ALL_ABILITIES);
+        assert_eq!(player.vel.x, -DASH_SPEED);
+
+        player.vel.x = 0.0;
+        player.handle_dash(&ALL_ABILITIES);
+        assert_eq!(player.vel.x, 0.0);
+    }
+
+    #[test]
+    fn test_held_jump_does_not_use_the_air_jump() {
+        let params = PhysicsParams::default();
+        let mut player = airborne_player();
+        player.grounded = true;
+        player.input.jump = true;
+        player.apply_input(&ALL_ABILITIES, &params, 0.016);
+        assert_eq!(player.vel.y, -JUMP_FORCE);
+
+        // Still holding jump once in the air and off cooldown
+        player.grounded = false;
+        player.jump_timer = 1.0;
+        player.vel.y = 0.0;
+        player.apply_input(&ALL_ABILITIES, &params, 0.016);
+        assert_eq!(player.vel.y, 0.0);
+        assert_eq!(player.air_jumps, 0);
+
+        player.input.jump = false;
+        player.apply_input(&ALL_ABILITIES, &params, 0.016);
+        player.input.jump = true;
+        player.apply_input(&ALL_ABILITIES, &params, 0.016);
+        assert_eq!(player.air_jumps, 1, "A new press uses it");
+    }
+
+    #[test]
+    fn test_held_dash_fires_once() {
+        let params = PhysicsParams::default();
+        let mut player = airborne_player();
+        player.input.dash = true;
+        player.apply_input(&ALL_ABILITIES, &params, 0.016);
+        assert_eq!(player.vel.x, DASH_SPEED);
+
+        player.vel.x = 0.0;
+        player.dash_timer = 1.0;
+        player.apply_input(&ALL_ABILITIES, &params, 0.016);
+        assert_eq!(player.vel.x, 0.0, "Holding dash past the cooldown");
+    }
+
+    #[test]
+    fn test_tap_between_steps_is_not_lost() {
+        let mut state = create_test_state();
+        state.abilities = ALL_ABILITIES;
+        state.players.insert(1, airborne_player());
+        let command = |sequence, commands, at| CommandContent {
+            player_id: 1,
+            player_state_command: PlayerStateCommand {
+                sequence,
+                dt_micros: FIXED_DT_MICROS,
+                commands,
+                client_timestamp_micros: at,
+                move_axis: 0,
+                acked_tick: None,
+            },
+            client_delay_micros: 0,
+        };
+
+        // Jump is pressed and released again before the step starts at 1 s
+        let tap = [
+            command(1, vec![PlayerCommand::Jump], 999_000),
+            command(2, vec![], 999_500),
+        ];
+        state.mutate_at(&tap, FIXED_DT_MICROS, None, 1_016_000);
+
+        let player = &state.players[&1];
+        assert_eq!(player.air_jumps, 1);
+        assert_eq!(player.input, PlayerInput::default());
+        assert_eq!(player.pending_press, PlayerInput::default(), "Fired once");
+    }
+
+    #[test]
+    fn test_landing_restores_air_jumps() {
+        let mut state = create_test_state();
+        state.abilities = ALL_ABILITIES;
+        let mut player = airborne_player();
+        player.pos.x = 100.0;
+        player.air_jumps = 1;
+        state.players.insert(1, player);
+
+        for _ in 0..60 {
+            physics(&mut state, FIXED_DT_MICROS as f32 / 1_000_000.0, None);
+        }
+
+        assert!(state.players[&1].grounded);
+        physics(&mut state, FIXED_DT_MICROS as f32 / 1_000_000.0, None);
+        assert_eq!(state.players[&1].air_jumps, 0);
     }
 }
@@ -1,47 +1,89 @@
-use super::{GRAVITY, GROUND_FRICTION};
-use super::{GameState, SceneObject, Vec2};
+use super::WALL_SLIDE_SPEED;
+use super::triggers::apply_triggers;
+use super::{
+    CollidableKind, CollisionGrid, GameState, GridEntry, PlayerState, SceneObject, Touch,
+    TouchTarget, Vec2,
+};
 
 pub fn physics(state: &mut GameState, dt: f32, client_player_id: Option<u32>) {
-    let mut reset_players = false;
+    // Only the server's simulation reports touches, what they mean is up to its game mode
+    let record_touches = client_player_id.is_none();
 
     // Check for win point collisions
     for (player_id, player) in &state.players {
-        if let Some(client_player_id) = client_player_id {
-            if *player_id != client_player_id {
-                continue;
-            }
+        if let Some(client_player_id) = client_player_id
+            && *player_id != client_player_id
+        {
+            continue;
         }
 
-        let player_rect = SceneObject {
-            x: player.pos.x,
-            y: player.pos.y,
-            w: player.size,
-            h: player.size,
-        };
+        let player_rect = bounds(player);
+        let near_win_point = state
+            .collision_grid
+            .query(&player_rect)
+            .contains(&GridEntry::WinPoint);
 
-        if check_collision(&player_rect, &state.win_point) {
-            reset_players = true;
-            break;
+        if record_touches && near_win_point && check_collision(&player_rect, &state.win_point) {
+            state.touches.push(Touch {
+                player_id: *player_id,
+                step: state.physics_step,
+                target: TouchTarget::WinPoint,
+            });
         }
     }
 
-    // Reset all players to spawn point if win point was touched
-    if reset_players {
-        for (_player_id, player) in &mut state.players {
-            player.pos = Vec2::new(state.spawn_point.x, state.spawn_point.y);
-            player.vel = Vec2::ZERO;
-        }
-    }
+    // Moving platforms, where they were at the end of the last step and where they are now
+    let previous_platforms = state.moving_platform_rects(state.physics_step);
+    state.physics_step = state.physics_step.wrapping_add(1);
+    let platforms = state.moving_platform_rects(state.physics_step);
+    let params = state.physics_params;
 
     // Regular physics update
-    for (_player_id, player) in &mut state.players {
-        player.vel.x *= GROUND_FRICTION.powf(dt);
-        player.vel.y += GRAVITY * dt;
-        player.pos.x += player.vel.x * dt;
-        player.pos.y += player.vel.y * dt;
+    for player in state.players.values_mut() {
+        // Players standing on a moving platform move along with it
+        if player.grounded
+            && let Some(i) = previous_platforms
+                .iter()
+                .position(|platform| is_standing_on(player, platform))
+        {
+            player.pos.x += platforms[i].x - previous_platforms[i].x;
+            player.pos.y += platforms[i].y - previous_platforms[i].y;
+        }
+
+        player.apply_input(&state.abilities, &params, dt);
+        let friction = if player.grounded {
+            params.ground_friction
+        } else {
+            params.air_friction
+        };
+        player.vel.x *= friction.powf(dt);
+        player.vel.y += params.gravity * dt;
 
         player.jump_timer += dt;
+        player.dash_timer += dt;
+        player.drop_timer = (player.drop_timer - dt).max(0.0);
+        if player.grounded {
+            player.air_jumps = 0;
+        }
+        let start = player.pos;
+        let was_grounded = player.grounded;
         player.grounded = false;
+        player.wall_dir = 0.0;
+
+        move_and_collide(
+            player,
+            &state.collidables,
+            &state.collision_grid,
+            &platforms,
+            dt,
+        );
+        land_on_slopes(
+            player,
+            start,
+            was_grounded,
+            &state.collidables,
+            &state.collision_grid,
+        );
 
         if player.pos.y > state.height - player.size {
             player.pos.y = state.height - player.size;
@@ -55,143 +97,363 @@ pub fn physics(state: &mut GameState, dt: f32, client_player_id: Option<u32>) {
         if player.pos.x > state.width - player.size {
             player.pos.x = state.width - player.size;
             player.vel.x = 0.0;
+            player.wall_dir = 1.0;
         }
         if player.pos.x < 0.0 {
             player.pos.x = 0.0;
             player.vel.x = 0.0;
+            player.wall_dir = -1.0;
+        }
+
+        // Wall sliding, the slow fall that lets players line up a wall jump
+        if state.abilities.wall_jump && player.wall_dir != 0.0 && !player.grounded {
+            player.vel.y = player.vel.y.min(WALL_SLIDE_SPEED);
         }
+
+        // Sweeping can't help a player that starts the step inside a collidable
+        depenetrate_world(
+            player,
+            &state.collidables,
+            &state.collision_grid,
+            &platforms,
+        );
+
+        let touched = apply_triggers(
+            player,
+            &state.triggers,
+            &state.collision_grid,
+            &state.spawn_point,
+        );
+        if record_touches {
+            state.touches.extend(touched.into_iter().map(|index| Touch {
+                player_id: player.id,
+                step: state.physics_step,
+                target: TouchTarget::Trigger(index),
+            }));
+        }
+    }
+
+    // Resolved after the world, so a player stacked on someone that a platform just
+    // pushed up ends on top of them instead of inside
+    let contacts = resolve_player_collisions(state);
+    // A push can shove a player into a wall, the world wins over the other player
+    for player in state.players.values_mut() {
+        depenetrate_world(
+            player,
+            &state.collidables,
+            &state.collision_grid,
+            &platforms,
+        );
+        player.pos.x = player.pos.x.clamp(0.0, state.width - player.size);
+    }
+    if record_touches {
+        state
+            .touches
+            .extend(contacts.into_iter().map(|(player_id, other_id)| Touch {
+                player_id,
+                step: state.physics_step,
+                target: TouchTarget::Player(other_id),
+            }));
     }
+}
+
+/// Max number of surfaces a player can slide along in one step
+const MAX_SWEEP_ITERATIONS: usize = 3;
 
-    let collidables = &state.collidables;
-
-    for (_id, player) in &mut state.players {
-        // player's AABB
-        let px1 = player.pos.x;
-        let py1 = player.pos.y;
-        let px2 = px1 + player.size;
-        let py2 = py1 + player.size;
-
-        for col in collidables {
-            let cx1 = col.x;
-            let cy1 = col.y;
-            let cx2 = cx1 + col.w;
-            let cy2 = cy1 + col.h;
-
-            // overlap test
-            if px1 < cx2 && px2 > cx1 && py1 < cy2 && py2 > cy1 {
-                // compute penetration depths on each axis
-                let pen_x = if player.vel.x > 0.0 {
-                    px2 - cx1
-                } else {
-                    cx2 - px1
-                };
-                let pen_y = if player.vel.y > 0.0 {
-                    py2 - cy1
-                } else {
-                    cy2 - py1
-                };
-
-                if pen_x < pen_y {
-                    // resolve in X
-                    if player.vel.x > 0.0 {
-                        player.pos.x = cx1 - player.size;
-                    } else {
-                        player.pos.x = cx2;
-                    }
-                    player.vel.x = 0.0;
-                } else {
-                    // resolve in Y
-                    if player.vel.y > 0.0 {
-                        player.pos.y = cy1 - player.size;
-                    } else {
-                        player.pos.y = cy2;
-                    }
-                    player.vel.y = 0.0;
-                    player.grounded = true;
+/// Move the player by its velocity, stopping at the first collidable or platform in the
+/// way and sliding along it for the rest of the step
+fn move_and_collide(
+    player: &mut PlayerState,
+    collidables: &[SceneObject],
+    grid: &CollisionGrid,
+    platforms: &[SceneObject],
+    dt: f32,
+) {
+    let mut delta = player.vel * dt;
+
+    // Everything the player could touch on the way
+    let swept_bounds = SceneObject {
+        x: player.pos.x.min(player.pos.x + delta.x),
+        y: player.pos.y.min(player.pos.y + delta.y),
+        w: delta.x.abs() + player.size,
+        h: delta.y.abs() + player.size,
+        kind: CollidableKind::Solid,
+    };
+    let candidates: Vec<&SceneObject> = grid
+        .query_collidables(&swept_bounds)
+        .map(|index| &collidables[index])
+        .chain(platforms)
+        .filter(|col| blocks_sweep(player, delta, col))
+        .collect();
+
+    for _ in 0..MAX_SWEEP_ITERATIONS {
+        let hit = candidates
+            .iter()
+            .filter_map(|col| {
+                let (time, normal) = sweep(player.pos, player.size, delta, col)?;
+                // One-way platforms only stop players landing on top of them
+                if col.kind == CollidableKind::OneWay && normal.y >= 0.0 {
+                    return None;
                 }
+                Some((time, normal))
+            })
+            .min_by(|a, b| a.0.total_cmp(&b.0));
+
+        let Some((time, normal)) = hit else {
+            break;
+        };
+
+        player.pos = player.pos + delta * time;
+        delta = delta * (1.0 - time);
+        if normal.x != 0.0 {
+            player.vel.x = 0.0;
+            delta.x = 0.0;
+            player.wall_dir = -normal.x;
+        } else {
+            player.vel.y = 0.0;
+            delta.y = 0.0;
+            if normal.y < 0.0 {
+                player.grounded = true;
             }
         }
     }
+
+    player.pos = player.pos + delta;
+}
+
+/// Whether `col` can stop a player about to move by `delta`. Slopes are handled after the
+/// move by `land_on_slopes`.
+fn blocks_sweep(player: &PlayerState, delta: Vec2, col: &SceneObject) -> bool {
+    match col.kind {
+        CollidableKind::Solid => true,
+        CollidableKind::OneWay => {
+            player.drop_timer <= 0.0
+                && delta.y > 0.0
+                && player.pos.y + player.size <= col.y + STANDING_TOLERANCE
+        }
+        CollidableKind::Slope { .. } => false,
+    }
+}
+
+/// Put the player on any slope it walked into or fell onto during the step. Slopes are
+/// only solid from above, so players can walk and jump into them from below or the sides.
+fn land_on_slopes(
+    player: &mut PlayerState,
+    start: Vec2,
+    was_grounded: bool,
+    collidables: &[SceneObject],
+    grid: &CollisionGrid,
+) {
+    let travelled = SceneObject {
+        x: player.pos.x.min(start.x),
+        y: player.pos.y.min(start.y),
+        w: (player.pos.x - start.x).abs() + player.size,
+        h: (player.pos.y - start.y).abs() + player.size,
+        kind: CollidableKind::Solid,
+    };
+    let step_x = (player.pos.x - start.x).abs();
+
+    for index in grid.query_collidables(&travelled) {
+        let col = &collidables[index];
+        let CollidableKind::Slope { left, right } = col.kind else {
+            continue;
+        };
+        let center = player.pos.x + player.size / 2.0;
+        if center < col.x || center > col.x + col.w {
+            continue;
+        }
+
+        let surface = slope_surface(col, left, right, center);
+        let start_surface = slope_surface(col, left, right, start.x + player.size / 2.0);
+        let bottom = player.pos.y + player.size;
+        let start_bottom = start.y + player.size;
+
+        // Walking down a slope moves the surface away from the feet faster than gravity
+        // pulls the player after it, so grounded players are snapped down instead
+        let snap = if was_grounded && player.vel.y >= 0.0 {
+            step_x * (right - left).abs() / col.w + STANDING_TOLERANCE
+        } else {
+            0.0
+        };
+        let was_above = start_bottom <= start_surface + STANDING_TOLERANCE;
+        if was_above && bottom + snap >= surface {
+            player.pos.y = surface - player.size;
+            player.vel.y = 0.0;
+            player.grounded = true;
+        }
+    }
 }
 
-fn check_collision(rect1: &SceneObject, rect2: &SceneObject) -> bool {
+/// Height of the slope surface at `x`, clamped to the ends of the slope
+fn slope_surface(col: &SceneObject, left: f32, right: f32, x: f32) -> f32 {
+    let t = ((x - col.x) / col.w).clamp(0.0, 1.0);
+    col.y + col.h - (left + (right - left) * t)
+}
+
+/// Swept AABB test of a square moving by `delta` against `col`. Returns the fraction of
+/// `delta` travelled before touching and the normal of the surface that was hit.
+fn sweep(pos: Vec2, size: f32, delta: Vec2, col: &SceneObject) -> Option<(f32, Vec2)> {
+    // Moving a point against the collidable grown by the player size is the same test
+    let (entry_x, exit_x) = slab(pos.x, delta.x, col.x - size, col.x + col.w)?;
+    let (entry_y, exit_y) = slab(pos.y, delta.y, col.y - size, col.y + col.h)?;
+
+    let entry = entry_x.max(entry_y);
+    let exit = exit_x.min(exit_y);
+    if entry >= exit || !(0.0..=1.0).contains(&entry) {
+        return None;
+    }
+
+    let normal = if entry_x > entry_y {
+        Vec2::new(-delta.x.signum(), 0.0)
+    } else {
+        Vec2::new(0.0, -delta.y.signum())
+    };
+    Some((entry, normal))
+}
+
+/// Times at which a point moving from `start` by `delta` enters and leaves `[min, max]`
+fn slab(start: f32, delta: f32, min: f32, max: f32) -> Option<(f32, f32)> {
+    if delta == 0.0 {
+        return if start > min && start < max {
+            Some((f32::NEG_INFINITY, f32::INFINITY))
+        } else {
+            None
+        };
+    }
+
+    let t1 = (min - start) / delta;
+    let t2 = (max - start) / delta;
+    Some((t1.min(t2), t1.max(t2)))
+}
+
+/// Push the player out of every solid collidable and moving platform it overlaps
+fn depenetrate_world(
+    player: &mut PlayerState,
+    collidables: &[SceneObject],
+    grid: &CollisionGrid,
+    platforms: &[SceneObject],
+) {
+    for index in grid.query_collidables(&bounds(player)) {
+        let col = &collidables[index];
+        if col.kind == CollidableKind::Solid {
+            depenetrate(player, col);
+        }
+    }
+    for platform in platforms {
+        depenetrate(player, platform);
+    }
+}
+
+/// Push the player out of `col` along the axis of least penetration
+fn depenetrate(player: &mut PlayerState, col: &SceneObject) {
+    if !check_collision(&bounds(player), col) {
+        return;
+    }
+
+    let left = player.pos.x + player.size - col.x;
+    let right = col.x + col.w - player.pos.x;
+    let up = player.pos.y + player.size - col.y;
+    let down = col.y + col.h - player.pos.y;
+
+    if left.min(right) < up.min(down) {
+        player.pos.x = if left < right {
+            col.x - player.size
+        } else {
+            col.x + col.w
+        };
+        player.vel.x = 0.0;
+    } else if up < down {
+        player.pos.y = col.y - player.size;
+        player.vel.y = player.vel.y.min(0.0);
+        player.grounded = true;
+    } else {
+        player.pos.y = col.y + col.h;
+        player.vel.y = player.vel.y.max(0.0);
+    }
+}
+
+/// Max gap between a player's feet and a platform for the player to count as standing on it
+const STANDING_TOLERANCE: f32 = 0.5;
+
+fn is_standing_on(player: &PlayerState, platform: &SceneObject) -> bool {
+    (player.pos.y + player.size - platform.y).abs() <= STANDING_TOLERANCE
+        && player.pos.x < platform.x + platform.w
+        && player.pos.x + player.size > platform.x
+}
+
+fn bounds(player: &PlayerState) -> SceneObject {
+    SceneObject {
+        x: player.pos.x,
+        y: player.pos.y,
+        w: player.size,
+        h: player.size,
+        kind: CollidableKind::Solid,
+    }
+}
+
+pub(super) fn check_collision(rect1: &SceneObject, rect2: &SceneObject) -> bool {
     rect1.x < rect2.x + rect2.w
         && rect1.x + rect1.w > rect2.x
         && rect1.y < rect2.y + rect2.h
         && rect1.y + rect1.h > rect2.y
 }
 
-// pub fn collision(state: &mut GameState) -> Vec<(usize, Vec2, Vec2)> {
-//     let mut player_forces = vec![];
-//     for (player_id_1, player) in &mut state.players {
-//         for p2 in players {
-//             if p1.id == p2.id {
-//                 continue;
-//             }
-
-//             let v_overlap = p1.pos.y <= p2.pos.y + p2.size && p2.pos.y <= p1.pos.y + p1.size;
-//             let h_overlap = p1.pos.x <= p2.pos.x + p2.size && p2.pos.x <= p1.pos.x + p1.size;
-//             let overlap = v_overlap && h_overlap;
-
-//             let p1_top = overlap && p1.vel.y > p2.vel.y;
-//             let p1_bottom = overlap && p1.vel.y < p2.vel.y;
-//             let p1_left = overlap && p1.vel.x > p2.vel.x;
-//             let p1_right = overlap && p1.vel.x < p2.vel.x;
-
-//             if overlap {
-//                 /*if p1_top {
-//                     let force = Vec2 { x: (p1.vel.x + p2.vel.x) / 2.0, y: 0.0 };
-//                     let pos = Vec2 { x: p1.pos.x, y: p2.pos.y - p1.size };
-//                     player_forces.push((i, force, pos));
-//                 } else*/
-//                 if p1_left {
-//                     let force = Vec2 {
-//                         x: (p1.vel.x + p2.vel.x) / 2.0,
-//                         y: (p1.vel.y + p2.vel.y) / 2.0,
-//                     };
-//                     let pos = Vec2 {
-//                         x: p2.pos.x - p1.size,
-//                         y: p1.pos.y,
-//                     };
-//                     player_forces.push((i, force, pos));
-//                 } else if p1_right {
-//                     let force = Vec2 {
-//                         x: (p1.vel.x + p2.vel.x) / 2.0,
-//                         y: (p1.vel.y + p2.vel.y) / 2.0,
-//                     };
-//                     let pos = Vec2 {
-//                         x: p1.pos.x,
-//                         y: p1.pos.y,
-//                     };
-//                     player_forces.push((i, force, pos));
-//                 }
-//             }
-//         }
-//     }
-//     player_forces
-// }
+/// Push overlapping players out of each other and return the pairs that were in contact,
+/// lower id first. Pairs are handled in id order so every simulation of the same state ends
+/// up with the same result.
+fn resolve_player_collisions(state: &mut GameState) -> Vec<(u32, u32)> {
+    let mut players: Vec<&mut PlayerState> = state.players.values_mut().collect();
+    let mut contacts = Vec::new();
+
+    for i in 0..players.len() {
+        let (head, tail) = players.split_at_mut(i + 1);
+        let a = &mut *head[i];
+        for b in tail {
+            if resolve_player_pair(a, b) {
+                contacts.push((a.id, b.id));
+            }
+        }
+    }
+    contacts
+}
+
+/// Separate two players, returns whether they overlapped
+fn resolve_player_pair(a: &mut PlayerState, b: &mut PlayerState) -> bool {
+    let overlap_x = (a.pos.x + a.size).min(b.pos.x + b.size) - a.pos.x.max(b.pos.x);
+    let overlap_y = (a.pos.y + a.size).min(b.pos.y + b.size) - a.pos.y.max(b.pos.y);
+    if overlap_x <= 0.0 || overlap_y <= 0.0 {
+        return false;
+    }
+
+    if overlap_y < overlap_x {
+        // Stack: the upper player lands on the lower one's head
+        let (upper, lower) = if a.pos.y < b.pos.y { (a, b) } else { (b, a) };
+        upper.pos.y = lower.pos.y - upper.size;
+        if upper.vel.y > lower.vel.y {
+            upper.vel.y = lower.vel.y;
+        }
+        upper.grounded = true;
+    } else {
+        // Push: split the overlap and let both move at their average speed
+        let (left, right) = if a.pos.x < b.pos.x { (a, b) } else { (b, a) };
+        left.pos.x -= overlap_x / 2.0;
+        right.pos.x += overlap_x / 2.0;
+        if left.vel.x > right.vel.x {
+            let shared_vel = (left.vel.x + right.vel.x) / 2.0;
+            left.vel.x = shared_vel;
+            right.vel.x = shared_vel;
+        }
+    }
+    true
+}
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use std::collections::{BinaryHeap, HashMap};
+    use crate::state::PlayerInput;
 
     fn create_test_state() -> GameState {
-        GameState {
-            players: HashMap::new(),
-            collidables: vec![],
-            width: 800.0,
-            height: 600.0,
-            spawn_point: crate::state::SpawnPoint { x: 0.0, y: 0.0 },
-            win_point: SceneObject {
-                x: 750.0,
-                y: 50.0,
-                w: 50.0,
-                h: 50.0,
-            },
-            cached_dt_micros: 0,
-            scheduled_commands: BinaryHeap::new(),
-        }
+        GameState::empty(800.0, 600.0)
     }
 
     fn create_test_player(id: u32, x: f32, y: f32) -> (u32, crate::state::PlayerState) {
@@ -204,6 +466,16 @@ mod tests {
                 vel: Vec2 { x: 0.0, y: 0.0 },
                 grounded: false,
                 jump_timer: 0.0,
+                drop_timer: 0.0,
+                respawn_point: Vec2::ZERO,
+                is_it: false,
+                facing: 1.0,
+                wall_dir: 0.0,
+                air_jumps: 0,
+                dash_timer: 0.0,
+                input: PlayerInput::default(),
+                prev_input: PlayerInput::default(),
+                pending_press: PlayerInput::default(),
                 color: crate::generated::Color::Red,
                 size: 32.0,
             },
@@ -245,4 +517,471 @@ mod tests {
         );
         assert!(player.grounded, "Player should be marked as grounded");
     }
+
+    #[test]
+    fn test_player_stands_on_other_player() {
+        let mut state = create_test_state();
+        let (id, lower) = create_test_player(1, 100.0, state.height - 32.0);
+        state.players.insert(id, lower);
+        let (id, mut upper) = create_test_player(2, 105.0, state.height - 70.0);
+        upper.vel = Vec2 { x: 0.0, y: 300.0 };
+        state.players.insert(id, upper);
+
+        for _ in 0..60 {
+            physics(&mut state, 0.016, None);
+        }
+
+        let lower = &state.players[&1];
+        let upper = &state.players[&2];
+        assert_eq!(
+            upper.pos.y,
+            lower.pos.y - upper.size,
+            "Should rest on the head"
+        );
+        assert!(upper.grounded, "Standing on a player counts as grounded");
+        assert_eq!(upper.pos.x, 105.0, "Stacking should not push sideways");
+    }
+
+    #[test]
+    fn test_player_pushes_other_player() {
+        let mut state = create_test_state();
+        let (id, mut pusher) = create_test_player(1, 100.0, state.height - 32.0);
+        pusher.vel = Vec2 { x: 200.0, y: 0.0 };
+        state.players.insert(id, pusher);
+        let (id, pushed) = create_test_player(2, 134.0, state.height - 32.0);
+        state.players.insert(id, pushed);
+
+        for _ in 0..10 {
+            physics(&mut state, 0.016, None);
+        }
+
+        let pusher = &state.players[&1];
+        let pushed = &state.players[&2];
+        assert!(pushed.pos.x > 134.0, "Standing player should be pushed");
+        assert!(
+            pushed.pos.x - pusher.pos.x >= pusher.size - 1e-3,
+            "Players should not overlap"
+        );
+    }
+
+    #[test]
+    fn test_push_does_not_shove_player_into_wall() {
+        let mut state = create_test_state();
+        state.collidables.push(SceneObject {
+            x: 0.0,
+            y: 0.0,
+            w: 100.0,
+            h: state.height,
+            kind: CollidableKind::Solid,
+        });
+        state.rebuild_collision_grid();
+        let (id, against_wall) = create_test_player(1, 100.0, state.height - 32.0);
+        state.players.insert(id, against_wall);
+        let (id, mut pusher) = create_test_player(2, 134.0, state.height - 32.0);
+        pusher.vel = Vec2 { x: -400.0, y: 0.0 };
+        state.players.insert(id, pusher);
+
+        for _ in 0..10 {
+            physics(&mut state, 0.016, None);
+        }
+
+        assert!(
+            state.players[&1].pos.x >= 100.0,
+            "Pushed player should stay out of the wall"
+        );
+    }
+
+    #[test]
+    fn test_scene_physics_params_are_used() {
+        let mut state = create_test_state();
+        state.physics_params.gravity = 1000.0;
+        state.physics_params.air_friction = 0.5;
+        let (id, mut player) = create_test_player(1, 100.0, 100.0);
+        player.vel.x = 100.0;
+        state.players.insert(id, player);
+
+        physics(&mut state, 0.5, None);
+
+        let player = &state.players[&1];
+        assert_eq!(player.vel.y, 500.0);
+        assert!((player.vel.x - 100.0 * 0.5f32.sqrt()).abs() < 1e-3);
+    }
+
+    #[test]
+    fn test_wall_slide_slows_fall() {
+        let mut state = create_test_state();
+        state.collidables.push(SceneObject {
+            x: 200.0,
+            y: 100.0,
+            w: 50.0,
+            h: 500.0,
+            kind: CollidableKind::Solid,
+        });
+        state.rebuild_collision_grid();
+        state.abilities.wall_jump = true;
+        let (id, mut player) = create_test_player(1, 200.0 - 32.0, 150.0);
+        player.vel = Vec2 { x: 50.0, y: 600.0 };
+        state.players.insert(id, player);
+
+        for _ in 0..10 {
+            state.players.get_mut(&1).unwrap().vel.x = 50.0;
+            physics(&mut state, 0.016, None);
+        }
+
+        let player = &state.players[&1];
+        assert_eq!(player.wall_dir, 1.0);
+        assert!(player.vel.y <= WALL_SLIDE_SPEED);
+    }
+
+    #[test]
+    fn test_player_contacts_are_touches_on_server_only() {
+        let setup = || {
+            let mut state = create_test_state();
+            let (id, mut runner) = create_test_player(1, 100.0, state.height - 32.0);
+            runner.vel = Vec2 { x: 200.0, y: 0.0 };
+            state.players.insert(id, runner);
+            let (id, standing) = create_test_player(2, 134.0, state.height - 32.0);
+            state.players.insert(id, standing);
+            let (id, far) = create_test_player(3, 500.0, state.height - 32.0);
+            state.players.insert(id, far);
+            state
+        };
+
+        let mut state = setup();
+        for _ in 0..5 {
+            physics(&mut state, 0.016, None);
+        }
+        assert!(!state.touches.is_empty(), "Pushing is a contact");
+        assert!(
+            state
+                .touches
+                .iter()
+                .all(|t| t.player_id == 1 && t.target == TouchTarget::Player(2))
+        );
+
+        let mut state = setup();
+        for _ in 0..5 {
+            physics(&mut state, 0.016, Some(1));
+        }
+        assert!(state.touches.is_empty());
+    }
+
+    #[test]
+    fn test_player_collisions_are_deterministic() {
+        let run = || {
+            let mut state = create_test_state();
+            for (id, x, vel_x) in [(1, 100.0, 150.0), (2, 120.0, 0.0), (3, 140.0, -150.0)] {
+                let (id, mut player) = create_test_player(id, x, state.height - 32.0);
+                player.vel.x = vel_x;
+                state.players.insert(id, player);
+            }
+            for _ in 0..20 {
+                physics(&mut state, 0.016, None);
+            }
+            let mut positions: Vec<(u32, f32, f32)> = state
+                .players
+                .values()
+                .map(|p| (p.id, p.pos.x, p.pos.y))
+                .collect();
+            positions.sort_by_key(|(id, _, _)| *id);
+            positions
+        };
+
+        let expected = run();
+        for _ in 0..20 {
+            assert_eq!(run(), expected);
+        }
+    }
+
+    fn thin_platform() -> SceneObject {
+        SceneObject {
+            x: 50.0,
+            y: 300.0,
+            w: 200.0,
+            h: 20.0,
+            kind: CollidableKind::Solid,
+        }
+    }
+
+    #[test]
+    fn test_fast_fall_does_not_tunnel_through_thin_platform() {
+        let mut state = create_test_state();
+        state.collidables.push(thin_platform());
+        state.rebuild_collision_grid();
+        let (id, mut player) = create_test_player(1, 100.0, 250.0);
+        player.vel = Vec2 { x: 0.0, y: 3000.0 };
+        state.players.insert(id, player);
+
+        // 150px in a single step, far more than the platform is thick
+        physics(&mut state, 0.05, None);
+
+        let player = &state.players[&1];
+        assert_eq!(
+            player.pos.y,
+            300.0 - player.size,
+            "Should land on the platform"
+        );
+        assert_eq!(player.vel.y, 0.0);
+        assert!(player.grounded);
+    }
+
+    #[test]
+    fn test_large_dt_does_not_tunnel() {
+        let mut state = create_test_state();
+        state.collidables.push(thin_platform());
+        state.rebuild_collision_grid();
+        let (id, player) = create_test_player(1, 100.0, 0.0);
+        state.players.insert(id, player);
+
+        // A stalled frame: one step of half a second
+        physics(&mut state, 0.5, None);
+
+        let player = &state.players[&1];
+        assert_eq!(player.pos.y, 300.0 - player.size);
+        assert!(player.grounded);
+    }
+
+    #[test]
+    fn test_fast_horizontal_move_stops_at_wall() {
+        let mut state = create_test_state();
+        state.collidables.push(SceneObject {
+            x: 400.0,
+            y: 0.0,
+            w: 10.0,
+            h: 600.0,
+            kind: CollidableKind::Solid,
+        });
+        state.rebuild_collision_grid();
+        let (id, mut player) = create_test_player(1, 300.0, 500.0);
+        player.vel = Vec2 { x: 10000.0, y: 0.0 };
+        state.players.insert(id, player);
+
+        physics(&mut state, 0.016, None);
+
+        let player = &state.players[&1];
+        assert_eq!(player.pos.x, 400.0 - player.size, "Should stop at the wall");
+        assert_eq!(player.vel.x, 0.0);
+        assert!(player.pos.y > 500.0, "Should keep falling along the wall");
+    }
+
+    #[test]
+    fn test_overlap_resolves_along_shallowest_axis() {
+        let mut state = create_test_state();
+        state.collidables.push(thin_platform());
+        state.rebuild_collision_grid();
+        // Mostly inside from above but moving up: the old velocity-based check pushed
+        // this player out through the bottom of the platform
+        let (id, mut player) = create_test_player(1, 100.0, 270.0);
+        player.vel = Vec2 { x: 0.0, y: -10.0 };
+        state.players.insert(id, player);
+
+        physics(&mut state, 0.001, None);
+
+        let player = &state.players[&1];
+        assert_eq!(player.pos.y, 300.0 - player.size);
+        assert!(player.grounded);
+    }
+
+    fn create_platform_state(path: Vec<Vec2>) -> GameState {
+        let mut state = create_test_state();
+        state.moving_platforms.push(crate::state::MovingPlatform {
+            w: 80.0,
+            h: 20.0,
+            path,
+            speed: 100.0,
+            mode: crate::state::PathMode::PingPong,
+        });
+        state
+    }
+
+    #[test]
+    fn test_player_rides_horizontal_platform() {
+        let mut state =
+            create_platform_state(vec![Vec2::new(100.0, 400.0), Vec2::new(300.0, 400.0)]);
+        let (id, mut player) = create_test_player(1, 120.0, 400.0 - 32.0);
+        player.grounded = true;
+        state.players.insert(id, player);
+
+        for _ in 0..60 {
+            physics(&mut state, 0.016, None);
+        }
+
+        let platform = &state.moving_platform_rects(state.physics_step)[0];
+        let player = &state.players[&1];
+        assert!((platform.x - 196.0).abs() < 0.01);
+        assert!(
+            (player.pos.x - 216.0).abs() < 0.01,
+            "Should be carried along"
+        );
+        assert_eq!(player.pos.y, platform.y - player.size);
+        assert!(player.grounded);
+    }
+
+    #[test]
+    fn test_player_rides_elevator_up_and_down() {
+        let mut state =
+            create_platform_state(vec![Vec2::new(100.0, 400.0), Vec2::new(100.0, 200.0)]);
+        let (id, mut player) = create_test_player(1, 120.0, 400.0 - 32.0);
+        player.grounded = true;
+        state.players.insert(id, player);
+
+        // Up to the top and a bit back down again
+        for step in 0..150 {
+            physics(&mut state, 0.016, None);
+
+            let platform = &state.moving_platform_rects(state.physics_step)[0];
+            let player = &state.players[&1];
+            assert!(
+                (player.pos.y - (platform.y - player.size)).abs() < 0.01,
+                "Player fell off the elevator at step {}",
+                step
+            );
+        }
+    }
+
+    fn create_kind_state(kind: CollidableKind) -> GameState {
+        let mut state = create_test_state();
+        state.collidables.push(SceneObject {
+            x: 100.0,
+            y: 400.0,
+            w: 200.0,
+            h: 100.0,
+            kind,
+        });
+        state.rebuild_collision_grid();
+        state
+    }
+
+    #[test]
+    fn test_jump_through_one_way_platform_from_below() {
+        let mut state = create_kind_state(CollidableKind::OneWay);
+        let (id, mut player) = create_test_player(1, 150.0, 510.0);
+        player.vel = Vec2 { x: 0.0, y: -1200.0 };
+        state.players.insert(id, player);
+
+        for _ in 0..60 {
+            physics(&mut state, 0.016, None);
+        }
+
+        let player = &state.players[&1];
+        assert_eq!(
+            player.pos.y,
+            400.0 - player.size,
+            "Should pass through and land on top"
+        );
+        assert!(player.grounded);
+    }
+
+    #[test]
+    fn test_one_way_platform_blocks_from_above() {
+        let mut state = create_kind_state(CollidableKind::OneWay);
+        let (id, mut player) = create_test_player(1, 150.0, 300.0);
+        player.vel = Vec2 { x: 0.0, y: 3000.0 };
+        state.players.insert(id, player);
+
+        for _ in 0..30 {
+            physics(&mut state, 0.016, None);
+        }
+
+        let player = &state.players[&1];
+        assert_eq!(player.pos.y, 400.0 - player.size);
+        assert_eq!(player.vel.y, 0.0);
+        assert!(player.grounded);
+    }
+
+    #[test]
+    fn test_drop_through_one_way_platform() {
+        let mut state = create_kind_state(CollidableKind::OneWay);
+        let (id, mut player) = create_test_player(1, 150.0, 400.0 - 32.0);
+        player.grounded = true;
+        state.players.insert(id, player);
+
+        physics(&mut state, 0.016, None);
+        assert!(state.players[&1].grounded, "Should stand on the platform");
+
+        state.players.get_mut(&1).unwrap().drop_timer = crate::state::DROP_THROUGH_TIME;
+        for _ in 0..30 {
+            physics(&mut state, 0.016, None);
+        }
+
+        let player = &state.players[&1];
+        assert_eq!(
+            player.pos.y,
+            state.height - player.size,
+            "Should fall through to the ground"
+        );
+    }
+
+    /// 45 degree slope on the ground from (100, 600) up to (300, 400), or down when `rising` is false
+    fn create_slope_state(rising: bool) -> GameState {
+        let (left, right) = if rising { (0.0, 200.0) } else { (200.0, 0.0) };
+        let mut state = create_test_state();
+        state.collidables.push(SceneObject {
+            x: 100.0,
+            y: 400.0,
+            w: 200.0,
+            h: 200.0,
+            kind: CollidableKind::Slope { left, right },
+        });
+        state.rebuild_collision_grid();
+        state
+    }
+
+    #[test]
+    fn test_walk_up_slope_stays_grounded() {
+        let mut state = create_slope_state(true);
+        let (id, mut player) = create_test_player(1, 70.0, state.height - 32.0);
+        player.grounded = true;
+        state.players.insert(id, player);
+
+        for step in 0..40 {
+            state.players.get_mut(&1).unwrap().vel.x = 200.0;
+            physics(&mut state, 0.016, None);
+
+            let player = &state.players[&1];
+            assert!(player.grounded, "Lost ground at step {}", step);
+        }
+
+        let player = &state.players[&1];
+        let center = player.pos.x + player.size / 2.0;
+        assert!(center > 150.0, "Should have walked up the slope");
+        assert!(
+            (player.pos.y + player.size - (700.0 - center)).abs() < 0.01,
+            "Feet should follow the surface"
+        );
+    }
+
+    #[test]
+    fn test_walk_down_slope_stays_grounded() {
+        let mut state = create_slope_state(false);
+        // Feet on the surface right below the center at x = 120
+        let (id, mut player) = create_test_player(1, 104.0, 420.0 - 32.0);
+        player.grounded = true;
+        state.players.insert(id, player);
+
+        for step in 0..30 {
+            state.players.get_mut(&1).unwrap().vel.x = 300.0;
+            physics(&mut state, 0.016, None);
+
+            let player = &state.players[&1];
+            assert!(player.grounded, "Lost ground at step {}", step);
+        }
+    }
+
+    #[test]
+    fn test_land_on_slope() {
+        let mut state = create_slope_state(true);
+        let (id, player) = create_test_player(1, 184.0, 200.0);
+        state.players.insert(id, player);
+
+        for _ in 0..60 {
+            physics(&mut state, 0.016, None);
+        }
+
+        // Center at x = 200, halfway up the slope
+        let player = &state.players[&1];
+        assert_eq!(player.pos.y + player.size, 500.0);
+        assert_eq!(player.vel.y, 0.0);
+        assert!(player.grounded);
+    }
 }
@@ -0,0 +1,176 @@
+use serde::Deserialize;
+
+use super::mutate::FIXED_DT_MICROS;
+use super::{CollidableKind, GameState, SceneObject, Vec2};
+
+/// How a moving platform continues once it reaches the last waypoint.
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum PathMode {
+    /// Travel back along the path to the first waypoint
+    #[default]
+    PingPong,
+    /// Go straight from the last waypoint back to the first
+    Loop,
+}
+
+/// Kinematic platform following a waypoint path. Its position is a pure function of the
+/// physics step, so client and server agree on it without sending it over the network.
+#[derive(Debug, Deserialize, Clone)]
+pub struct MovingPlatform {
+    pub w: f32,
+    pub h: f32,
+    /// Top-left corner of the platform at each waypoint
+    pub path: Vec<Vec2>,
+    /// px/s along the path
+    pub speed: f32,
+    #[serde(default)]
+    pub mode: PathMode,
+}
+
+impl MovingPlatform {
+    /// Top-left corner `time_secs` after the platform started at the first waypoint
+    pub fn position_at(&self, time_secs: f64) -> Vec2 {
+        let Some(first) = self.path.first() else {
+            return Vec2::ZERO;
+        };
+
+        let mut segments: Vec<(Vec2, Vec2)> = self.path.windows(2).map(|w| (w[0], w[1])).collect();
+        match self.mode {
+            PathMode::PingPong => {
+                let back: Vec<(Vec2, Vec2)> =
+                    segments.iter().rev().map(|(a, b)| (*b, *a)).collect();
+                segments.extend(back);
+            }
+            PathMode::Loop => {
+                if let Some(last) = self.path.last() {
+                    segments.push((*last, *first));
+                }
+            }
+        }
+
+        let length = |(a, b): &(Vec2, Vec2)| {
+            let d = *b - *a;
+            ((d.x * d.x + d.y * d.y) as f64).sqrt()
+        };
+        let cycle: f64 = segments.iter().map(length).sum();
+        if cycle <= 0.0 || self.speed <= 0.0 {
+            return *first;
+        }
+
+        // f64 so platforms don't start to jitter after the server has been up for a while
+        let mut distance = (time_secs * self.speed as f64).rem_euclid(cycle);
+        for segment in &segments {
+            let segment_length = length(segment);
+            if distance <= segment_length {
+                let (a, b) = *segment;
+                let t = if segment_length > 0.0 {
+                    (distance / segment_length) as f32
+                } else {
+                    0.0
+                };
+                return a + (b - a) * t;
+            }
+            distance -= segment_length;
+        }
+        *first
+    }
+
+    pub fn rect_at(&self, time_secs: f64) -> SceneObject {
+        let pos = self.position_at(time_secs);
+        SceneObject {
+            x: pos.x,
+            y: pos.y,
+            w: self.w,
+            h: self.h,
+            kind: CollidableKind::Solid,
+        }
+    }
+}
+
+impl GameState {
+    /// Where the moving platforms are after `physics_step` fixed steps
+    pub fn moving_platform_rects(&self, physics_step: u32) -> Vec<SceneObject> {
+        let time_secs = physics_step as f64 * FIXED_DT_MICROS as f64 / 1_000_000.0;
+        self.moving_platforms
+            .iter()
+            .map(|platform| platform.rect_at(time_secs))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn create_platform(mode: PathMode) -> MovingPlatform {
+        MovingPlatform {
+            w: 80.0,
+            h: 20.0,
+            path: vec![
+                Vec2::new(0.0, 100.0),
+                Vec2::new(100.0, 100.0),
+                Vec2::new(100.0, 0.0),
+            ],
+            speed: 50.0,
+            mode,
+        }
+    }
+
+    fn assert_near(actual: Vec2, expected: Vec2) {
+        assert!(
+            (actual.x - expected.x).abs() < 1e-3 && (actual.y - expected.y).abs() < 1e-3,
+            "expected {}, got {}",
+            expected,
+            actual
+        );
+    }
+
+    #[test]
+    fn test_follows_waypoints() {
+        let platform = create_platform(PathMode::PingPong);
+
+        assert_near(platform.position_at(0.0), Vec2::new(0.0, 100.0));
+        assert_near(platform.position_at(1.0), Vec2::new(50.0, 100.0));
+        assert_near(platform.position_at(3.0), Vec2::new(100.0, 50.0));
+        assert_near(platform.position_at(4.0), Vec2::new(100.0, 0.0));
+    }
+
+    #[test]
+    fn test_ping_pong_travels_back() {
+        let platform = create_platform(PathMode::PingPong);
+
+        assert_near(platform.position_at(5.0), Vec2::new(100.0, 50.0));
+        assert_near(platform.position_at(7.0), Vec2::new(50.0, 100.0));
+        assert_near(platform.position_at(8.0), Vec2::new(0.0, 100.0));
+        assert_near(platform.position_at(9.0), Vec2::new(50.0, 100.0));
+    }
+
+    #[test]
+    fn test_loop_returns_directly_to_start() {
+        let platform = create_platform(PathMode::Loop);
+        // Closing segment is the diagonal from (100, 0) to (0, 100)
+        let diagonal = (2.0f64 * 100.0 * 100.0).sqrt();
+
+        let halfway_back = 4.0 + diagonal / 2.0 / 50.0;
+        assert_near(platform.position_at(halfway_back), Vec2::new(50.0, 50.0));
+        assert_near(
+            platform.position_at(4.0 + diagonal / 50.0),
+            Vec2::new(0.0, 100.0),
+        );
+    }
+
+    #[test]
+    fn test_parses_from_scene_json() {
+        let json = r#"{
+            "w": 80, "h": 20, "speed": 60,
+            "path": [{ "x": 0, "y": 0 }, { "x": 200, "y": 0 }],
+            "mode": "loop",
+            "color": { "r": 1, "g": 2, "b": 3, "a": 255 }
+        }"#;
+        let platform: MovingPlatform = serde_json::from_str(json).unwrap();
+
+        assert_eq!(platform.mode, PathMode::Loop);
+        assert_eq!(platform.path.len(), 2);
+    }
+}
@@ -0,0 +1,214 @@
+use std::collections::HashMap;
+
+use super::{GameState, Vec2};
+
+/// Tuning for snapshot packing.
+#[derive(Debug, Clone)]
+pub struct PriorityConfig {
+    /// Max size of a single snapshot in bytes, kept well below a typical 1500 byte MTU
+    pub byte_budget: usize,
+    /// Priority gained per tick by a player standing right next to the client
+    pub distance_weight: f32,
+    /// Distance at which the distance term has dropped to half
+    pub distance_falloff: f32,
+    /// Priority gained per tick per px/s of velocity change since the player was last sent
+    pub velocity_weight: f32,
+}
+
+impl Default for PriorityConfig {
+    fn default() -> Self {
+        PriorityConfig {
+            byte_budget: 1200,
+            distance_weight: 1.0,
+            distance_falloff: 200.0,
+            velocity_weight: 0.002,
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+struct EntityPriority {
+    accumulated: f32,
+    last_sent_vel: Vec2,
+}
+
+/// Ranks other players per client so the most important ones make it into a snapshot
+/// when not everything fits. Players that don't fit keep their priority for the next tick.
+pub struct SnapshotPrioritizer {
+    config: PriorityConfig,
+    clients: HashMap<u32, HashMap<u32, EntityPriority>>,
+}
+
+impl SnapshotPrioritizer {
+    pub fn new(config: PriorityConfig) -> Self {
+        SnapshotPrioritizer {
+            config,
+            clients: HashMap::new(),
+        }
+    }
+
+    pub fn byte_budget(&self) -> usize {
+        self.config.byte_budget
+    }
+
+    /// Add this tick's priority to every candidate and return them, most important first
+    pub fn rank(&mut self, state: &GameState, client_id: u32, candidates: &[u32]) -> Vec<u32> {
+        let Some(client_player) = state.players.get(&client_id) else {
+            return Vec::new();
+        };
+        let entities = self.clients.entry(client_id).or_default();
+
+        let mut ranked: Vec<(u32, f32)> = Vec::with_capacity(candidates.len());
+        for id in candidates {
+            let Some(player) = state.players.get(id) else {
+                continue;
+            };
+            let entity = entities.entry(*id).or_insert(EntityPriority {
+                accumulated: 0.0,
+                last_sent_vel: player.vel,
+            });
+
+            let offset = player.pos - client_player.pos;
+            let distance = (offset.x * offset.x + offset.y * offset.y).sqrt();
+            let vel_change = player.vel - entity.last_sent_vel;
+            let vel_change = (vel_change.x * vel_change.x + vel_change.y * vel_change.y).sqrt();
+
+            entity.accumulated += self.config.distance_weight * self.config.distance_falloff
+                / (self.config.distance_falloff + distance)
+                + self.config.velocity_weight * vel_change;
+            ranked.push((*id, entity.accumulated));
+        }
+
+        ranked.sort_by(|a, b| b.1.total_cmp(&a.1).then(a.0.cmp(&b.0)));
+        ranked.into_iter().map(|(id, _)| id).collect()
+    }
+
+    /// Reset the priority of players that made it into the snapshot
+    pub fn mark_sent(&mut self, state: &GameState, client_id: u32, sent: &[u32]) {
+        let Some(entities) = self.clients.get_mut(&client_id) else {
+            return;
+        };
+        for id in sent {
+            if let (Some(entity), Some(player)) = (entities.get_mut(id), state.players.get(id)) {
+                entity.accumulated = 0.0;
+                entity.last_sent_vel = player.vel;
+            }
+        }
+    }
+
+    /// Drop the carried priority of players the client no longer tracks
+    pub fn forget(&mut self, client_id: u32, player_ids: &[u32]) {
+        if let Some(entities) = self.clients.get_mut(&client_id) {
+            for id in player_ids {
+                entities.remove(id);
+            }
+        }
+    }
+
+    pub fn remove_client(&mut self, client_id: u32) {
+        self.clients.remove(&client_id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::{PlayerState, SendContext, SpawnPoint};
+    use flatbuffers::FlatBufferBuilder;
+
+    fn create_test_state() -> GameState {
+        GameState::empty(3000.0, 600.0)
+    }
+
+    fn set_player(state: &mut GameState, id: u32, x: f32) {
+        let player = PlayerState::new(id, &SpawnPoint { x, y: 100.0 });
+        state.players.insert(id, player);
+    }
+
+    #[test]
+    fn test_closer_players_rank_first() {
+        let mut state = create_test_state();
+        set_player(&mut state, 1, 0.0);
+        set_player(&mut state, 2, 500.0);
+        set_player(&mut state, 3, 50.0);
+        set_player(&mut state, 4, 200.0);
+        let mut prioritizer = SnapshotPrioritizer::new(PriorityConfig::default());
+
+        let ranked = prioritizer.rank(&state, 1, &[2, 3, 4]);
+
+        assert_eq!(ranked, vec![3, 4, 2]);
+    }
+
+    #[test]
+    fn test_velocity_change_raises_priority() {
+        let mut state = create_test_state();
+        set_player(&mut state, 1, 0.0);
+        set_player(&mut state, 2, 100.0);
+        set_player(&mut state, 3, 150.0);
+        let mut prioritizer = SnapshotPrioritizer::new(PriorityConfig::default());
+        prioritizer.rank(&state, 1, &[2, 3]);
+        prioritizer.mark_sent(&state, 1, &[2, 3]);
+
+        state.players.get_mut(&3).unwrap().vel = Vec2::new(800.0, 0.0);
+        let ranked = prioritizer.rank(&state, 1, &[2, 3]);
+
+        assert_eq!(ranked, vec![3, 2]);
+    }
+
+    #[test]
+    fn test_starved_players_carry_priority() {
+        let mut state = create_test_state();
+        set_player(&mut state, 1, 0.0);
+        set_player(&mut state, 2, 10.0);
+        set_player(&mut state, 3, 1000.0);
+        let mut prioritizer = SnapshotPrioritizer::new(PriorityConfig::default());
+
+        // Only one player fits per tick: the far one has to win eventually
+        let mut far_sent_at = None;
+        for tick in 0..20 {
+            let ranked = prioritizer.rank(&state, 1, &[2, 3]);
+            prioritizer.mark_sent(&state, 1, &ranked[..1]);
+            if ranked[0] == 3 {
+                far_sent_at = Some(tick);
+                break;
+            }
+        }
+
+        assert!(far_sent_at.is_some(), "Far player should not starve");
+    }
+
+    #[test]
+    fn test_snapshot_stays_within_budget() {
+        let mut state = create_test_state();
+        for id in 1..=100 {
+            set_player(&mut state, id, id as f32);
+        }
+        let mut prioritizer = SnapshotPrioritizer::new(PriorityConfig::default());
+        let candidates: Vec<u32> = (2..=100).collect();
+        let ranked = prioritizer.rank(&state, 1, &candidates);
+
+        let mut builder = FlatBufferBuilder::new();
+        let context = SendContext {
+            client_player_id: 1,
+            ..Default::default()
+        };
+        let sent = state.serialize_within_budget(
+            &mut builder,
+            &context,
+            &ranked,
+            &[],
+            prioritizer.byte_budget(),
+        );
+
+        let bytes = builder.finished_data();
+        assert!(bytes.len() <= prioritizer.byte_budget());
+        assert!(sent > 0 && sent < ranked.len());
+
+        let snapshot = state.deserialize(bytes);
+        assert_eq!(snapshot.client_player.id, 1);
+        assert_eq!(snapshot.players.len(), sent);
+        for id in &ranked[..sent] {
+            assert!(snapshot.players.contains_key(id));
+        }
+    }
+}
@@ -0,0 +1,493 @@
+use std::collections::VecDeque;
+
+use super::mutate::FIXED_DT_MICROS;
+use super::{GameMode, GameState, Touch, TouchTarget, Vec2};
+
+/// Seconds from the round being set up until players may move
+pub const COUNTDOWN_SECS: f32 = 3.0;
+/// Seconds the results are shown before the next round starts
+pub const RESULTS_SECS: f32 = 8.0;
+/// Seconds the others get to finish after the winner before the round is called
+pub const FINISH_GRACE_SECS: f32 = 30.0;
+/// Events are repeated in every snapshot for this long, so a lost packet doesn't lose them
+const EVENT_RESEND_SECS: f32 = 2.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum RacePhase {
+    /// No players on the server
+    #[default]
+    Waiting,
+    /// Everyone is at the spawn point and input is ignored
+    Countdown,
+    Running,
+    /// Round is over and the finish order is final
+    Results,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RaceEventKind {
+    /// A new round was set up, the race starts in `seconds`
+    Countdown {
+        seconds: f32,
+    },
+    Started,
+    /// `player_id` reached the win point as number `place`, `time` seconds after the start
+    Finished {
+        player_id: u32,
+        place: u32,
+        time: f32,
+    },
+    /// The round is over, the next countdown starts in `seconds`
+    Results {
+        seconds: f32,
+    },
+}
+
+/// Round state change, sent from the server to every client.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RaceEvent {
+    /// Increases by one per event, clients use it to skip repeats
+    pub id: u32,
+    pub round: u32,
+    pub kind: RaceEventKind,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RaceResult {
+    pub player_id: u32,
+    /// Seconds from the start until the player reached the win point
+    pub time: f32,
+}
+
+/// Server side race: counts down, times every player from the start to the win point and
+/// restarts the round once everyone is through. Clients follow along through the events.
+#[derive(Debug, Default)]
+pub struct Race {
+    phase: RacePhase,
+    /// Seconds spent in the current phase
+    phase_time: f32,
+    round: u32,
+    start_step: u32,
+    results: Vec<RaceResult>,
+    events: EventLog,
+}
+
+impl Race {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn phase(&self) -> RacePhase {
+        self.phase
+    }
+
+    pub fn round(&self) -> u32 {
+        self.round
+    }
+
+    /// Finish order of the current round
+    pub fn results(&self) -> &[RaceResult] {
+        &self.results
+    }
+
+    /// Events a snapshot sent now should carry, oldest first
+    pub fn recent_events(&self) -> Vec<RaceEvent> {
+        self.events.recent()
+    }
+
+    fn start_countdown(&mut self, state: &mut GameState) {
+        self.round += 1;
+        self.results.clear();
+        let spawn = Vec2::new(state.spawn_point.x, state.spawn_point.y);
+        for player in state.players.values_mut() {
+            player.respawn_point = spawn;
+            player.respawn();
+        }
+        self.enter(RacePhase::Countdown);
+        self.emit(RaceEventKind::Countdown {
+            seconds: COUNTDOWN_SECS,
+        });
+    }
+
+    fn enter(&mut self, phase: RacePhase) {
+        self.phase = phase;
+        self.phase_time = 0.0;
+    }
+
+    fn emit(&mut self, kind: RaceEventKind) {
+        self.events.emit(self.round, kind);
+    }
+}
+
+/// Round events of a mode, with the seconds since each was emitted. Tag reports its rounds
+/// through the same events as the race.
+#[derive(Debug, Default)]
+pub(super) struct EventLog {
+    next_event_id: u32,
+    recent: VecDeque<(f32, RaceEvent)>,
+}
+
+impl EventLog {
+    pub(super) fn emit(&mut self, round: u32, kind: RaceEventKind) {
+        self.next_event_id += 1;
+        let event = RaceEvent {
+            id: self.next_event_id,
+            round,
+            kind,
+        };
+        self.recent.push_back((0.0, event));
+    }
+
+    /// Let `dt` seconds pass and drop the events that were repeated long enough
+    pub(super) fn age(&mut self, dt: f32) {
+        for (age, _) in &mut self.recent {
+            *age += dt;
+        }
+        self.recent.retain(|(age, _)| *age <= EVENT_RESEND_SECS);
+    }
+
+    pub(super) fn recent(&self) -> Vec<RaceEvent> {
+        self.recent.iter().map(|(_, event)| *event).collect()
+    }
+}
+
+impl GameMode for Race {
+    fn name(&self) -> &'static str {
+        "race"
+    }
+
+    /// Player input is dropped during the countdown so nobody gets a head start
+    fn accepts_input(&self, _player_id: u32) -> bool {
+        self.phase != RacePhase::Countdown
+    }
+
+    /// Reaching the win point finishes the player's run, it doesn't move anyone
+    fn on_trigger(&mut self, state: &mut GameState, touch: Touch) {
+        let finished = self.results.iter().any(|r| r.player_id == touch.player_id);
+        if self.phase != RacePhase::Running
+            || touch.target != TouchTarget::WinPoint
+            || finished
+            || !state.players.contains_key(&touch.player_id)
+        {
+            return;
+        }
+
+        let steps = touch.step.wrapping_sub(self.start_step);
+        let time = (steps as f64 * FIXED_DT_MICROS as f64 / 1_000_000.0) as f32;
+        self.results.push(RaceResult {
+            player_id: touch.player_id,
+            time,
+        });
+        self.emit(RaceEventKind::Finished {
+            player_id: touch.player_id,
+            place: self.results.len() as u32,
+            time,
+        });
+    }
+
+    fn on_tick(&mut self, state: &mut GameState, dt: f32) {
+        self.events.age(dt);
+        self.phase_time += dt;
+
+        match self.phase {
+            RacePhase::Waiting => {
+                if !state.players.is_empty() {
+                    self.start_countdown(state);
+                }
+            }
+            RacePhase::Countdown => {
+                if self.phase_time >= COUNTDOWN_SECS {
+                    self.enter(RacePhase::Running);
+                    self.start_step = state.physics_step;
+                    self.emit(RaceEventKind::Started);
+                }
+            }
+            RacePhase::Running => {
+                let everyone_finished = state
+                    .players
+                    .keys()
+                    .all(|id| self.results.iter().any(|r| r.player_id == *id));
+                let grace_over = self
+                    .results
+                    .first()
+                    .is_some_and(|winner| self.phase_time - winner.time >= FINISH_GRACE_SECS);
+
+                if state.players.is_empty() {
+                    self.enter(RacePhase::Waiting);
+                } else if everyone_finished || grace_over {
+                    self.enter(RacePhase::Results);
+                    self.emit(RaceEventKind::Results {
+                        seconds: RESULTS_SECS,
+                    });
+                }
+            }
+            RacePhase::Results => {
+                if self.phase_time >= RESULTS_SECS {
+                    self.start_countdown(state);
+                }
+            }
+        }
+
+        state.race_events = self.recent_events();
+    }
+
+    /// Finish times, fastest first
+    fn scores(&self) -> Vec<(u32, f32)> {
+        self.results.iter().map(|r| (r.player_id, r.time)).collect()
+    }
+
+    fn winner(&self) -> Option<u32> {
+        self.results.first().map(|r| r.player_id)
+    }
+}
+
+/// Client side picture of the race, built from the server's events. Times are unix
+/// microseconds passed in by the caller.
+#[derive(Debug, Default)]
+pub struct RaceView {
+    last_event_id: u32,
+    phase: RacePhase,
+    round: u32,
+    /// When the current phase started here, and how long it lasts if it has an end
+    phase_started: Option<u64>,
+    phase_length: f32,
+    results: Vec<(u32, RaceResult)>,
+}
+
+impl RaceView {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn phase(&self) -> RacePhase {
+        self.phase
+    }
+
+    pub fn round(&self) -> u32 {
+        self.round
+    }
+
+    /// Apply the events not seen before. Events repeat over several snapshots.
+    pub fn apply(&mut self, events: &[RaceEvent], now_micros: u64) {
+        for event in events {
+            if event.id <= self.last_event_id {
+                continue;
+            }
+            self.last_event_id = event.id;
+            self.round = event.round;
+
+            match event.kind {
+                RaceEventKind::Countdown { seconds } => {
+                    self.results.clear();
+                    self.enter(RacePhase::Countdown, seconds, now_micros);
+                }
+                // Tag starts its rounds without a countdown
+                RaceEventKind::Started => {
+                    self.results.clear();
+                    self.enter(RacePhase::Running, 0.0, now_micros);
+                }
+                RaceEventKind::Finished {
+                    player_id,
+                    place,
+                    time,
+                } => self.results.push((place, RaceResult { player_id, time })),
+                RaceEventKind::Results { seconds } => {
+                    self.enter(RacePhase::Results, seconds, now_micros)
+                }
+            }
+        }
+    }
+
+    /// Seconds until the countdown or results phase ends
+    pub fn seconds_left(&self, now_micros: u64) -> f32 {
+        (self.phase_length - self.phase_time(now_micros)).max(0.0)
+    }
+
+    /// Seconds since the race started, while it is running
+    pub fn run_time(&self, now_micros: u64) -> Option<f32> {
+        (self.phase == RacePhase::Running).then(|| self.phase_time(now_micros))
+    }
+
+    /// Place and result of every finished player, in finish order
+    pub fn results(&self) -> impl Iterator<Item = &(u32, RaceResult)> {
+        self.results.iter()
+    }
+
+    pub fn result_for(&self, player_id: u32) -> Option<&(u32, RaceResult)> {
+        self.results.iter().find(|(_, r)| r.player_id == player_id)
+    }
+
+    fn enter(&mut self, phase: RacePhase, length: f32, now_micros: u64) {
+        self.phase = phase;
+        self.phase_length = length;
+        self.phase_started = Some(now_micros);
+    }
+
+    fn phase_time(&self, now_micros: u64) -> f32 {
+        self.phase_started
+            .map(|started| now_micros.saturating_sub(started) as f32 / 1_000_000.0)
+            .unwrap_or(0.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::{PlayerState, SpawnPoint};
+
+    fn create_test_state() -> GameState {
+        let mut state = GameState::empty(800.0, 600.0);
+        state.spawn_point = SpawnPoint { x: 10.0, y: 500.0 };
+        state.win_point.y = 500.0;
+        state.rebuild_collision_grid();
+        for id in 1..=3 {
+            let player = PlayerState::new(id, &state.spawn_point);
+            state.players.insert(id, player);
+        }
+        state
+    }
+
+    fn finish(race: &mut Race, state: &mut GameState, player_id: u32, step: u32) {
+        let touch = Touch {
+            player_id,
+            step,
+            target: TouchTarget::WinPoint,
+        };
+        race.on_trigger(state, touch);
+    }
+
+    fn kinds(race: &Race) -> Vec<RaceEventKind> {
+        race.recent_events().iter().map(|e| e.kind).collect()
+    }
+
+    /// Countdown and start, leaving the race running at physics step 100
+    fn start_race(race: &mut Race, state: &mut GameState) {
+        state.physics_step = 100;
+        race.on_tick(state, 0.1);
+        race.on_tick(state, COUNTDOWN_SECS);
+        assert_eq!(race.phase(), RacePhase::Running);
+    }
+
+    #[test]
+    fn test_countdown_holds_input_and_resets_players() {
+        let mut state = create_test_state();
+        state.players.get_mut(&2).unwrap().pos = Vec2::new(400.0, 100.0);
+        let mut race = Race::new();
+
+        race.on_tick(&mut state, 0.1);
+
+        assert_eq!(race.phase(), RacePhase::Countdown);
+        assert!(!race.accepts_input(1));
+        assert_eq!(
+            state.players[&2].pos.x, 10.0,
+            "Round starts at the spawn point"
+        );
+        assert_eq!(
+            kinds(&race),
+            vec![RaceEventKind::Countdown {
+                seconds: COUNTDOWN_SECS
+            }]
+        );
+
+        race.on_tick(&mut state, COUNTDOWN_SECS);
+        assert_eq!(race.phase(), RacePhase::Running);
+        assert!(race.accepts_input(1));
+    }
+
+    #[test]
+    fn test_finish_order_and_times() {
+        let mut state = create_test_state();
+        let mut race = Race::new();
+        start_race(&mut race, &mut state);
+
+        // Player 3 in 1.6 seconds, player 1 in 3.2 seconds, both in the same tick
+        finish(&mut race, &mut state, 3, 200);
+        finish(&mut race, &mut state, 1, 300);
+        race.on_tick(&mut state, 0.3);
+        // Touching again doesn't change anything
+        finish(&mut race, &mut state, 3, 400);
+        race.on_tick(&mut state, 0.3);
+
+        let results = race.results();
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].player_id, 3);
+        assert!((results[0].time - 1.6).abs() < 1e-4);
+        assert_eq!(results[1].player_id, 1);
+        assert!((results[1].time - 3.2).abs() < 1e-4);
+        assert_eq!(race.phase(), RacePhase::Running, "Player 2 is still racing");
+    }
+
+    #[test]
+    fn test_round_restarts_after_results() {
+        let mut state = create_test_state();
+        let mut race = Race::new();
+        start_race(&mut race, &mut state);
+
+        for id in 1..=3 {
+            finish(&mut race, &mut state, id, 150 + id);
+        }
+        race.on_tick(&mut state, 0.3);
+        assert_eq!(race.phase(), RacePhase::Results);
+        assert!(
+            race.recent_events()
+                .iter()
+                .any(|e| matches!(e.kind, RaceEventKind::Results { .. }))
+        );
+
+        race.on_tick(&mut state, RESULTS_SECS);
+        assert_eq!(race.phase(), RacePhase::Countdown);
+        assert_eq!(race.round(), 2);
+        assert!(race.results().is_empty());
+    }
+
+    #[test]
+    fn test_grace_period_ends_round() {
+        let mut state = create_test_state();
+        let mut race = Race::new();
+        start_race(&mut race, &mut state);
+
+        finish(&mut race, &mut state, 1, 200);
+        race.on_tick(&mut state, 2.0);
+        race.on_tick(&mut state, FINISH_GRACE_SECS);
+
+        assert_eq!(race.phase(), RacePhase::Results);
+        assert_eq!(race.results().len(), 1);
+    }
+
+    #[test]
+    fn test_view_skips_repeated_events() {
+        let mut state = create_test_state();
+        let mut race = Race::new();
+        start_race(&mut race, &mut state);
+        finish(&mut race, &mut state, 2, 200);
+        race.on_tick(&mut state, 0.3);
+
+        let mut view = RaceView::new();
+        let now = 1_700_000_000_000_000;
+        // Every snapshot repeats the recent events
+        view.apply(&race.recent_events(), now);
+        view.apply(&race.recent_events(), now);
+
+        assert_eq!(view.phase(), RacePhase::Running);
+        assert_eq!(view.round(), 1);
+        assert_eq!(view.results().count(), 1);
+        let (place, result) = view.result_for(2).unwrap();
+        assert_eq!(*place, 1);
+        assert!((result.time - 1.6).abs() < 1e-4);
+    }
+
+    #[test]
+    fn test_events_survive_snapshot_round_trip() {
+        let mut state = create_test_state();
+        let mut race = Race::new();
+        start_race(&mut race, &mut state);
+        finish(&mut race, &mut state, 2, 200);
+        race.on_tick(&mut state, 0.3);
+
+        let mut builder = flatbuffers::FlatBufferBuilder::new();
+        let bytes = state.serialize(&mut builder, 1, 0, 0);
+        let decoded = state.deserialize(bytes);
+
+        assert_eq!(decoded.race_events, state.race_events);
+    }
+}
@@ -0,0 +1,336 @@
+use std::collections::BTreeMap;
+
+use super::race::EventLog;
+use super::{GameMode, GameState, RaceEvent, RaceEventKind, Touch, TouchTarget};
+
+/// Seconds a player that just passed on "it" can't be tagged back
+pub const TAG_IMMUNITY_SECS: f32 = 2.0;
+/// Length of a round, the player who was "it" the shortest wins it
+pub const TAG_ROUND_SECS: f32 = 120.0;
+/// Seconds the results of a round are shown, play is paused meanwhile
+pub const TAG_RESULTS_SECS: f32 = 5.0;
+
+/// One player is "it" and passes the role on by touching someone else. Scores are the
+/// seconds each player has been "it" this round, lowest first.
+#[derive(Debug, Default)]
+pub struct Tag {
+    it: Option<u32>,
+    /// Seconds each player has been "it" this round
+    it_time: BTreeMap<u32, f32>,
+    /// Seconds left until these players can be tagged again
+    immune: BTreeMap<u32, f32>,
+    round: u32,
+    round_time: f32,
+    /// Winner of the last round that ended
+    winner: Option<u32>,
+    /// Seconds the last round's results are still shown
+    results_left: Option<f32>,
+    events: EventLog,
+}
+
+impl Tag {
+    pub fn new() -> Self {
+        Tag::default()
+    }
+
+    pub fn it(&self) -> Option<u32> {
+        self.it
+    }
+
+    /// Number of rounds that have ended
+    pub fn round(&self) -> u32 {
+        self.round
+    }
+
+    pub fn is_immune(&self, player_id: u32) -> bool {
+        self.immune.contains_key(&player_id)
+    }
+
+    fn make_it(&mut self, state: &mut GameState, player_id: Option<u32>) {
+        self.it = player_id;
+        for player in state.players.values_mut() {
+            player.is_it = Some(player.id) == player_id;
+        }
+    }
+
+    /// Events a snapshot sent now should carry, oldest first
+    pub fn recent_events(&self) -> Vec<RaceEvent> {
+        self.events.recent()
+    }
+
+    /// Report the finish order, least time as "it" first, and show it for a while
+    fn end_round(&mut self) {
+        self.winner = self.least_it_time();
+        self.round += 1;
+        self.round_time = 0.0;
+        for (place, (player_id, time)) in self.scores().into_iter().enumerate() {
+            let kind = RaceEventKind::Finished {
+                player_id,
+                place: place as u32 + 1,
+                time,
+            };
+            self.events.emit(self.round, kind);
+        }
+        let kind = RaceEventKind::Results {
+            seconds: TAG_RESULTS_SECS,
+        };
+        self.events.emit(self.round, kind);
+        self.results_left = Some(TAG_RESULTS_SECS);
+        for time in self.it_time.values_mut() {
+            *time = 0.0;
+        }
+    }
+
+    /// The player with the least time as "it", lowest id on ties
+    fn least_it_time(&self) -> Option<u32> {
+        self.it_time
+            .iter()
+            .min_by(|a, b| a.1.total_cmp(b.1).then(a.0.cmp(b.0)))
+            .map(|(id, _)| *id)
+    }
+}
+
+impl GameMode for Tag {
+    fn name(&self) -> &'static str {
+        "tag"
+    }
+
+    /// Nobody moves while the results are shown
+    fn accepts_input(&self, _player_id: u32) -> bool {
+        self.results_left.is_none()
+    }
+
+    /// The first player to join is "it"
+    fn on_join(&mut self, state: &mut GameState, player_id: u32) {
+        self.it_time.insert(player_id, 0.0);
+        if self.it.is_none() {
+            self.make_it(state, Some(player_id));
+        }
+    }
+
+    /// When "it" leaves, the role goes to whoever has had it the least
+    fn on_leave(&mut self, state: &mut GameState, player_id: u32) {
+        self.it_time.remove(&player_id);
+        self.immune.remove(&player_id);
+        if self.it == Some(player_id) {
+            let next = self.least_it_time();
+            self.make_it(state, next);
+        }
+    }
+
+    /// "It" touching a player that isn't immune passes the role on, and the old "it" can't
+    /// be tagged back right away
+    fn on_trigger(&mut self, state: &mut GameState, touch: Touch) {
+        let TouchTarget::Player(other_id) = touch.target else {
+            return;
+        };
+        let Some(it) = self.it else {
+            return;
+        };
+        if self.results_left.is_some() {
+            return;
+        }
+        let tagged = if touch.player_id == it {
+            other_id
+        } else if other_id == it {
+            touch.player_id
+        } else {
+            return;
+        };
+        if self.is_immune(tagged) || !state.players.contains_key(&tagged) {
+            return;
+        }
+
+        self.immune.insert(it, TAG_IMMUNITY_SECS);
+        self.make_it(state, Some(tagged));
+    }
+
+    fn on_tick(&mut self, state: &mut GameState, dt: f32) {
+        self.events.age(dt);
+        if let Some(left) = self.results_left.as_mut() {
+            *left -= dt;
+            if *left > 0.0 {
+                state.race_events = self.events.recent();
+                return;
+            }
+            self.results_left = None;
+        }
+
+        if let Some(it) = self.it {
+            *self.it_time.entry(it).or_insert(0.0) += dt;
+        }
+        for left in self.immune.values_mut() {
+            *left -= dt;
+        }
+        self.immune.retain(|_, left| *left > 0.0);
+
+        if state.players.is_empty() {
+            self.round_time = 0.0;
+        } else {
+            if self.round_time == 0.0 {
+                self.events.emit(self.round + 1, RaceEventKind::Started);
+            }
+            self.round_time += dt;
+            if self.round_time >= TAG_ROUND_SECS {
+                self.end_round();
+            }
+        }
+
+        state.race_events = self.events.recent();
+    }
+
+    /// Seconds as "it" this round, least first
+    fn scores(&self) -> Vec<(u32, f32)> {
+        let mut scores: Vec<(u32, f32)> = self.it_time.iter().map(|(id, t)| (*id, *t)).collect();
+        scores.sort_by(|a, b| a.1.total_cmp(&b.1).then(a.0.cmp(&b.0)));
+        scores
+    }
+
+    fn winner(&self) -> Option<u32> {
+        self.winner
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::{PlayerState, SpawnPoint};
+
+    fn create_test_state() -> GameState {
+        let mut state = GameState::empty(800.0, 600.0);
+        state.spawn_point = SpawnPoint { x: 10.0, y: 500.0 };
+        state.win_point.y = 500.0;
+        state.rebuild_collision_grid();
+        state
+    }
+
+    /// Tag mode with players 1 to 3 joined in order, so player 1 is "it"
+    fn start_tag(state: &mut GameState) -> Tag {
+        let mut tag = Tag::new();
+        for id in 1..=3 {
+            let player = PlayerState::new(id, &state.spawn_point);
+            state.players.insert(id, player);
+            tag.on_join(state, id);
+        }
+        tag
+    }
+
+    fn touch(tag: &mut Tag, state: &mut GameState, player_id: u32, other_id: u32) {
+        let touch = Touch {
+            player_id,
+            step: state.physics_step,
+            target: TouchTarget::Player(other_id),
+        };
+        tag.on_trigger(state, touch);
+    }
+
+    #[test]
+    fn test_touch_passes_it_on() {
+        let mut state = create_test_state();
+        let mut tag = start_tag(&mut state);
+        assert_eq!(tag.it(), Some(1));
+        assert!(state.players[&1].is_it);
+
+        touch(&mut tag, &mut state, 2, 3);
+        assert_eq!(tag.it(), Some(1), "Only touching it matters");
+
+        touch(&mut tag, &mut state, 1, 2);
+        assert_eq!(tag.it(), Some(2));
+        assert!(!state.players[&1].is_it);
+        assert!(state.players[&2].is_it);
+    }
+
+    #[test]
+    fn test_immunity_prevents_tagging_back() {
+        let mut state = create_test_state();
+        let mut tag = start_tag(&mut state);
+        touch(&mut tag, &mut state, 1, 2);
+
+        touch(&mut tag, &mut state, 1, 2);
+        assert_eq!(tag.it(), Some(2), "Player 1 was just tagged away");
+
+        tag.on_tick(&mut state, TAG_IMMUNITY_SECS + 0.1);
+        touch(&mut tag, &mut state, 1, 2);
+        assert_eq!(tag.it(), Some(1));
+    }
+
+    #[test]
+    fn test_least_time_as_it_wins() {
+        let mut state = create_test_state();
+        let mut tag = start_tag(&mut state);
+        tag.on_tick(&mut state, 10.0);
+        touch(&mut tag, &mut state, 1, 2);
+        tag.on_tick(&mut state, 5.0);
+        touch(&mut tag, &mut state, 2, 3);
+        tag.on_tick(&mut state, 1.0);
+
+        assert_eq!(tag.scores(), vec![(3, 1.0), (2, 5.0), (1, 10.0)]);
+        assert_eq!(tag.winner(), None);
+
+        tag.on_tick(&mut state, TAG_ROUND_SECS);
+        assert_eq!(tag.round(), 1);
+        // Player 3 stayed it for the rest of the round
+        assert_eq!(tag.winner(), Some(2));
+        assert!(tag.scores().iter().all(|(_, time)| *time == 0.0));
+    }
+
+    #[test]
+    fn test_it_leaving_passes_role_on() {
+        let mut state = create_test_state();
+        let mut tag = start_tag(&mut state);
+        tag.on_tick(&mut state, 3.0);
+        touch(&mut tag, &mut state, 1, 2);
+        tag.on_tick(&mut state, 1.0);
+
+        state.players.remove(&2);
+        tag.on_leave(&mut state, 2);
+
+        assert_eq!(tag.it(), Some(3), "Player 3 has been it the shortest");
+        assert!(state.players[&3].is_it);
+    }
+
+    #[test]
+    fn test_round_end_reports_results_then_resumes() {
+        let mut state = create_test_state();
+        let mut tag = start_tag(&mut state);
+        tag.on_tick(&mut state, 1.0);
+        touch(&mut tag, &mut state, 1, 2);
+        tag.on_tick(&mut state, TAG_ROUND_SECS);
+
+        let kinds: Vec<RaceEventKind> = state.race_events.iter().map(|e| e.kind).collect();
+        // The round's start is long out of the snapshots
+        assert_eq!(
+            kinds,
+            [
+                RaceEventKind::Finished {
+                    player_id: 3,
+                    place: 1,
+                    time: 0.0
+                },
+                RaceEventKind::Finished {
+                    player_id: 1,
+                    place: 2,
+                    time: 1.0
+                },
+                RaceEventKind::Finished {
+                    player_id: 2,
+                    place: 3,
+                    time: TAG_ROUND_SECS
+                },
+                RaceEventKind::Results {
+                    seconds: TAG_RESULTS_SECS
+                },
+            ]
+        );
+        assert!(state.race_events.iter().all(|e| e.round == 1));
+
+        assert!(!tag.accepts_input(1), "Paused while the results are shown");
+        touch(&mut tag, &mut state, 2, 1);
+        assert_eq!(tag.it(), Some(2));
+
+        tag.on_tick(&mut state, TAG_RESULTS_SECS);
+        assert!(tag.accepts_input(1));
+        let last = state.race_events.last().unwrap();
+        assert_eq!((last.round, last.kind), (2, RaceEventKind::Started));
+    }
+}
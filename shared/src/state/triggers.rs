@@ -0,0 +1,174 @@
+use serde::Deserialize;
+
+use super::physics::check_collision;
+use super::{CollidableKind, CollisionGrid, GridEntry, PlayerState, SceneObject, SpawnPoint, Vec2};
+
+/// What happens to a player that touches a trigger.
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum TriggerKind {
+    /// Kills the player, who respawns at their last checkpoint
+    Hazard,
+    /// Moves the player's respawn point here
+    Checkpoint,
+    /// Sends the player back to the spawn point and clears their checkpoint
+    Finish,
+}
+
+/// Scene-defined volume that affects only the players inside it.
+#[derive(Debug, Deserialize, Clone)]
+pub struct Trigger {
+    pub x: f32,
+    pub y: f32,
+    pub w: f32,
+    pub h: f32,
+    pub kind: TriggerKind,
+}
+
+impl Trigger {
+    pub fn rect(&self) -> SceneObject {
+        SceneObject {
+            x: self.x,
+            y: self.y,
+            w: self.w,
+            h: self.h,
+            kind: CollidableKind::Solid,
+        }
+    }
+
+    /// Where a player of `size` respawns after touching this checkpoint: standing on the
+    /// bottom of the volume, centred
+    fn respawn_point(&self, size: f32) -> Vec2 {
+        Vec2::new(self.x + (self.w - size) / 2.0, self.y + self.h - size)
+    }
+}
+
+impl PlayerState {
+    /// Put the player back at their respawn point, standing still
+    pub fn respawn(&mut self) {
+        self.pos = self.respawn_point;
+        self.vel = Vec2::ZERO;
+        self.grounded = false;
+    }
+}
+
+/// Apply the triggers `player` overlaps and return their indices. Checkpoints are taken
+/// before hazards are checked, so a hazard inside a checkpoint respawns the player at that
+/// checkpoint.
+pub(super) fn apply_triggers(
+    player: &mut PlayerState,
+    triggers: &[Trigger],
+    grid: &CollisionGrid,
+    spawn_point: &SpawnPoint,
+) -> Vec<usize> {
+    let player_rect = SceneObject {
+        x: player.pos.x,
+        y: player.pos.y,
+        w: player.size,
+        h: player.size,
+        kind: CollidableKind::Solid,
+    };
+    let touched: Vec<usize> = grid
+        .query(&player_rect)
+        .into_iter()
+        .filter_map(|entry| match entry {
+            GridEntry::Trigger(index) => Some(index),
+            _ => None,
+        })
+        .filter(|index| check_collision(&player_rect, &triggers[*index].rect()))
+        .collect();
+
+    let touching = |kind: TriggerKind| {
+        touched
+            .iter()
+            .map(|index| &triggers[*index])
+            .filter(move |t| t.kind == kind)
+    };
+
+    if let Some(checkpoint) = touching(TriggerKind::Checkpoint).next() {
+        player.respawn_point = checkpoint.respawn_point(player.size);
+    }
+    if touching(TriggerKind::Hazard).next().is_some() {
+        player.respawn();
+    } else if touching(TriggerKind::Finish).next().is_some() {
+        player.respawn_point = Vec2::new(spawn_point.x, spawn_point.y);
+        player.respawn();
+    }
+    touched
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn trigger(x: f32, kind: TriggerKind) -> Trigger {
+        Trigger {
+            x,
+            y: 400.0,
+            w: 40.0,
+            h: 100.0,
+            kind,
+        }
+    }
+
+    fn create_test_player(x: f32) -> PlayerState {
+        PlayerState::new(1, &SpawnPoint { x, y: 450.0 })
+    }
+
+    fn apply(player: &mut PlayerState, triggers: &[Trigger]) {
+        let win_point = Trigger {
+            x: 5000.0,
+            ..trigger(0.0, TriggerKind::Finish)
+        };
+        let grid = CollisionGrid::new(&[], triggers, &win_point.rect());
+        apply_triggers(player, triggers, &grid, &SpawnPoint { x: 0.0, y: 0.0 });
+    }
+
+    #[test]
+    fn test_hazard_respawns_at_last_checkpoint() {
+        let triggers = vec![
+            trigger(100.0, TriggerKind::Checkpoint),
+            trigger(300.0, TriggerKind::Hazard),
+        ];
+        let mut player = create_test_player(110.0);
+        apply(&mut player, &triggers);
+        assert_eq!(player.pos.x, 110.0, "Checkpoints don't move the player");
+
+        player.pos = Vec2::new(310.0, 450.0);
+        player.vel = Vec2::new(200.0, 50.0);
+        apply(&mut player, &triggers);
+
+        assert_eq!(player.pos.x, 100.0 + (40.0 - player.size) / 2.0);
+        assert_eq!(player.pos.y, 500.0 - player.size);
+        assert_eq!(player.vel.x, 0.0);
+    }
+
+    #[test]
+    fn test_hazard_without_checkpoint_respawns_at_spawn() {
+        let triggers = vec![trigger(300.0, TriggerKind::Hazard)];
+        let mut player = create_test_player(50.0);
+        player.pos = Vec2::new(310.0, 450.0);
+
+        apply(&mut player, &triggers);
+
+        assert_eq!(player.pos.x, 50.0);
+        assert_eq!(player.pos.y, 450.0);
+    }
+
+    #[test]
+    fn test_finish_clears_checkpoint() {
+        let triggers = vec![
+            trigger(100.0, TriggerKind::Checkpoint),
+            trigger(300.0, TriggerKind::Finish),
+        ];
+        let mut player = create_test_player(110.0);
+        apply(&mut player, &triggers);
+
+        player.pos = Vec2::new(310.0, 450.0);
+        apply(&mut player, &triggers);
+
+        assert_eq!(player.pos.x, 0.0);
+        assert_eq!(player.pos.y, 0.0);
+        assert_eq!(player.respawn_point.x, 0.0);
+    }
+}
@@ -0,0 +1,46 @@
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Source of time for anything that has to run on a virtual clock in tests
+pub trait Clock: Send + Sync {
+    /// Microseconds since the unix epoch, or since whatever the clock started at
+    fn now_micros(&self) -> u64;
+}
+
+/// The wall clock
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now_micros(&self) -> u64 {
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_micros() as u64
+    }
+}
+
+/// A clock that only moves when told to. Clones share the same time.
+#[derive(Debug, Clone, Default)]
+pub struct ManualClock {
+    now_micros: Arc<AtomicU64>,
+}
+
+impl ManualClock {
+    pub fn new(start_micros: u64) -> Self {
+        ManualClock {
+            now_micros: Arc::new(AtomicU64::new(start_micros)),
+        }
+    }
+
+    pub fn advance(&self, dt_micros: u64) {
+        self.now_micros.fetch_add(dt_micros, Ordering::SeqCst);
+    }
+}
+
+impl Clock for ManualClock {
+    fn now_micros(&self) -> u64 {
+        self.now_micros.load(Ordering::SeqCst)
+    }
+}
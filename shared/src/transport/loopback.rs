@@ -0,0 +1,126 @@
+use std::collections::{HashMap, VecDeque};
+use std::io;
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+
+use super::Transport;
+
+/// First port handed out when binding port 0, the start of the ephemeral range
+const FIRST_EPHEMERAL_PORT: u16 = 49152;
+
+type Inboxes = HashMap<SocketAddr, VecDeque<(Vec<u8>, SocketAddr)>>;
+
+/// Addresses that only exist in this process. Datagrams arrive instantly, in order and
+/// without loss; wrap an end in a `SimulatedTransport` for anything worse. Clones share
+/// the same network.
+#[derive(Clone, Default)]
+pub struct LoopbackNetwork {
+    inboxes: Arc<Mutex<Inboxes>>,
+}
+
+impl LoopbackNetwork {
+    pub fn new() -> Self {
+        LoopbackNetwork::default()
+    }
+
+    /// Take `addr` on this network, any free port if its port is 0
+    pub fn bind(&self, addr: SocketAddr) -> io::Result<LoopbackTransport> {
+        let mut inboxes = self.inboxes.lock().unwrap();
+        let mut addr = addr;
+        if addr.port() == 0 {
+            let port = (FIRST_EPHEMERAL_PORT..=u16::MAX)
+                .find(|port| !inboxes.contains_key(&SocketAddr::new(addr.ip(), *port)))
+                .ok_or_else(|| io::Error::new(io::ErrorKind::AddrInUse, "No free port"))?;
+            addr.set_port(port);
+        }
+        if inboxes.contains_key(&addr) {
+            return Err(io::Error::new(
+                io::ErrorKind::AddrInUse,
+                format!("{} is already bound", addr),
+            ));
+        }
+
+        inboxes.insert(addr, VecDeque::new());
+        Ok(LoopbackTransport {
+            addr,
+            network: self.clone(),
+        })
+    }
+}
+
+/// One address on a `LoopbackNetwork`, freed again when dropped
+pub struct LoopbackTransport {
+    addr: SocketAddr,
+    network: LoopbackNetwork,
+}
+
+impl Transport for LoopbackTransport {
+    fn send(&self, bytes: &[u8], addr: SocketAddr) -> io::Result<usize> {
+        let mut inboxes = self.network.inboxes.lock().unwrap();
+        if let Some(inbox) = inboxes.get_mut(&addr) {
+            inbox.push_back((bytes.to_vec(), self.addr));
+        }
+        Ok(bytes.len())
+    }
+
+    /// Datagrams longer than `buf` are cut off, like with UDP
+    fn recv(&self, buf: &mut [u8]) -> io::Result<Option<(usize, SocketAddr)>> {
+        let mut inboxes = self.network.inboxes.lock().unwrap();
+        let Some((bytes, from)) = inboxes.get_mut(&self.addr).and_then(VecDeque::pop_front) else {
+            return Ok(None);
+        };
+        let amt = bytes.len().min(buf.len());
+        buf[..amt].copy_from_slice(&bytes[..amt]);
+        Ok(Some((amt, from)))
+    }
+
+    fn local_addr(&self) -> io::Result<SocketAddr> {
+        Ok(self.addr)
+    }
+}
+
+impl Drop for LoopbackTransport {
+    fn drop(&mut self) {
+        self.network.inboxes.lock().unwrap().remove(&self.addr);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn addr(port: u16) -> SocketAddr {
+        SocketAddr::from(([10, 0, 0, 1], port))
+    }
+
+    #[test]
+    fn test_datagrams_reach_the_bound_address() {
+        let network = LoopbackNetwork::new();
+        let a = network.bind(addr(1)).unwrap();
+        let b = network.bind(addr(2)).unwrap();
+        let mut buf = [0u8; 16];
+
+        a.send(&[1, 2, 3], addr(2)).unwrap();
+        a.send(&[4], addr(2)).unwrap();
+        a.send(&[5], addr(3)).unwrap();
+
+        assert_eq!(b.recv(&mut buf).unwrap(), Some((3, addr(1))));
+        assert_eq!(&buf[..3], &[1, 2, 3]);
+        assert_eq!(b.recv(&mut buf).unwrap(), Some((1, addr(1))));
+        assert_eq!(b.recv(&mut buf).unwrap(), None);
+        assert_eq!(a.recv(&mut buf).unwrap(), None);
+    }
+
+    #[test]
+    fn test_addresses_are_exclusive_until_dropped() {
+        let network = LoopbackNetwork::new();
+        let taken = network.bind(addr(1)).unwrap();
+        assert!(network.bind(addr(1)).is_err());
+
+        let any = network.bind(addr(0)).unwrap();
+        assert_ne!(any.local_addr().unwrap().port(), 0);
+
+        drop(taken);
+        assert!(network.bind(addr(1)).is_ok());
+    }
+}
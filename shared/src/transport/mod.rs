@@ -0,0 +1,40 @@
+mod clock;
+mod loopback;
+mod simulated;
+mod udp;
+
+pub use clock::{Clock, ManualClock, SystemClock};
+pub use loopback::{LoopbackNetwork, LoopbackTransport};
+pub use simulated::{NetConditions, SimulatedTransport};
+pub use udp::UdpTransport;
+
+use std::io;
+use std::net::SocketAddr;
+
+/// Unreliable datagrams between addresses, the way the game uses UDP. The server and the
+/// client only talk through this, so they run the same over a real socket, in memory or
+/// over a simulated bad network.
+pub trait Transport: Send + Sync {
+    /// Send one datagram to `addr` and return its size. Like UDP, a datagram to nobody is
+    /// not an error.
+    fn send(&self, bytes: &[u8], addr: SocketAddr) -> io::Result<usize>;
+
+    /// Next datagram and who sent it, or `None` if nothing is waiting. Never blocks.
+    fn recv(&self, buf: &mut [u8]) -> io::Result<Option<(usize, SocketAddr)>>;
+
+    fn local_addr(&self) -> io::Result<SocketAddr>;
+}
+
+impl<T: Transport + ?Sized> Transport for Box<T> {
+    fn send(&self, bytes: &[u8], addr: SocketAddr) -> io::Result<usize> {
+        (**self).send(bytes, addr)
+    }
+
+    fn recv(&self, buf: &mut [u8]) -> io::Result<Option<(usize, SocketAddr)>> {
+        (**self).recv(buf)
+    }
+
+    fn local_addr(&self) -> io::Result<SocketAddr> {
+        (**self).local_addr()
+    }
+}
@@ -0,0 +1,261 @@
+use std::io;
+use std::net::SocketAddr;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use super::{Clock, SystemClock, Transport};
+
+/// Latency and loss of a simulated network, the same in both directions
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct NetConditions {
+    /// Round trip time, half of it spent in each direction
+    pub rtt: Duration,
+    /// Up to this much extra delay per datagram, so datagrams can arrive out of order
+    pub jitter: Duration,
+    /// Fraction of datagrams dropped, from 0 to 1
+    pub loss: f32,
+}
+
+struct Delayed {
+    deliver_at: u64,
+    /// Order the datagram entered in, to keep ones due at the same time in order
+    order: u64,
+    /// Destination when outgoing, sender when incoming
+    addr: SocketAddr,
+    bytes: Vec<u8>,
+}
+
+struct Simulation {
+    conditions: NetConditions,
+    rng: u64,
+    entered: u64,
+    dropped: u64,
+    outgoing: Vec<Delayed>,
+    incoming: Vec<Delayed>,
+}
+
+impl Simulation {
+    /// When a datagram entering now should come out, `None` if it is lost
+    fn schedule(&mut self, now_micros: u64, addr: SocketAddr, bytes: Vec<u8>) -> Option<Delayed> {
+        self.entered += 1;
+        if self.next_unit() < self.conditions.loss {
+            self.dropped += 1;
+            return None;
+        }
+        let jitter = self.conditions.jitter.as_micros() as f32 * self.next_unit();
+        Some(Delayed {
+            deliver_at: now_micros + self.conditions.rtt.as_micros() as u64 / 2 + jitter as u64,
+            order: self.entered,
+            addr,
+            bytes,
+        })
+    }
+
+    /// Uniform in [0, 1)
+    fn next_unit(&mut self) -> f32 {
+        self.rng ^= self.rng << 13;
+        self.rng ^= self.rng >> 7;
+        self.rng ^= self.rng << 17;
+        (self.rng >> 40) as f32 / (1u64 << 24) as f32
+    }
+}
+
+/// Take the datagrams in `queue` that are due, earliest first
+fn take_due(queue: &mut Vec<Delayed>, now_micros: u64) -> Vec<Delayed> {
+    let (mut due, waiting) = std::mem::take(queue)
+        .into_iter()
+        .partition(|delayed: &Delayed| delayed.deliver_at <= now_micros);
+    *queue = waiting;
+    due.sort_by_key(|delayed| (delayed.deliver_at, delayed.order));
+    due
+}
+
+/// Wraps a transport in a bad network: every datagram in either direction is held back
+/// for half the round trip plus jitter, or dropped. Wrapping one end is enough for the
+/// whole round trip. Loss and jitter come from a seeded generator, so on a `ManualClock`
+/// a run is the same every time.
+///
+/// Nothing runs in the background, held back datagrams move on whenever the transport is
+/// used or `pump`ed.
+pub struct SimulatedTransport<T: Transport, C: Clock = SystemClock> {
+    inner: T,
+    clock: C,
+    simulation: Mutex<Simulation>,
+}
+
+impl<T: Transport> SimulatedTransport<T> {
+    pub fn new(inner: T, conditions: NetConditions, seed: u64) -> Self {
+        SimulatedTransport::with_clock(inner, SystemClock, conditions, seed)
+    }
+}
+
+impl<T: Transport, C: Clock> SimulatedTransport<T, C> {
+    pub fn with_clock(inner: T, clock: C, conditions: NetConditions, seed: u64) -> Self {
+        SimulatedTransport {
+            inner,
+            clock,
+            simulation: Mutex::new(Simulation {
+                conditions,
+                // xorshift gets stuck on zero
+                rng: seed.wrapping_mul(0x9e37_79b9_7f4a_7c15) | 1,
+                entered: 0,
+                dropped: 0,
+                outgoing: Vec::new(),
+                incoming: Vec::new(),
+            }),
+        }
+    }
+
+    pub fn conditions(&self) -> NetConditions {
+        self.simulation.lock().unwrap().conditions
+    }
+
+    /// Applies to datagrams from now on, ones already held back keep their delay
+    pub fn set_conditions(&self, conditions: NetConditions) {
+        self.simulation.lock().unwrap().conditions = conditions;
+    }
+
+    /// Datagrams lost so far, in both directions
+    pub fn dropped(&self) -> u64 {
+        self.simulation.lock().unwrap().dropped
+    }
+
+    pub fn inner(&self) -> &T {
+        &self.inner
+    }
+
+    /// Take in what arrived on the inner transport and send out what is due
+    pub fn pump(&self) -> io::Result<()> {
+        let now_micros = self.clock.now_micros();
+        let mut simulation = self.simulation.lock().unwrap();
+
+        let mut buf = [0u8; 2048];
+        while let Some((amt, from)) = self.inner.recv(&mut buf)? {
+            if let Some(delayed) = simulation.schedule(now_micros, from, buf[..amt].to_vec()) {
+                simulation.incoming.push(delayed);
+            }
+        }
+
+        for delayed in take_due(&mut simulation.outgoing, now_micros) {
+            self.inner.send(&delayed.bytes, delayed.addr)?;
+        }
+        Ok(())
+    }
+}
+
+impl<T: Transport, C: Clock> Transport for SimulatedTransport<T, C> {
+    /// Always succeeds once queued, a failed send surfaces from a later call
+    fn send(&self, bytes: &[u8], addr: SocketAddr) -> io::Result<usize> {
+        self.pump()?;
+        let now_micros = self.clock.now_micros();
+        let mut simulation = self.simulation.lock().unwrap();
+        if let Some(delayed) = simulation.schedule(now_micros, addr, bytes.to_vec()) {
+            simulation.outgoing.push(delayed);
+        }
+        Ok(bytes.len())
+    }
+
+    fn recv(&self, buf: &mut [u8]) -> io::Result<Option<(usize, SocketAddr)>> {
+        self.pump()?;
+        let now_micros = self.clock.now_micros();
+        let mut simulation = self.simulation.lock().unwrap();
+        let next = simulation
+            .incoming
+            .iter()
+            .enumerate()
+            .filter(|(_, delayed)| delayed.deliver_at <= now_micros)
+            .min_by_key(|(_, delayed)| (delayed.deliver_at, delayed.order))
+            .map(|(index, _)| index);
+        let Some(index) = next else {
+            return Ok(None);
+        };
+
+        let delayed = simulation.incoming.remove(index);
+        let amt = delayed.bytes.len().min(buf.len());
+        buf[..amt].copy_from_slice(&delayed.bytes[..amt]);
+        Ok(Some((amt, delayed.addr)))
+    }
+
+    fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.inner.local_addr()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transport::{LoopbackNetwork, ManualClock};
+
+    fn addr(port: u16) -> SocketAddr {
+        SocketAddr::from(([10, 0, 0, 1], port))
+    }
+
+    #[test]
+    fn test_each_direction_takes_half_the_rtt() {
+        let network = LoopbackNetwork::new();
+        let clock = ManualClock::new(0);
+        let conditions = NetConditions {
+            rtt: Duration::from_millis(100),
+            ..NetConditions::default()
+        };
+        let slow = SimulatedTransport::with_clock(
+            network.bind(addr(1)).unwrap(),
+            clock.clone(),
+            conditions,
+            1,
+        );
+        let other = network.bind(addr(2)).unwrap();
+        let mut buf = [0u8; 16];
+
+        slow.send(&[1], addr(2)).unwrap();
+        other.send(&[2], addr(1)).unwrap();
+        assert_eq!(slow.recv(&mut buf).unwrap(), None);
+
+        clock.advance(49_999);
+        slow.pump().unwrap();
+        assert_eq!(other.recv(&mut buf).unwrap(), None);
+        assert_eq!(slow.recv(&mut buf).unwrap(), None);
+
+        clock.advance(1);
+        slow.pump().unwrap();
+        assert_eq!(other.recv(&mut buf).unwrap(), Some((1, addr(1))));
+        assert_eq!(buf[0], 1);
+        assert_eq!(slow.recv(&mut buf).unwrap(), Some((1, addr(2))));
+        assert_eq!(buf[0], 2);
+    }
+
+    #[test]
+    fn test_loss_is_seeded() {
+        let conditions = NetConditions {
+            loss: 0.25,
+            ..NetConditions::default()
+        };
+        let run = |seed| {
+            let network = LoopbackNetwork::new();
+            let lossy = SimulatedTransport::with_clock(
+                network.bind(addr(1)).unwrap(),
+                ManualClock::new(0),
+                conditions,
+                seed,
+            );
+            let other = network.bind(addr(2)).unwrap();
+            for i in 0..1000 {
+                lossy.send(&[i as u8], addr(2)).unwrap();
+            }
+            lossy.pump().unwrap();
+
+            let mut buf = [0u8; 16];
+            let mut received = Vec::new();
+            while let Some((_, _)) = other.recv(&mut buf).unwrap() {
+                received.push(buf[0]);
+            }
+            (lossy.dropped(), received)
+        };
+
+        let (dropped, received) = run(7);
+        assert!((200..300).contains(&dropped), "dropped {}", dropped);
+        assert_eq!(received.len() as u64 + dropped, 1000);
+        assert_eq!(run(7), (dropped, received.clone()));
+        assert_ne!(run(8).1, received, "Another seed drops other datagrams");
+    }
+}